@@ -0,0 +1,182 @@
+use std::borrow::Cow;
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{ensure, Error, Result};
+use serde::{
+    de::{Deserializer, Error as _},
+    Deserialize, Serialize, Serializer,
+};
+use time::{Date, Month};
+
+/// A date which is possibly only known up to its year or month
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PartialDate {
+    Year(i32),
+    YearMonth(i32, Month),
+    Full(Date),
+}
+
+impl PartialDate {
+    /// Yields the first day of the denoted period, e.g. January 1st for dates only known up to their year.
+    pub fn first_day(self) -> Option<Date> {
+        match self {
+            Self::Year(year) => Date::from_calendar_date(year, Month::January, 1).ok(),
+            Self::YearMonth(year, month) => Date::from_calendar_date(year, month, 1).ok(),
+            Self::Full(date) => Some(date),
+        }
+    }
+}
+
+impl From<Date> for PartialDate {
+    fn from(val: Date) -> Self {
+        Self::Full(val)
+    }
+}
+
+impl FromStr for PartialDate {
+    type Err = Error;
+
+    /// Parses `YYYY`, `YYYY-MM` and `YYYY-MM-DD` using either `-` or `/` as separator
+    /// while ignoring the time of day of ISO 8601 timestamps.
+    fn from_str(val: &str) -> Result<Self> {
+        let val = val.trim();
+        let val = val.split(['T', ' ']).next().unwrap();
+
+        let mut parts = val.split(['-', '/']);
+
+        let year = parts.next().unwrap();
+        ensure!(year.len() == 4, "Invalid year {year}");
+        let year = year.parse()?;
+
+        let val = match (parts.next(), parts.next(), parts.next()) {
+            (None, _, _) => Self::Year(year),
+            (Some(month), None, _) => Self::YearMonth(year, month.parse::<u8>()?.try_into()?),
+            (Some(month), Some(day), None) => Self::Full(Date::from_calendar_date(
+                year,
+                month.parse::<u8>()?.try_into()?,
+                day.parse()?,
+            )?),
+            _ => return Err(Error::msg(format!("Invalid date {val}"))),
+        };
+
+        Ok(val)
+    }
+}
+
+impl fmt::Display for PartialDate {
+    /// Renders only the known components so that year-only dates do not appear more precise than they are.
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Year(year) => write!(fmt, "{year}"),
+            Self::YearMonth(year, month) => write!(fmt, "{year}-{:02}", *month as u8),
+            Self::Full(date) => write!(
+                fmt,
+                "{}-{:02}-{:02}",
+                date.year(),
+                date.month() as u8,
+                date.day()
+            ),
+        }
+    }
+}
+
+/// Period covered by the data of a dataset which might be open at either end
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub struct TimeRange {
+    pub start: Option<PartialDate>,
+    pub end: Option<PartialDate>,
+}
+
+impl TimeRange {
+    /// Yields `None` if neither start nor end are known.
+    pub fn new(start: Option<PartialDate>, end: Option<PartialDate>) -> Option<Self> {
+        if start.is_none() && end.is_none() {
+            return None;
+        }
+
+        Some(Self { start, end })
+    }
+}
+
+impl fmt::Display for TimeRange {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match (self.start, self.end) {
+            (Some(start), Some(end)) => write!(fmt, "{start} – {end}"),
+            (Some(start), None) => write!(fmt, "since {start}"),
+            (None, Some(end)) => write!(fmt, "until {end}"),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+impl Serialize for PartialDate {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for PartialDate {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let val = Cow::<str>::deserialize(deserializer)?;
+
+        val.parse().map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_partial_dates() {
+        assert_eq!(
+            "2020".parse::<PartialDate>().unwrap(),
+            PartialDate::Year(2020)
+        );
+        assert_eq!(
+            "2020/05".parse::<PartialDate>().unwrap(),
+            PartialDate::YearMonth(2020, Month::May)
+        );
+        assert_eq!(
+            "2020-05-17T12:00:00Z".parse::<PartialDate>().unwrap(),
+            PartialDate::Full(Date::from_calendar_date(2020, Month::May, 17).unwrap())
+        );
+
+        "20".parse::<PartialDate>().unwrap_err();
+        "2020-13".parse::<PartialDate>().unwrap_err();
+        "2020-01-02-03".parse::<PartialDate>().unwrap_err();
+    }
+
+    #[test]
+    fn display_only_known_components() {
+        assert_eq!(PartialDate::Year(2020).to_string(), "2020");
+        assert_eq!(
+            PartialDate::YearMonth(2020, Month::May).to_string(),
+            "2020-05"
+        );
+    }
+
+    #[test]
+    fn time_ranges_might_be_open() {
+        assert_eq!(TimeRange::new(None, None), None);
+        assert_eq!(
+            TimeRange::new(Some(PartialDate::Year(2010)), Some(PartialDate::Year(2020)))
+                .unwrap()
+                .to_string(),
+            "2010 – 2020"
+        );
+        assert_eq!(
+            TimeRange::new(Some(PartialDate::Year(2010)), None)
+                .unwrap()
+                .to_string(),
+            "since 2010"
+        );
+    }
+}
@@ -0,0 +1,147 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Persistent identifier of a dataset assigned by its publisher, e.g. a DOI
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Identifier {
+    /// Digital Object Identifier without resolver prefix, e.g. `10.5676/DWD_CDC/ABC`
+    Doi(String),
+    /// Uniform Resource Name including its scheme, e.g. `urn:nbn:de:0221-2019010417060`
+    Urn(String),
+    Other(String),
+}
+
+impl Identifier {
+    /// Parses DOIs given as bare names, `doi:` URIs or resolver URLs as well as URNs.
+    ///
+    /// Other non-empty values are kept verbatim.
+    pub fn parse(val: &str) -> Option<Self> {
+        let val = val.trim();
+
+        if val.is_empty() {
+            return None;
+        }
+
+        let lowercase = val.to_ascii_lowercase();
+
+        for prefix in [
+            "doi:",
+            "https://doi.org/",
+            "http://doi.org/",
+            "https://dx.doi.org/",
+            "http://dx.doi.org/",
+        ] {
+            if lowercase.starts_with(prefix) {
+                return Some(Self::Doi(val[prefix.len()..].trim().to_owned()));
+            }
+        }
+
+        if is_doi(val) {
+            return Some(Self::Doi(val.to_owned()));
+        }
+
+        if lowercase.starts_with("urn:") {
+            return Some(Self::Urn(val.to_owned()));
+        }
+
+        Some(Self::Other(val.to_owned()))
+    }
+
+    /// Whether the identifier is persistent, i.e. a DOI or URN
+    pub fn is_persistent(&self) -> bool {
+        matches!(self, Self::Doi(_) | Self::Urn(_))
+    }
+
+    /// Normalized form under which the identifier is indexed as DOIs and URNs are case-insensitive
+    pub fn term(&self) -> String {
+        match self {
+            Self::Doi(doi) => format!("doi:{}", doi.to_lowercase()),
+            Self::Urn(urn) => urn.to_lowercase(),
+            Self::Other(val) => val.clone(),
+        }
+    }
+
+    /// Link to a resolver for the identifier if available
+    pub fn url(&self) -> Option<String> {
+        match self {
+            Self::Doi(doi) => Some(format!("https://doi.org/{doi}")),
+            Self::Urn(urn) if urn.to_ascii_lowercase().starts_with("urn:nbn:") => {
+                Some(format!("https://nbn-resolving.org/{urn}"))
+            }
+            Self::Urn(_) => None,
+            Self::Other(val) if val.starts_with("https://") || val.starts_with("http://") => {
+                Some(val.clone())
+            }
+            Self::Other(_) => None,
+        }
+    }
+}
+
+/// Bare DOIs consist of a registrant code like `10.5676` and a suffix separated by a slash.
+fn is_doi(val: &str) -> bool {
+    let Some((prefix, suffix)) = val.split_once('/') else {
+        return false;
+    };
+
+    let Some(registrant) = prefix.strip_prefix("10.") else {
+        return false;
+    };
+
+    !registrant.is_empty()
+        && registrant
+            .chars()
+            .all(|char| char.is_ascii_digit() || char == '.')
+        && !suffix.is_empty()
+        && !suffix.contains(char::is_whitespace)
+}
+
+impl fmt::Display for Identifier {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Doi(doi) => write!(fmt, "DOI {doi}"),
+            Self::Urn(urn) => write!(fmt, "URN {urn}"),
+            Self::Other(val) => fmt.write_str(val),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_identifiers() {
+        let doi = Identifier::Doi("10.5676/DWD_CDC/ABC".to_owned());
+
+        assert_eq!(Identifier::parse("10.5676/DWD_CDC/ABC"), Some(doi.clone()));
+        assert_eq!(
+            Identifier::parse("doi:10.5676/DWD_CDC/ABC"),
+            Some(doi.clone())
+        );
+        assert_eq!(
+            Identifier::parse(" https://doi.org/10.5676/DWD_CDC/ABC"),
+            Some(doi.clone())
+        );
+        assert_eq!(
+            Identifier::parse("DOI:10.5676/dwd_cdc/abc").unwrap().term(),
+            doi.term()
+        );
+
+        assert_eq!(
+            Identifier::parse("urn:nbn:de:0221-2019010417060"),
+            Some(Identifier::Urn("urn:nbn:de:0221-2019010417060".to_owned()))
+        );
+        assert_eq!(
+            Identifier::parse("https://registry.gdi-de.org/id/de.bund.uba/1"),
+            Some(Identifier::Other(
+                "https://registry.gdi-de.org/id/de.bund.uba/1".to_owned()
+            ))
+        );
+        assert_eq!(
+            Identifier::parse("10.5mg/l"),
+            Some(Identifier::Other("10.5mg/l".to_owned()))
+        );
+        assert_eq!(Identifier::parse("  "), None);
+    }
+}
@@ -0,0 +1,44 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Language of the title and description of a dataset
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Language {
+    German,
+    English,
+}
+
+impl Language {
+    /// Parses the language codes and labels used by metadata standards, e.g. `de` or `ger` as per ISO 639
+    /// or EU vocabulary URIs like `http://publications.europa.eu/resource/authority/language/DEU`.
+    pub fn parse(val: &str) -> Option<Self> {
+        let val = val.trim();
+        let val = val.rsplit('/').next().unwrap_or(val).to_lowercase();
+
+        match &*val {
+            "de" | "deu" | "ger" | "german" | "deutsch" => Some(Self::German),
+            "en" | "eng" | "english" | "englisch" => Some(Self::English),
+            _ => None,
+        }
+    }
+
+    /// Two-letter code as per ISO 639-1, e.g. for the `lang` attribute in HTML
+    pub fn code(self) -> &'static str {
+        match self {
+            Self::German => "de",
+            Self::English => "en",
+        }
+    }
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let val = match self {
+            Self::German => "Deutsch",
+            Self::English => "Englisch",
+        };
+
+        fmt.write_str(val)
+    }
+}
@@ -1,3 +1,4 @@
+use std::borrow::Cow;
 use std::fmt;
 
 use hashbrown::HashMap;
@@ -55,10 +56,10 @@ impl License {
         Some(val)
     }
 
-    pub fn facet(&self) -> &[&str] {
-        match self {
+    pub fn facet(&self) -> Vec<Cow<'static, str>> {
+        let path: &[&str] = match self {
             Self::Unknown => &["unknown"],
-            Self::Other(_val) => &["other"],
+            Self::Other(val) => return vec!["other".into(), normalize_other(val).into()],
             Self::DlDeBy20 => &["open", "dl-de", "by", "2.0"],
             Self::DlDeZero20 => &["open", "dl-de", "zero", "2.0"],
             Self::CcBy40 => &["open", "cc", "by", "4.0"],
@@ -70,8 +71,69 @@ impl License {
             Self::DorisBfs => &["open", "doris-bfs"],
             Self::GeoNutz20130319 => &["open", "geoNutz", "20130319"],
             Self::GeoNutz20131001 => &["open", "geoNutz", "20131001"],
+        };
+
+        path.iter().map(|component| (*component).into()).collect()
+    }
+
+    /// Yields a normalized identifier for unrecognized licenses
+    ///
+    /// This groups e.g. `CC BY-NC 3.0 DE` and `http://creativecommons.org/licenses/by-nc/3.0/de/` as `cc-by-nc-3.0-de`.
+    pub fn normalized_other(&self) -> Option<String> {
+        match self {
+            Self::Other(val) => Some(normalize_other(val)),
+            _ => None,
+        }
+    }
+}
+
+fn normalize_other(val: &str) -> String {
+    let val = val.trim().to_lowercase();
+
+    let val = match val
+        .strip_prefix("https://")
+        .or_else(|| val.strip_prefix("http://"))
+    {
+        Some(url) => {
+            let url = url.strip_prefix("www.").unwrap_or(url);
+            let (host, path) = url.split_once('/').unwrap_or((url, ""));
+
+            let mut components = path
+                .split('/')
+                .filter(|component| {
+                    !component.is_empty()
+                        && !matches!(*component, "def" | "licenses" | "license" | "legalcode")
+                })
+                .collect::<Vec<_>>();
+
+            if host.starts_with("creativecommons.org") {
+                components.insert(0, "cc");
+            }
+
+            if components.is_empty() {
+                host.to_owned()
+            } else {
+                components.join("-")
+            }
         }
+        None => val,
+    };
+
+    let mut normalized = String::with_capacity(val.len());
+
+    for char in val.chars() {
+        if char.is_alphanumeric() || char == '.' || char == '+' {
+            normalized.push(char);
+        } else if !normalized.is_empty() && !normalized.ends_with('-') {
+            normalized.push('-');
+        }
+    }
+
+    while normalized.ends_with(['-', '.']) {
+        normalized.pop();
     }
+
+    normalized
 }
 
 impl From<&'_ str> for License {
@@ -191,3 +253,30 @@ impl fmt::Display for License {
         fmt.write_str(val)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_other_text() {
+        assert_eq!(normalize_other(" CC BY-NC 3.0 DE "), "cc-by-nc-3.0-de");
+        assert_eq!(normalize_other("Creative Commons"), "creative-commons");
+    }
+
+    #[test]
+    fn normalize_other_urls() {
+        assert_eq!(
+            normalize_other("http://creativecommons.org/licenses/by-nc/3.0/de/"),
+            "cc-by-nc-3.0-de"
+        );
+        assert_eq!(
+            normalize_other("https://www.example.org/license/"),
+            "example.org"
+        );
+        assert_eq!(
+            normalize_other("http://dcat-ap.de/def/licenses/other-closed"),
+            "other-closed"
+        );
+    }
+}
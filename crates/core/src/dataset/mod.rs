@@ -0,0 +1,416 @@
+mod contact;
+mod date;
+mod identifier;
+mod language;
+mod license;
+mod publisher;
+mod relation;
+mod reporting_obligation;
+mod resource;
+mod summary;
+mod tag;
+mod theme;
+mod update_frequency;
+
+use std::io::Read;
+
+use anyhow::{Context, Result};
+use bincode::{deserialize, serialize};
+use cap_std::fs::File;
+use serde::{Deserialize, Serialize};
+use smallvec::SmallVec;
+use string_cache::DefaultAtom;
+use time::Date;
+use tokio::{fs::File as AsyncFile, io::AsyncWriteExt};
+
+pub use contact::Contact;
+pub use date::{PartialDate, TimeRange};
+pub use identifier::Identifier;
+pub use language::Language;
+pub use license::License;
+pub use publisher::Publisher;
+pub use relation::{Relation, Target as RelationTarget, Type as RelationType};
+pub use reporting_obligation::ReportingObligation;
+pub use resource::{LinkCheck, Resource, Type as ResourceType};
+pub use tag::Tag;
+pub use theme::Theme;
+pub use update_frequency::UpdateFrequency;
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Dataset {
+    pub title: String,
+    pub description: Option<String>,
+    /// Extract of overly long descriptions shown in search results
+    pub summary: Option<String>,
+    pub comment: Option<String>,
+    pub provenance: DefaultAtom,
+    /// Organisation which published the dataset as opposed to the portal it was harvested from
+    pub publisher: Option<Publisher>,
+    pub license: License,
+    /// Citations of the legal mandates under which the data is collected, e.g. `§ 44 BImSchG`
+    pub legal_basis: Vec<String>,
+    /// Entries of the EEA Reporting Obligations Database the dataset contributes to, e.g. due to the directives it is tagged with
+    pub reporting_obligations: Vec<ReportingObligation>,
+    pub contacts: Vec<Contact>,
+    pub tags: Vec<Tag>,
+    /// Themes from a controlled vocabulary as opposed to the free-form tags
+    pub themes: Vec<Theme>,
+    pub region: Option<String>,
+    /// Language of title and description, either given by the source or detected when harvesting
+    pub language: Option<Language>,
+    /// Bounding box of the spatial extent as west, south, east and north in degrees (WGS 84)
+    pub bbox: Option<[f64; 4]>,
+    /// Period covered by the data, e.g. of a measurement series
+    pub time_range: Option<TimeRange>,
+    pub issued: Option<PartialDate>,
+    /// Date of the last modification as given by the source, as opposed to when it was last harvested
+    pub modified: Option<PartialDate>,
+    pub update_frequency: Option<UpdateFrequency>,
+    pub last_checked: Option<PartialDate>,
+    /// Date on which the dataset was harvested for the first time
+    pub first_seen: Option<PartialDate>,
+    /// Persistent identifiers like DOIs or URNs assigned by the publisher
+    pub identifiers: SmallVec<[Identifier; 1]>,
+    /// Other datasets this one is part or a version of and vice versa
+    pub relations: Vec<Relation>,
+    pub source_url: String,
+    pub resources: SmallVec<[Resource; 4]>,
+}
+
+/// Previously deployed version of the above [`Dataset`] type.
+///
+/// It will be updated when a new harvester has been deployed. Feature branches should only modify [`Dataset`] and the mapping between both types defined by [`Dataset::read`].
+#[derive(Debug, Deserialize, Serialize)]
+struct OldDataset {
+    pub title: String,
+    pub description: Option<String>,
+    pub comment: Option<String>,
+    pub provenance: DefaultAtom,
+    pub license: License,
+    pub contacts: Vec<Contact>,
+    pub tags: Vec<Tag>,
+    pub region: Option<String>,
+    pub issued: Option<Date>,
+    pub last_checked: Option<Date>,
+    pub source_url: String,
+    pub resources: SmallVec<[OldResource; 4]>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct OldResource {
+    pub r#type: ResourceType,
+    pub url: String,
+}
+
+/// Layout in which a stored dataset was decoded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Schema {
+    /// The layout of the above [`Dataset`] type
+    Current,
+    /// The layout of the previously deployed version
+    Old,
+}
+
+impl Dataset {
+    pub fn read(mut file: File) -> Result<Self> {
+        let mut buf = Vec::new();
+        file.read_to_end(&mut buf)?;
+
+        let (val, _schema) = Self::decode(&buf)?;
+
+        Ok(val)
+    }
+
+    /// Decodes a stored dataset, falling back to the layout of the previously deployed version.
+    pub fn decode(buf: &[u8]) -> Result<(Self, Schema)> {
+        let val = match deserialize::<Dataset>(buf) {
+            Ok(val) => (val, Schema::Current),
+            Err(err) => {
+                let old_val = deserialize::<OldDataset>(buf)
+                    .map_err(|_old_err| err)
+                    .context("Failed to deserialize dataset")?;
+
+                let mut val = Self {
+                    title: old_val.title,
+                    description: old_val.description,
+                    summary: None,
+                    comment: old_val.comment,
+                    provenance: old_val.provenance,
+                    publisher: None,
+                    license: old_val.license,
+                    legal_basis: Vec::new(),
+                    reporting_obligations: Vec::new(),
+                    contacts: old_val.contacts,
+                    tags: old_val.tags,
+                    themes: Vec::new(),
+                    region: old_val.region,
+                    language: None,
+                    bbox: None,
+                    time_range: None,
+                    issued: old_val.issued.map(Into::into),
+                    modified: None,
+                    update_frequency: None,
+                    last_checked: old_val.last_checked.map(Into::into),
+                    first_seen: None,
+                    identifiers: SmallVec::new(),
+                    relations: Vec::new(),
+                    source_url: old_val.source_url,
+                    resources: old_val
+                        .resources
+                        .into_iter()
+                        .map(|resource| Resource {
+                            r#type: resource.r#type,
+                            url: resource.url,
+                            title: None,
+                            description: None,
+                            license: None,
+                            content_type: None,
+                            format: None,
+                            content_length: None,
+                            checksum: None,
+                            link: None,
+                        })
+                        .collect(),
+                };
+
+                val.summarize();
+
+                (val, Schema::Old)
+            }
+        };
+
+        Ok(val)
+    }
+
+    /// Decodes only title and description which lead all layouts, e.g. to identify datasets which cannot be decoded completely.
+    pub fn decode_partially(buf: &[u8]) -> Option<(String, Option<String>)> {
+        deserialize(buf).ok()
+    }
+
+    /// Derives the license of the whole dataset if it is unknown but all resources share the same license.
+    pub fn summarize_license(&mut self) {
+        if self.license != License::Unknown {
+            return;
+        }
+
+        let mut licenses = self
+            .resources
+            .iter()
+            .map(|resource| resource.license.as_ref());
+
+        if let Some(Some(first)) = licenses.next() {
+            if licenses.all(|license| license == Some(first)) {
+                self.license = first.clone();
+            }
+        }
+    }
+
+    /// Yields whether all checked resources were available or `None` if none were checked.
+    pub fn links_available(&self) -> Option<bool> {
+        self.resources
+            .iter()
+            .filter_map(|resource| resource.link.as_ref())
+            .fold(None, |available, link| {
+                Some(available.unwrap_or(true) && link.available())
+            })
+    }
+
+    /// Yields the distinct licenses of the dataset and its resources.
+    pub fn licenses(&self) -> Vec<&License> {
+        let mut licenses = vec![&self.license];
+
+        for resource in &self.resources {
+            if let Some(license) = &resource.license {
+                if !licenses.contains(&license) {
+                    licenses.push(license);
+                }
+            }
+        }
+
+        licenses
+    }
+
+    /// Merges another record of the same dataset into this one, e.g. if a source yields it on multiple pages.
+    ///
+    /// Resources, identifiers, relations, tags, themes, legal bases, reporting obligations and contacts are combined while the longer of both descriptions is kept
+    /// and a missing spatial or temporal extent, publisher, language, modification date or update frequency is taken from the other record.
+    pub fn merge(&mut self, other: Self) {
+        let len = |description: &Option<String>| description.as_ref().map_or(0, String::len);
+
+        if len(&other.description) > len(&self.description) {
+            self.description = other.description;
+            self.summarize();
+        }
+
+        for resource in other.resources {
+            if !self.resources.iter().any(|known| known.url == resource.url) {
+                self.resources.push(resource);
+            }
+        }
+
+        for identifier in other.identifiers {
+            if !self.identifiers.contains(&identifier) {
+                self.identifiers.push(identifier);
+            }
+        }
+
+        for relation in other.relations {
+            if !self.relations.contains(&relation) {
+                self.relations.push(relation);
+            }
+        }
+
+        for tag in other.tags {
+            if !self.tags.contains(&tag) {
+                self.tags.push(tag);
+            }
+        }
+
+        for theme in other.themes {
+            if !self.themes.contains(&theme) {
+                self.themes.push(theme);
+            }
+        }
+
+        for legal_basis in other.legal_basis {
+            if !self.legal_basis.contains(&legal_basis) {
+                self.legal_basis.push(legal_basis);
+            }
+        }
+
+        for reporting_obligation in other.reporting_obligations {
+            if !self.reporting_obligations.contains(&reporting_obligation) {
+                self.reporting_obligations.push(reporting_obligation);
+            }
+        }
+
+        for contact in other.contacts {
+            if !self.contacts.contains(&contact) {
+                self.contacts.push(contact);
+            }
+        }
+
+        if self.bbox.is_none() {
+            self.bbox = other.bbox;
+        }
+
+        if self.time_range.is_none() {
+            self.time_range = other.time_range;
+        }
+
+        if self.publisher.is_none() {
+            self.publisher = other.publisher;
+        }
+
+        if self.language.is_none() {
+            self.language = other.language;
+        }
+
+        if self.modified.is_none() {
+            self.modified = other.modified;
+        }
+
+        if self.update_frequency.is_none() {
+            self.update_frequency = other.update_frequency;
+        }
+    }
+
+    /// Updates the summary after the description was set or changed.
+    pub fn summarize(&mut self) {
+        self.summary = self.description.as_deref().and_then(summary::summarize);
+    }
+
+    /// Encodes the dataset using the layout of the above [`Dataset`] type.
+    pub fn encode(&self) -> Result<Vec<u8>> {
+        Ok(serialize(self)?)
+    }
+
+    pub async fn write(&self, file: File) -> Result<()> {
+        let buf = self.encode()?;
+
+        let mut file = AsyncFile::from_std(file.into_std());
+        file.write_all(&buf).await?;
+        // The write is performed in the background and might not have completed otherwise.
+        file.flush().await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use smallvec::smallvec;
+
+    fn dataset(description: Option<&str>, url: &str, tag: Tag, contact: &str) -> Dataset {
+        Dataset {
+            title: "Messwerte".to_owned(),
+            description: description.map(ToOwned::to_owned),
+            summary: None,
+            comment: None,
+            provenance: Default::default(),
+            publisher: None,
+            license: License::Unknown,
+            legal_basis: Vec::new(),
+            reporting_obligations: Vec::new(),
+            contacts: vec![Contact {
+                name: contact.to_owned(),
+                emails: SmallVec::new(),
+            }],
+            tags: vec![tag],
+            themes: Vec::new(),
+            region: None,
+            language: None,
+            bbox: None,
+            time_range: None,
+            issued: None,
+            modified: None,
+            update_frequency: None,
+            last_checked: None,
+            first_seen: None,
+            identifiers: SmallVec::new(),
+            relations: Vec::new(),
+            source_url: String::new(),
+            resources: smallvec![Resource::unknown(url.to_owned())],
+        }
+    }
+
+    #[test]
+    fn merge_duplicate_records() {
+        let mut dataset1 = dataset(Some("Pegel"), "https://example.org/a", Tag::Wrrl, "UBA");
+
+        dataset1.merge(dataset(
+            Some("Pegel der Elbe"),
+            "https://example.org/b",
+            Tag::Wrrl,
+            "BfG",
+        ));
+
+        dataset1.merge(dataset(
+            None,
+            "https://example.org/a",
+            "Pegel".into(),
+            "UBA",
+        ));
+
+        assert_eq!(dataset1.description.as_deref(), Some("Pegel der Elbe"));
+        assert_eq!(
+            dataset1
+                .resources
+                .iter()
+                .map(|resource| resource.url.as_str())
+                .collect::<Vec<_>>(),
+            ["https://example.org/a", "https://example.org/b"]
+        );
+        assert_eq!(dataset1.tags, [Tag::Wrrl, Tag::from("Pegel")]);
+        assert_eq!(
+            dataset1
+                .contacts
+                .iter()
+                .map(|contact| contact.name.as_str())
+                .collect::<Vec<_>>(),
+            ["UBA", "BfG"]
+        );
+    }
+}
@@ -0,0 +1,17 @@
+use serde::{Deserialize, Serialize};
+
+/// Organisation responsible for publishing a dataset, e.g. an authority
+///
+/// Distinct from the provenance which describes the portal from which the dataset was harvested.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Publisher {
+    pub name: String,
+    pub url: Option<String>,
+}
+
+impl Publisher {
+    /// Collapses whitespace so that spelling variants of the name yield the same facet.
+    pub fn facet(&self) -> String {
+        self.name.split_whitespace().collect::<Vec<_>>().join(" ")
+    }
+}
@@ -0,0 +1,72 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Relation of a dataset to another one, e.g. the series it belongs to
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Relation {
+    pub r#type: Type,
+    pub target: Target,
+}
+
+/// Kind of relation following the terms of Dublin Core
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Type {
+    /// `dct:isPartOf`, e.g. a single year of a series
+    IsPartOf,
+    /// `dct:hasPart`
+    HasPart,
+    /// `dct:isVersionOf`
+    IsVersionOf,
+    /// `dct:hasVersion`
+    HasVersion,
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let val = match self {
+            Self::IsPartOf => "Gehört zu",
+            Self::HasPart => "Teile",
+            Self::IsVersionOf => "Version von",
+            Self::HasVersion => "Versionen",
+        };
+
+        fmt.write_str(val)
+    }
+}
+
+/// Dataset referenced by a relation
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Target {
+    /// Dataset in our catalogue given by source name and ID
+    Dataset { source: String, id: String },
+    /// Dataset outside of our catalogue or whose ID is unknown
+    Url(String),
+}
+
+impl Target {
+    /// Considers values which do not look like an URL to be IDs of datasets of the given source.
+    pub fn parse(source: &str, val: &str) -> Option<Self> {
+        let val = val.trim();
+
+        if val.is_empty() {
+            None
+        } else if val.starts_with("https://") || val.starts_with("http://") {
+            Some(Self::Url(val.to_owned()))
+        } else {
+            Some(Self::Dataset {
+                source: source.to_owned(),
+                id: val.to_owned(),
+            })
+        }
+    }
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Dataset { source, id } => write!(fmt, "{id} ({source})"),
+            Self::Url(url) => fmt.write_str(url),
+        }
+    }
+}
@@ -0,0 +1,23 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Entry of the Reporting Obligations Database (ROD) maintained by the EEA which a dataset contributes to
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ReportingObligation {
+    /// Number of the obligation as used in the URLs of ROD
+    pub id: u32,
+    pub title: String,
+}
+
+impl ReportingObligation {
+    pub fn url(&self) -> String {
+        format!("https://rod.eionet.europa.eu/obligations/{}", self.id)
+    }
+}
+
+impl fmt::Display for ReportingObligation {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str(&self.title)
+    }
+}
@@ -0,0 +1,99 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::dataset::License;
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Resource {
+    pub r#type: Type,
+    pub url: String,
+    /// Human-readable name of this resource as given by the source
+    pub title: Option<String>,
+    /// Longer explanation of the contents of this resource as given by the source
+    pub description: Option<String>,
+    /// License of this resource if the source states one separately from the dataset
+    pub license: Option<License>,
+    /// Media type as reported by the server hosting this resource
+    pub content_type: Option<String>,
+    /// Format as given by the source, e.g. `WMS` or `XLSX`, which is more specific than the type
+    pub format: Option<String>,
+    /// Size in bytes as given by the source or reported by the server hosting this resource
+    pub content_length: Option<u64>,
+    /// Checksum of the contents as given by the source, e.g. an SHA-256 hash
+    pub checksum: Option<String>,
+    /// Outcome of the last check whether this resource can be retrieved
+    pub link: Option<LinkCheck>,
+}
+
+/// Outcome of checking whether the URL of a resource can be retrieved
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+pub struct LinkCheck {
+    /// HTTP status of the response if one was received at all
+    pub status: Option<u16>,
+}
+
+impl LinkCheck {
+    pub fn available(&self) -> bool {
+        matches!(self.status, Some(200..=299))
+    }
+}
+
+impl Resource {
+    pub fn unknown(url: String) -> Self {
+        Self {
+            r#type: Type::Unknown,
+            url,
+            title: None,
+            description: None,
+            license: None,
+            content_type: None,
+            format: None,
+            content_length: None,
+            checksum: None,
+            link: None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub enum Type {
+    Unknown,
+    Pdf,
+    Csv,
+    JsonLd,
+}
+
+impl From<&'_ str> for Type {
+    /// Maps format names and media types as commonly used by CKAN and DCAT.
+    fn from(val: &str) -> Self {
+        let val = val.trim();
+        let val = val.strip_prefix('.').unwrap_or(val);
+
+        if val.eq_ignore_ascii_case("pdf") || val.eq_ignore_ascii_case("application/pdf") {
+            Self::Pdf
+        } else if val.eq_ignore_ascii_case("csv") || val.eq_ignore_ascii_case("text/csv") {
+            Self::Csv
+        } else if val.eq_ignore_ascii_case("json-ld")
+            || val.eq_ignore_ascii_case("jsonld")
+            || val.eq_ignore_ascii_case("application/ld+json")
+        {
+            Self::JsonLd
+        } else {
+            Self::Unknown
+        }
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let val = match self {
+            Self::Unknown => "unbekannt",
+            Self::Pdf => "PDF",
+            Self::Csv => "CSV",
+            Self::JsonLd => "JSON-LD",
+        };
+
+        fmt.write_str(val)
+    }
+}
@@ -0,0 +1,164 @@
+use hashbrown::HashMap;
+
+/// Descriptions up to this length are not summarized.
+const MAX_LEN: usize = 600;
+
+/// Extracts the most representative sentences of overly long descriptions.
+///
+/// Sentences are scored by the average frequency of their words within the whole description
+/// with a bonus for leading sentences which usually state the topic.
+pub fn summarize(text: &str) -> Option<String> {
+    let text = text.trim();
+
+    if text.len() <= MAX_LEN {
+        return None;
+    }
+
+    let sentences = sentences(text);
+
+    let mut frequencies = HashMap::<String, usize>::new();
+
+    for sentence in &sentences {
+        for word in words(sentence) {
+            *frequencies.entry(word).or_default() += 1;
+        }
+    }
+
+    let mut scored = sentences
+        .iter()
+        .enumerate()
+        .map(|(index, sentence)| {
+            let (sum, count) = words(sentence).fold((0, 0), |(sum, count), word| {
+                (sum + frequencies[&word], count + 1)
+            });
+
+            let score = if count != 0 {
+                sum as f64 / count as f64
+            } else {
+                0.0
+            };
+
+            let bonus = 1.0 + 1.0 / (1 + index) as f64;
+
+            (index, score * bonus)
+        })
+        .collect::<Vec<_>>();
+
+    scored.sort_unstable_by(|(_, lhs), (_, rhs)| rhs.partial_cmp(lhs).unwrap());
+
+    let mut len = 0;
+    let mut selected = Vec::new();
+
+    for (index, _score) in scored {
+        let sentence = sentences[index];
+        let sentence_len = sentence.len() + 1;
+
+        // Repeated sentences would otherwise be favoured by their word frequencies.
+        let repeated = selected.iter().any(|&other| sentences[other] == sentence);
+
+        if !repeated && len + sentence_len <= MAX_LEN {
+            len += sentence_len;
+            selected.push(index);
+        }
+    }
+
+    if selected.is_empty() {
+        return Some(truncate(text));
+    }
+
+    selected.sort_unstable();
+
+    let summary = selected
+        .into_iter()
+        .map(|index| sentences[index])
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    Some(summary)
+}
+
+fn sentences(text: &str) -> Vec<&str> {
+    let mut sentences = Vec::new();
+    let mut start = 0;
+
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((pos, char)) = chars.next() {
+        let end = match char {
+            '.' | '!' | '?' => match chars.peek() {
+                Some((_, next)) if next.is_whitespace() => pos + char.len_utf8(),
+                _ => continue,
+            },
+            '\n' => pos,
+            _ => continue,
+        };
+
+        let sentence = text[start..end].trim();
+
+        if !sentence.is_empty() {
+            sentences.push(sentence);
+        }
+
+        start = end;
+    }
+
+    let sentence = text[start..].trim();
+
+    if !sentence.is_empty() {
+        sentences.push(sentence);
+    }
+
+    sentences
+}
+
+/// Yields the lower-cased words of a sentence, skipping short ones which are mostly stop words.
+fn words(sentence: &str) -> impl Iterator<Item = String> + '_ {
+    sentence
+        .split(|char: char| !char.is_alphanumeric())
+        .filter(|word| word.chars().count() > 3)
+        .map(|word| word.to_lowercase())
+}
+
+fn truncate(text: &str) -> String {
+    let mut end = MAX_LEN;
+
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+
+    let text = &text[..end];
+    let text = text
+        .rsplit_once(char::is_whitespace)
+        .map_or(text, |(text, _)| text);
+
+    format!("{}…", text.trim_end())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_descriptions_are_kept() {
+        assert_eq!(summarize("Messwerte der Wasserqualität."), None);
+    }
+
+    #[test]
+    fn representative_sentences_are_selected() {
+        let mut text = "Die Wasserqualität der Elbe wird an Messstellen überwacht.".to_owned();
+
+        for idx in 0..20 {
+            text.push_str(&format!(
+                " Zusatz{idx}a Zusatz{idx}b Zusatz{idx}c Zusatz{idx}d."
+            ));
+        }
+
+        text.push_str(" Die Messstellen der Elbe erfassen die Wasserqualität täglich.");
+
+        let summary = summarize(&text).unwrap();
+
+        assert!(summary.len() <= MAX_LEN);
+        assert!(summary.starts_with("Die Wasserqualität der Elbe wird an Messstellen überwacht."));
+        assert!(summary.ends_with("Die Messstellen der Elbe erfassen die Wasserqualität täglich."));
+    }
+}
@@ -0,0 +1,151 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Themes of datasets following the data theme vocabulary of DCAT-AP, extended by categories specific to umwelt.info
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum Theme {
+    Agri,
+    Econ,
+    Educ,
+    Ener,
+    Envi,
+    Gove,
+    Heal,
+    Intr,
+    Just,
+    Regi,
+    Soci,
+    Tech,
+    Tran,
+    Wasser,
+    Luft,
+    Boden,
+    Laerm,
+}
+
+impl Theme {
+    /// Parses the codes of the DCAT-AP data themes like `ENVI`, also given as URIs like
+    /// `http://publications.europa.eu/resource/authority/data-theme/ENVI`, and the names of our own categories.
+    pub fn parse(val: &str) -> Option<Self> {
+        let val = val.trim();
+        let val = val.rsplit('/').next().unwrap_or(val).to_lowercase();
+
+        let val = match &*val {
+            "agri" => Self::Agri,
+            "econ" => Self::Econ,
+            "educ" => Self::Educ,
+            "ener" => Self::Ener,
+            "envi" => Self::Envi,
+            "gove" => Self::Gove,
+            "heal" => Self::Heal,
+            "intr" => Self::Intr,
+            "just" => Self::Just,
+            "regi" => Self::Regi,
+            "soci" => Self::Soci,
+            "tech" => Self::Tech,
+            "tran" => Self::Tran,
+            "wasser" => Self::Wasser,
+            "luft" => Self::Luft,
+            "boden" => Self::Boden,
+            "lärm" | "laerm" => Self::Laerm,
+            _ => return None,
+        };
+
+        Some(val)
+    }
+
+    /// Maps the values of `MD_TopicCategoryCode` defined by ISO 19115 onto themes.
+    pub fn from_iso_topic(topic: &str) -> &'static [Self] {
+        match topic.trim() {
+            "farming" => &[Self::Agri],
+            "biota" | "environment" | "elevation" => &[Self::Envi],
+            "boundaries"
+            | "imageryBaseMapsEarthCover"
+            | "location"
+            | "planningCadastre"
+            | "structure" => &[Self::Regi],
+            "climatologyMeteorologyAtmosphere" => &[Self::Envi, Self::Luft],
+            "economy" => &[Self::Econ],
+            "geoscientificInformation" => &[Self::Envi, Self::Boden],
+            "health" => &[Self::Heal],
+            "inlandWaters" | "oceans" => &[Self::Envi, Self::Wasser],
+            "intelligenceMilitary" => &[Self::Just],
+            "society" => &[Self::Soci],
+            "transportation" => &[Self::Tran],
+            "utilitiesCommunication" => &[Self::Ener],
+            _ => &[],
+        }
+    }
+
+    /// Yields the facet under which datasets are indexed, separating the DCAT-AP themes from our own categories.
+    pub fn facet(self) -> [&'static str; 2] {
+        match self {
+            Self::Agri => ["dcat", "agri"],
+            Self::Econ => ["dcat", "econ"],
+            Self::Educ => ["dcat", "educ"],
+            Self::Ener => ["dcat", "ener"],
+            Self::Envi => ["dcat", "envi"],
+            Self::Gove => ["dcat", "gove"],
+            Self::Heal => ["dcat", "heal"],
+            Self::Intr => ["dcat", "intr"],
+            Self::Just => ["dcat", "just"],
+            Self::Regi => ["dcat", "regi"],
+            Self::Soci => ["dcat", "soci"],
+            Self::Tech => ["dcat", "tech"],
+            Self::Tran => ["dcat", "tran"],
+            Self::Wasser => ["umwelt", "wasser"],
+            Self::Luft => ["umwelt", "luft"],
+            Self::Boden => ["umwelt", "boden"],
+            Self::Laerm => ["umwelt", "laerm"],
+        }
+    }
+}
+
+impl fmt::Display for Theme {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let val = match self {
+            Self::Agri => "Landwirtschaft, Fischerei, Forstwirtschaft und Nahrungsmittel",
+            Self::Econ => "Wirtschaft und Finanzen",
+            Self::Educ => "Bildung, Kultur und Sport",
+            Self::Ener => "Energie",
+            Self::Envi => "Umwelt",
+            Self::Gove => "Regierung und öffentlicher Sektor",
+            Self::Heal => "Gesundheit",
+            Self::Intr => "Internationale Themen",
+            Self::Just => "Justiz, Rechtssystem und öffentliche Sicherheit",
+            Self::Regi => "Regionen und Städte",
+            Self::Soci => "Bevölkerung und Gesellschaft",
+            Self::Tech => "Wissenschaft und Technologie",
+            Self::Tran => "Verkehr",
+            Self::Wasser => "Wasser",
+            Self::Luft => "Luft",
+            Self::Boden => "Boden",
+            Self::Laerm => "Lärm",
+        };
+
+        fmt.write_str(val)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_themes() {
+        assert_eq!(Theme::parse("envi"), Some(Theme::Envi));
+        assert_eq!(
+            Theme::parse("http://publications.europa.eu/resource/authority/data-theme/TRAN"),
+            Some(Theme::Tran)
+        );
+        assert_eq!(Theme::parse("Lärm"), Some(Theme::Laerm));
+        assert_eq!(Theme::parse("Verkehr"), None);
+
+        assert_eq!(
+            Theme::from_iso_topic("inlandWaters"),
+            [Theme::Envi, Theme::Wasser]
+        );
+        assert_eq!(Theme::from_iso_topic("unknown"), []);
+    }
+}
@@ -0,0 +1,118 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// How often a dataset is updated, coarsened to the next longer interval, e.g. biweekly updates are considered monthly
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum UpdateFrequency {
+    /// Continuously or at least hourly, e.g. measurement networks
+    Continuous,
+    Daily,
+    Weekly,
+    Monthly,
+    Quarterly,
+    Annual,
+    /// Every couple of years, e.g. periodic reports
+    Multiannual,
+    Irregular,
+    /// Never updated, e.g. one-off studies
+    Never,
+}
+
+impl UpdateFrequency {
+    /// Parses the codes of the frequency vocabulary used by DCAT-AP like `DAILY`, also given as URIs like
+    /// `http://publications.europa.eu/resource/authority/frequency/DAILY`, and the values of `MD_MaintenanceFrequencyCode` defined by ISO 19115.
+    ///
+    /// Unknown frequencies yield `None`.
+    pub fn parse(val: &str) -> Option<Self> {
+        let val = val.trim();
+        let val = val.rsplit('/').next().unwrap_or(val).to_lowercase();
+
+        let val = match &*val {
+            "cont" | "update_cont" | "continual" | "hourly" => Self::Continuous,
+            "daily" | "daily_2" => Self::Daily,
+            "weekly" | "weekly_2" | "weekly_3" | "semiweekly" | "three_times_a_week" => {
+                Self::Weekly
+            }
+            "biweekly"
+            | "fortnightly"
+            | "monthly"
+            | "monthly_2"
+            | "monthly_3"
+            | "semimonthly"
+            | "three_times_a_month" => Self::Monthly,
+            "bimonthly" | "quarterly" => Self::Quarterly,
+            "annual" | "annual_2" | "annual_3" | "annually" | "biannually" | "semiannual" => {
+                Self::Annual
+            }
+            "biennial" | "triennial" | "quadrennial" | "quinquennial" | "decennial"
+            | "bidecennial" | "tridecennial" => Self::Multiannual,
+            "irreg" | "irregular" | "asneeded" | "other" => Self::Irregular,
+            "never" | "notplanned" => Self::Never,
+            _ => return None,
+        };
+
+        Some(val)
+    }
+
+    /// Lowercase German label used as the search facet, e.g. `/nie` for one-off studies
+    pub fn facet(self) -> &'static str {
+        match self {
+            Self::Continuous => "kontinuierlich",
+            Self::Daily => "täglich",
+            Self::Weekly => "wöchentlich",
+            Self::Monthly => "monatlich",
+            Self::Quarterly => "vierteljährlich",
+            Self::Annual => "jährlich",
+            Self::Multiannual => "mehrjährlich",
+            Self::Irregular => "unregelmäßig",
+            Self::Never => "nie",
+        }
+    }
+}
+
+impl fmt::Display for UpdateFrequency {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let val = match self {
+            Self::Continuous => "Kontinuierlich",
+            Self::Daily => "Täglich",
+            Self::Weekly => "Wöchentlich",
+            Self::Monthly => "Monatlich",
+            Self::Quarterly => "Vierteljährlich",
+            Self::Annual => "Jährlich",
+            Self::Multiannual => "Mehrjährlich",
+            Self::Irregular => "Unregelmäßig",
+            Self::Never => "Keine Aktualisierung",
+        };
+
+        fmt.write_str(val)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_frequencies() {
+        assert_eq!(
+            UpdateFrequency::parse(
+                "http://publications.europa.eu/resource/authority/frequency/DAILY"
+            ),
+            Some(UpdateFrequency::Daily)
+        );
+        assert_eq!(
+            UpdateFrequency::parse("UPDATE_CONT"),
+            Some(UpdateFrequency::Continuous)
+        );
+        assert_eq!(
+            UpdateFrequency::parse("biannually"),
+            Some(UpdateFrequency::Annual)
+        );
+        assert_eq!(
+            UpdateFrequency::parse("notPlanned"),
+            Some(UpdateFrequency::Never)
+        );
+        assert_eq!(UpdateFrequency::parse("unknown"), None);
+    }
+}
@@ -0,0 +1,556 @@
+//! Detection of datasets harvested from multiple sources, e.g. from an aggregator like GovData as well as from the originating portal
+//!
+//! Datasets are considered duplicates if they reference the same non-empty set of resources after normalizing their URLs.
+//! The record of the most trusted source is chosen as the canonical one of each group.
+//!
+//! Near-duplicates which do not match exactly, e.g. due to mirrored file hosts, are scored by the similarity of their titles,
+//! resource URLs and dates of issue. Pairs scoring above a threshold are queued as [`Candidates`] for review by an administrator
+//! and those confirmed via [`Reviews`] are grouped like exact duplicates.
+use std::io::{Read, Write};
+
+use anyhow::Result;
+use cap_std::fs::Dir;
+use hashbrown::{HashMap, HashSet};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{from_str, to_vec_pretty};
+use time::Date;
+
+use crate::dataset::Dataset;
+
+/// Score above which a pair of records is queued for review
+const CANDIDATE_THRESHOLD: f64 = 0.75;
+
+/// Title words shared by more records are too common to efficiently find candidates.
+const MAX_BLOCK_SIZE: usize = 250;
+
+#[derive(Default)]
+pub struct Duplicates {
+    records: Vec<Record>,
+    groups: HashMap<String, Vec<usize>>,
+}
+
+struct Record {
+    source: String,
+    id: String,
+    trust: f64,
+    /// Normalized resource URLs if there are any
+    urls: Option<String>,
+    /// Distinct lowercase words of the title
+    words: Vec<String>,
+    /// Distinct hosts of the resource URLs
+    hosts: Vec<String>,
+    /// Distinct last path segments of the resource URLs, e.g. file names
+    segments: Vec<String>,
+    issued: Option<Date>,
+}
+
+impl Record {
+    fn key(&self) -> String {
+        format!("{}/{}", self.source, self.id)
+    }
+
+    /// Weighs the similarity of the titles highest as resource URLs and dates are often missing or differ between mirrors.
+    fn score(&self, other: &Self) -> f64 {
+        let title = jaccard(&self.words, &other.words);
+
+        let urls = 0.3 * jaccard(&self.hosts, &other.hosts)
+            + 0.7 * jaccard(&self.segments, &other.segments);
+
+        let issued = match (self.issued, other.issued) {
+            (Some(lhs), Some(rhs)) => {
+                let days = (lhs - rhs).whole_days().unsigned_abs().min(365);
+
+                1.0 - days as f64 / 365.0
+            }
+            _ => 0.5,
+        };
+
+        0.6 * title + 0.25 * urls + 0.15 * issued
+    }
+}
+
+impl Duplicates {
+    pub fn record(&mut self, source: &str, id: &str, dataset: &Dataset, trust: f64) {
+        let urls = dataset
+            .resources
+            .iter()
+            .map(|resource| normalize_url(&resource.url))
+            .filter(|url| !url.is_empty())
+            .collect();
+
+        let issued = dataset.issued.and_then(|issued| issued.first_day());
+
+        self.insert(source, id, trust, &dataset.title, urls, issued);
+    }
+
+    fn insert(
+        &mut self,
+        source: &str,
+        id: &str,
+        trust: f64,
+        title: &str,
+        mut urls: Vec<String>,
+        issued: Option<Date>,
+    ) {
+        urls.sort_unstable();
+        urls.dedup();
+
+        let mut hosts = Vec::new();
+        let mut segments = Vec::new();
+
+        for url in &urls {
+            let (host, path) = url.split_once('/').unwrap_or((url, ""));
+
+            hosts.push(host.to_owned());
+
+            if let Some(segment) = path
+                .rsplit('/')
+                .next()
+                .filter(|segment| !segment.is_empty())
+            {
+                segments.push(segment.to_owned());
+            }
+        }
+
+        let mut words = title
+            .split(|char: char| !char.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .map(|word| word.to_lowercase())
+            .collect::<Vec<_>>();
+
+        for vals in [&mut words, &mut hosts, &mut segments] {
+            vals.sort_unstable();
+            vals.dedup();
+        }
+
+        let urls = (!urls.is_empty()).then(|| urls.join(" "));
+
+        let index = self.records.len();
+
+        if let Some(urls) = &urls {
+            self.groups.entry(urls.clone()).or_default().push(index);
+        }
+
+        self.records.push(Record {
+            source: source.to_owned(),
+            id: id.to_owned(),
+            trust,
+            urls,
+            words,
+            hosts,
+            segments,
+            issued,
+        });
+    }
+
+    /// Yields pairs of records from different sources which are likely duplicates and have not been reviewed yet.
+    ///
+    /// Only records sharing a title word of at least four characters are compared.
+    pub fn candidates(&self, reviews: &Reviews) -> Candidates {
+        let mut blocks = HashMap::<&str, Vec<usize>>::new();
+
+        for (index, record) in self.records.iter().enumerate() {
+            for word in &record.words {
+                if word.chars().count() >= 4 {
+                    blocks.entry(word).or_default().push(index);
+                }
+            }
+        }
+
+        let reviewed = reviews
+            .confirmed
+            .iter()
+            .chain(&reviews.rejected)
+            .map(|(lhs, rhs)| (lhs.as_str(), rhs.as_str()))
+            .collect::<HashSet<_>>();
+
+        let mut compared = HashSet::new();
+        let mut pairs = Vec::new();
+
+        for block in blocks.values() {
+            if block.len() > MAX_BLOCK_SIZE {
+                continue;
+            }
+
+            for (pos, &lhs) in block.iter().enumerate() {
+                for &rhs in &block[pos + 1..] {
+                    let (lhs, rhs) = (&self.records[lhs], &self.records[rhs]);
+
+                    if lhs.source == rhs.source {
+                        continue;
+                    }
+
+                    // Exact duplicates are grouped without review.
+                    if lhs.urls.is_some() && lhs.urls == rhs.urls {
+                        continue;
+                    }
+
+                    let (lhs, rhs) = if lhs.key() < rhs.key() {
+                        (lhs, rhs)
+                    } else {
+                        (rhs, lhs)
+                    };
+
+                    let (lhs_key, rhs_key) = (lhs.key(), rhs.key());
+
+                    if reviewed.contains(&(lhs_key.as_str(), rhs_key.as_str())) {
+                        continue;
+                    }
+
+                    if !compared.insert((lhs_key.clone(), rhs_key.clone())) {
+                        continue;
+                    }
+
+                    let score = lhs.score(rhs);
+
+                    if score >= CANDIDATE_THRESHOLD {
+                        pairs.push(Candidate {
+                            lhs: lhs_key,
+                            rhs: rhs_key,
+                            score,
+                        });
+                    }
+                }
+            }
+        }
+
+        pairs.sort_unstable_by(|lhs, rhs| {
+            rhs.score
+                .total_cmp(&lhs.score)
+                .then_with(|| lhs.lhs.cmp(&rhs.lhs))
+                .then_with(|| lhs.rhs.cmp(&rhs.rhs))
+        });
+
+        Candidates { pairs }
+    }
+
+    /// Chooses the canonical record for each dataset which has duplicates in other sources or was confirmed as a duplicate.
+    pub fn canonical(self, reviews: &Reviews) -> Canonical {
+        let mut parents = (0..self.records.len()).collect::<Vec<_>>();
+
+        fn find(parents: &mut [usize], mut index: usize) -> usize {
+            while parents[index] != index {
+                parents[index] = parents[parents[index]];
+                index = parents[index];
+            }
+
+            index
+        }
+
+        let mut union = |lhs: usize, rhs: usize| {
+            let lhs = find(&mut parents, lhs);
+            let rhs = find(&mut parents, rhs);
+
+            parents[rhs] = lhs;
+        };
+
+        for records in self.groups.values() {
+            let source = &self.records[records[0]].source;
+
+            if records
+                .iter()
+                .all(|&record| self.records[record].source == *source)
+            {
+                continue;
+            }
+
+            for &record in &records[1..] {
+                union(records[0], record);
+            }
+        }
+
+        if !reviews.confirmed.is_empty() {
+            let indices = self
+                .records
+                .iter()
+                .enumerate()
+                .map(|(index, record)| (record.key(), index))
+                .collect::<HashMap<_, _>>();
+
+            for (lhs, rhs) in &reviews.confirmed {
+                if let (Some(&lhs), Some(&rhs)) = (indices.get(lhs), indices.get(rhs)) {
+                    union(lhs, rhs);
+                }
+            }
+        }
+
+        let mut groups = HashMap::<usize, Vec<&Record>>::new();
+
+        for index in 0..self.records.len() {
+            let root = find(&mut parents, index);
+
+            groups.entry(root).or_default().push(&self.records[index]);
+        }
+
+        let mut canonical = HashMap::new();
+
+        for (_, mut records) in groups {
+            if records.len() < 2 {
+                continue;
+            }
+
+            // Prefer the most trusted source and break ties deterministically.
+            records.sort_unstable_by(|lhs, rhs| {
+                rhs.trust
+                    .total_cmp(&lhs.trust)
+                    .then_with(|| lhs.source.cmp(&rhs.source))
+                    .then_with(|| lhs.id.cmp(&rhs.id))
+            });
+
+            let key = records[0].key();
+
+            for record in records {
+                canonical.insert(record.key(), key.clone());
+            }
+        }
+
+        Canonical(canonical)
+    }
+}
+
+/// Canonical records of all datasets which have duplicates, identified as `source/id`
+pub struct Canonical(HashMap<String, String>);
+
+impl Canonical {
+    pub fn get(&self, source: &str, id: &str) -> Option<&str> {
+        self.0.get(&format!("{source}/{id}")).map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// Queue of likely duplicates written by the indexer to `$DATA_PATH/duplicate_candidates.json`
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct Candidates {
+    /// Ordered by descending score
+    pub pairs: Vec<Candidate>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Candidate {
+    /// Record identified as `source/id` which sorts before the other one
+    pub lhs: String,
+    pub rhs: String,
+    pub score: f64,
+}
+
+impl Candidates {
+    pub fn read(dir: &Dir) -> Result<Self> {
+        read_json(dir, "duplicate_candidates.json")
+    }
+
+    pub fn write(&self, dir: &Dir) -> Result<()> {
+        write_json(dir, "duplicate_candidates.json", self)
+    }
+}
+
+/// Decisions of administrators on candidate pairs stored as `$DATA_PATH/duplicate_reviews.json`
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct Reviews {
+    /// Pairs grouped like exact duplicates by the next run of the indexer
+    pub confirmed: Vec<(String, String)>,
+    /// Pairs which are not queued for review again
+    pub rejected: Vec<(String, String)>,
+}
+
+impl Reviews {
+    pub fn read(dir: &Dir) -> Result<Self> {
+        read_json(dir, "duplicate_reviews.json")
+    }
+
+    pub fn write(&self, dir: &Dir) -> Result<()> {
+        write_json(dir, "duplicate_reviews.json", self)
+    }
+
+    /// Records the decision on the given pair, replacing any previous one.
+    pub fn review(&mut self, lhs: String, rhs: String, confirmed: bool) {
+        let pair = if lhs < rhs { (lhs, rhs) } else { (rhs, lhs) };
+
+        self.confirmed.retain(|other| *other != pair);
+        self.rejected.retain(|other| *other != pair);
+
+        if confirmed {
+            self.confirmed.push(pair);
+        } else {
+            self.rejected.push(pair);
+        }
+    }
+}
+
+fn read_json<T>(dir: &Dir, name: &str) -> Result<T>
+where
+    T: Default + DeserializeOwned,
+{
+    let val = if let Ok(mut file) = dir.open(name) {
+        let mut buf = String::new();
+        file.read_to_string(&mut buf)?;
+
+        from_str(&buf)?
+    } else {
+        Default::default()
+    };
+
+    Ok(val)
+}
+
+fn write_json<T>(dir: &Dir, name: &str, val: &T) -> Result<()>
+where
+    T: Serialize,
+{
+    let buf = to_vec_pretty(val)?;
+
+    let new_name = format!("{name}.new");
+
+    let mut file = dir.create(&new_name)?;
+    file.write_all(&buf)?;
+    dir.rename(&new_name, dir, name)?;
+
+    Ok(())
+}
+
+/// Jaccard index of two sorted sets of distinct values
+fn jaccard(lhs: &[String], rhs: &[String]) -> f64 {
+    if lhs.is_empty() && rhs.is_empty() {
+        return 0.0;
+    }
+
+    let common = lhs
+        .iter()
+        .filter(|val| rhs.binary_search(val).is_ok())
+        .count();
+
+    common as f64 / (lhs.len() + rhs.len() - common) as f64
+}
+
+/// Normalizes a resource URL so that trivially different spellings compare equal.
+///
+/// The scheme is ignored, the host is lowercased without a leading `www.` and fragments as well as trailing slashes are removed.
+fn normalize_url(url: &str) -> String {
+    let url = url.trim();
+
+    let url = url.split_once('#').map_or(url, |(url, _fragment)| url);
+
+    let url = ["https://", "http://"]
+        .into_iter()
+        .find_map(|scheme| {
+            url.get(..scheme.len())
+                .filter(|prefix| prefix.eq_ignore_ascii_case(scheme))
+                .map(|_| &url[scheme.len()..])
+        })
+        .unwrap_or(url);
+
+    let (host, path) = match url.find(['/', '?']) {
+        Some(pos) => url.split_at(pos),
+        None => (url, ""),
+    };
+
+    let host = host.to_ascii_lowercase();
+    let host = host.strip_prefix("www.").unwrap_or(&host);
+
+    let path = path.trim_end_matches('/');
+
+    format!("{host}{path}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use time::macros::date;
+
+    fn insert(
+        duplicates: &mut Duplicates,
+        source: &str,
+        id: &str,
+        trust: f64,
+        title: &str,
+        url: &str,
+    ) {
+        duplicates.insert(
+            source,
+            id,
+            trust,
+            title,
+            vec![normalize_url(url)],
+            Some(date!(2023 - 03 - 01)),
+        );
+    }
+
+    #[test]
+    fn near_duplicates_are_queued_and_grouped_once_confirmed() {
+        let mut duplicates = Duplicates::default();
+
+        insert(
+            &mut duplicates,
+            "a",
+            "1",
+            1.0,
+            "Pegelstände der Elbe",
+            "https://example.org/pegel.csv",
+        );
+        insert(
+            &mut duplicates,
+            "b",
+            "2",
+            0.5,
+            "Pegelstände der Elbe",
+            "https://mirror.example.com/pegel.csv",
+        );
+        insert(
+            &mut duplicates,
+            "c",
+            "3",
+            1.0,
+            "Luftqualität in Berlin",
+            "https://example.net/luft.csv",
+        );
+        insert(
+            &mut duplicates,
+            "d",
+            "4",
+            0.5,
+            "Luftqualität",
+            "https://example.net/luft.csv/",
+        );
+
+        let candidates = duplicates.candidates(&Reviews::default());
+
+        assert_eq!(candidates.pairs.len(), 1);
+        assert_eq!(candidates.pairs[0].lhs, "a/1");
+        assert_eq!(candidates.pairs[0].rhs, "b/2");
+
+        let mut reviews = Reviews::default();
+        reviews.review("b/2".to_owned(), "a/1".to_owned(), true);
+
+        assert_eq!(duplicates.candidates(&reviews).pairs.len(), 0);
+
+        let canonical = duplicates.canonical(&reviews);
+
+        assert_eq!(canonical.get("b", "2"), Some("a/1"));
+        assert_eq!(canonical.get("d", "4"), Some("c/3"));
+        assert_eq!(canonical.len(), 4);
+    }
+
+    #[test]
+    fn urls_are_normalized() {
+        assert_eq!(
+            normalize_url("HTTPS://WWW.Example.org/Daten/"),
+            "example.org/Daten"
+        );
+        assert_eq!(
+            normalize_url("http://example.org/daten.csv#top"),
+            "example.org/daten.csv"
+        );
+        assert_eq!(
+            normalize_url("https://example.org?format=csv"),
+            "example.org?format=csv"
+        );
+        assert_eq!(normalize_url(" "), "");
+    }
+}
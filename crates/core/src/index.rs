@@ -0,0 +1,1083 @@
+use std::fs::{create_dir_all, remove_dir_all};
+use std::io::ErrorKind;
+use std::path::Path;
+
+use anyhow::Result;
+use hashbrown::{HashMap, HashSet};
+use serde::{Deserialize, Serialize};
+use tantivy::{
+    collector::{Count, DocSetCollector, FacetCollector, FacetCounts, TopDocs},
+    directory::MmapDirectory,
+    fastfield::FastFieldReader,
+    query::{AllQuery, BooleanQuery, Occur, Query, QueryParser, TermQuery},
+    schema::{
+        Facet, FacetOptions, Field, IndexRecordOption, Schema, TextFieldIndexing, TextOptions,
+        Value, FAST, STORED, STRING,
+    },
+    tokenizer::{Language, LowerCaser, RemoveLongFilter, Stemmer, TextAnalyzer},
+    Document, Index, IndexReader, IndexWriter, Score, SegmentReader, Term,
+};
+
+use crate::{
+    dataset::{Dataset, Identifier, PartialDate, RelationTarget, RelationType},
+    provenance::provenance_facet,
+    sources::{default_trust, Source as SourceInfo, Visibility},
+    units::UnitTokenizer,
+};
+
+fn schema() -> Schema {
+    let text = TextOptions::default().set_indexing_options(
+        TextFieldIndexing::default()
+            .set_index_option(IndexRecordOption::WithFreqsAndPositions)
+            .set_tokenizer("de_stem"),
+    );
+
+    let mut schema = Schema::builder();
+
+    schema.add_text_field("source", STRING | STORED);
+    schema.add_text_field("id", STRING | STORED);
+
+    schema.add_text_field("canonical", STRING | STORED);
+
+    schema.add_text_field("title", text.clone());
+    schema.add_text_field("description", text.clone());
+
+    schema.add_text_field("comment", text);
+
+    schema.add_facet_field("provenance", FacetOptions::default());
+    schema.add_facet_field("license", FacetOptions::default());
+    schema.add_facet_field("availability", FacetOptions::default());
+    schema.add_facet_field("publisher", FacetOptions::default());
+    schema.add_facet_field("themes", FacetOptions::default());
+    schema.add_facet_field("update_frequency", FacetOptions::default());
+    schema.add_facet_field("reporting_obligations", FacetOptions::default());
+
+    schema.add_text_field("tags", STRING);
+
+    schema.add_text_field("legal_basis", STRING);
+
+    schema.add_text_field("identifiers", STRING);
+
+    schema.add_text_field("is_part_of", STRING);
+    schema.add_text_field("has_part", STRING);
+
+    schema.add_u64_field("accesses", FAST);
+
+    schema.add_u64_field("first_seen", FAST);
+
+    schema.add_u64_field("modified", FAST);
+
+    schema.add_f64_field("trust", FAST);
+
+    schema.add_text_field("visibility", STRING);
+
+    schema.build()
+}
+
+fn register_tokenizers(index: &Index) {
+    let de_stem = TextAnalyzer::from(UnitTokenizer)
+        .filter(RemoveLongFilter::limit(40))
+        .filter(LowerCaser)
+        .filter(Stemmer::new(Language::German));
+
+    index.tokenizers().register("de_stem", de_stem);
+}
+
+pub struct Searcher {
+    reader: IndexReader,
+    parser: QueryParser,
+    fields: Fields,
+}
+
+impl Searcher {
+    pub fn open(data_path: &Path) -> Result<Self> {
+        Self::open_in_dir(&data_path.join("index"))
+    }
+
+    /// Opens the candidate index built by `indexer --candidate` if there is one.
+    pub fn open_candidate(data_path: &Path) -> Result<Option<Self>> {
+        let index_path = data_path.join("index.candidate");
+
+        if !index_path.exists() {
+            return Ok(None);
+        }
+
+        Self::open_in_dir(&index_path).map(Some)
+    }
+
+    fn open_in_dir(index_path: &Path) -> Result<Self> {
+        let index = Index::open_in_dir(index_path)?;
+        register_tokenizers(&index);
+
+        let fields = Fields::new(&index.schema());
+
+        let reader = index.reader()?;
+        let parser = QueryParser::for_index(&index, vec![fields.title, fields.description]);
+
+        Ok(Self {
+            reader,
+            parser,
+            fields,
+        })
+    }
+
+    /// Yields the operation stamp of the last commit which identifies the current index generation.
+    pub fn generation(&self) -> Result<u64> {
+        let metas = self.reader.searcher().index().load_metas()?;
+
+        Ok(metas.opstamp)
+    }
+
+    pub fn search(
+        &self,
+        query: &str,
+        roots: &FacetRoots,
+        sort: Sort,
+        limit: usize,
+        offset: usize,
+        include_hidden: bool,
+    ) -> Result<Results<impl Iterator<Item = Result<(String, String)>> + '_>> {
+        // Queries consisting of a single DOI or URN resolve the dataset carrying it.
+        let query = match Identifier::parse(query) {
+            Some(identifier) if identifier.is_persistent() => Box::new(TermQuery::new(
+                Term::from_field_text(self.fields.identifiers, &identifier.term()),
+                IndexRecordOption::Basic,
+            )),
+            _ => self.parser.parse_query(query)?,
+        };
+        let searcher = self.reader.searcher();
+        let accesses = self.fields.accesses;
+        let trust = self.fields.trust;
+
+        let facet_query = |field, root| -> Box<dyn Query> {
+            Box::new(TermQuery::new(
+                Term::from_facet(field, root),
+                IndexRecordOption::Basic,
+            ))
+        };
+
+        let mut clauses = vec![
+            (Occur::Must, query),
+            (
+                Occur::Must,
+                facet_query(self.fields.provenance, roots.provenances),
+            ),
+            (
+                Occur::Must,
+                facet_query(self.fields.license, roots.licenses),
+            ),
+            (
+                Occur::Must,
+                facet_query(self.fields.availability, roots.availability),
+            ),
+        ];
+
+        // Only datasets with a publisher, themes, an update frequency or reporting obligations carry the root facet,
+        // so it must not restrict the results.
+        if !roots.publishers.is_root() {
+            clauses.push((
+                Occur::Must,
+                facet_query(self.fields.publisher, roots.publishers),
+            ));
+        }
+
+        if !roots.themes.is_root() {
+            clauses.push((Occur::Must, facet_query(self.fields.themes, roots.themes)));
+        }
+
+        if !roots.update_frequencies.is_root() {
+            clauses.push((
+                Occur::Must,
+                facet_query(self.fields.update_frequency, roots.update_frequencies),
+            ));
+        }
+
+        if !roots.reporting_obligations.is_root() {
+            clauses.push((
+                Occur::Must,
+                facet_query(
+                    self.fields.reporting_obligations,
+                    roots.reporting_obligations,
+                ),
+            ));
+        }
+
+        if !include_hidden {
+            let hidden: Box<dyn Query> = Box::new(TermQuery::new(
+                Term::from_field_text(self.fields.visibility, Visibility::Hidden.as_str()),
+                IndexRecordOption::Basic,
+            ));
+
+            clauses.push((Occur::MustNot, hidden));
+        }
+
+        let query = BooleanQuery::new(clauses);
+
+        let facet_collector = |field, root: &Facet| {
+            let mut collector = FacetCollector::for_field(field);
+            collector.add_facet(root.clone());
+            collector
+        };
+
+        // Collectors are only implemented for tuples of up to four elements.
+        let facets = (
+            facet_collector(self.fields.provenance, roots.provenances),
+            facet_collector(self.fields.license, roots.licenses),
+            facet_collector(self.fields.availability, roots.availability),
+            (
+                facet_collector(self.fields.publisher, roots.publishers),
+                facet_collector(self.fields.themes, roots.themes),
+                facet_collector(self.fields.update_frequency, roots.update_frequencies),
+                facet_collector(
+                    self.fields.reporting_obligations,
+                    roots.reporting_obligations,
+                ),
+            ),
+        );
+
+        let top_docs = TopDocs::with_limit(limit).and_offset(offset);
+
+        let (
+            count,
+            docs,
+            (
+                provenances,
+                licenses,
+                availability,
+                (publishers, themes, update_frequencies, reporting_obligations),
+            ),
+        ) = match sort {
+            Sort::Relevance => {
+                let (count, docs, facets) = searcher.search(
+                    &query,
+                    &(
+                        Count,
+                        top_docs.tweak_score(move |reader: &SegmentReader| {
+                            let accesses = reader.fast_fields().u64(accesses).unwrap();
+                            let trust = reader.fast_fields().f64(trust).unwrap();
+
+                            move |doc, score| {
+                                let accesses: u64 = accesses.get(doc);
+                                let trust: f64 = trust.get(doc);
+                                let boost = ((2 + accesses) as Score).log2() * trust as Score;
+
+                                boost * score
+                            }
+                        }),
+                        facets,
+                    ),
+                )?;
+
+                let docs = docs
+                    .into_iter()
+                    .map(|(_score, doc)| doc)
+                    .collect::<Vec<_>>();
+
+                (count, docs, facets)
+            }
+            Sort::FirstSeen => {
+                let (count, docs, facets) = searcher.search(
+                    &query,
+                    &(
+                        Count,
+                        top_docs.order_by_u64_field(self.fields.first_seen),
+                        facets,
+                    ),
+                )?;
+
+                let docs = docs
+                    .into_iter()
+                    .map(|(_first_seen, doc)| doc)
+                    .collect::<Vec<_>>();
+
+                (count, docs, facets)
+            }
+            Sort::Modified => {
+                let (count, docs, facets) = searcher.search(
+                    &query,
+                    &(
+                        Count,
+                        top_docs.order_by_u64_field(self.fields.modified),
+                        facets,
+                    ),
+                )?;
+
+                let docs = docs
+                    .into_iter()
+                    .map(|(_modified, doc)| doc)
+                    .collect::<Vec<_>>();
+
+                (count, docs, facets)
+            }
+        };
+
+        let iter = docs.into_iter().map(move |doc| {
+            let doc = searcher.doc(doc)?;
+
+            let source = match doc.get_first(self.fields.source) {
+                Some(Value::Str(source)) => source.clone(),
+                _ => unreachable!(),
+            };
+
+            let id = match doc.get_first(self.fields.id) {
+                Some(Value::Str(id)) => id.clone(),
+                _ => unreachable!(),
+            };
+
+            Ok((source, id))
+        });
+
+        Ok(Results {
+            count,
+            iter,
+            provenances,
+            licenses,
+            availability,
+            publishers,
+            themes,
+            update_frequencies,
+            reporting_obligations,
+        })
+    }
+
+    /// Yields the source and ID of all indexed documents.
+    pub fn documents(&self) -> Result<Vec<(String, String)>> {
+        let searcher = self.reader.searcher();
+
+        let docs = searcher.search(&AllQuery, &DocSetCollector)?;
+
+        docs.into_iter()
+            .map(|doc| {
+                let doc = searcher.doc(doc)?;
+
+                let (Some(Value::Str(source)), Some(Value::Str(id))) = (
+                    doc.get_first(self.fields.source),
+                    doc.get_first(self.fields.id),
+                ) else {
+                    unreachable!()
+                };
+
+                Ok((source.clone(), id.clone()))
+            })
+            .collect()
+    }
+
+    /// Yields the number of indexed datasets of the given source.
+    pub fn count(&self, source: &str) -> Result<usize> {
+        let query = TermQuery::new(
+            Term::from_field_text(self.fields.source, source),
+            IndexRecordOption::Basic,
+        );
+
+        let count = self.reader.searcher().search(&query, &Count)?;
+
+        Ok(count)
+    }
+
+    /// Yields the records of other sources which duplicate the given dataset, starting with the canonical one.
+    pub fn duplicates(&self, source: &str, id: &str) -> Result<Vec<(String, String)>> {
+        let searcher = self.reader.searcher();
+
+        let term_query = |field, text| -> Box<dyn Query> {
+            Box::new(TermQuery::new(
+                Term::from_field_text(field, text),
+                IndexRecordOption::Basic,
+            ))
+        };
+
+        let query = BooleanQuery::intersection(vec![
+            term_query(self.fields.source, source),
+            term_query(self.fields.id, id),
+        ]);
+
+        let doc = match searcher.search(&query, &TopDocs::with_limit(1))?.first() {
+            Some((_score, doc)) => searcher.doc(*doc)?,
+            None => return Ok(Vec::new()),
+        };
+
+        let canonical = match doc.get_first(self.fields.canonical) {
+            Some(Value::Str(canonical)) => canonical.clone(),
+            _ => return Ok(Vec::new()),
+        };
+
+        let query = term_query(self.fields.canonical, &canonical);
+
+        let mut duplicates = Vec::new();
+
+        for (_score, doc) in searcher.search(&query, &TopDocs::with_limit(100))? {
+            let doc = searcher.doc(doc)?;
+
+            let (Some(Value::Str(other_source)), Some(Value::Str(other_id))) = (
+                doc.get_first(self.fields.source),
+                doc.get_first(self.fields.id),
+            ) else {
+                unreachable!()
+            };
+
+            if other_source != source || other_id != id {
+                duplicates.push((other_source.clone(), other_id.clone()));
+            }
+        }
+
+        duplicates.sort_unstable_by_key(|(source, id)| {
+            (
+                format!("{source}/{id}") != canonical,
+                source.clone(),
+                id.clone(),
+            )
+        });
+
+        Ok(duplicates)
+    }
+
+    /// Yields the datasets to which the given one stands in the given relation as declared by the inverse relation of those datasets,
+    /// e.g. the datasets which declare to contain the given one for [`RelationType::IsPartOf`].
+    pub fn relatives(
+        &self,
+        r#type: RelationType,
+        source: &str,
+        id: &str,
+    ) -> Result<Vec<(String, String)>> {
+        let field = match r#type {
+            RelationType::IsPartOf => self.fields.has_part,
+            RelationType::HasPart => self.fields.is_part_of,
+            RelationType::IsVersionOf | RelationType::HasVersion => return Ok(Vec::new()),
+        };
+
+        let searcher = self.reader.searcher();
+
+        let query = TermQuery::new(
+            Term::from_field_text(field, &format!("{source}/{id}")),
+            IndexRecordOption::Basic,
+        );
+
+        let mut relatives = Vec::new();
+
+        for (_score, doc) in searcher.search(&query, &TopDocs::with_limit(100))? {
+            let doc = searcher.doc(doc)?;
+
+            let (Some(Value::Str(source)), Some(Value::Str(id))) = (
+                doc.get_first(self.fields.source),
+                doc.get_first(self.fields.id),
+            ) else {
+                unreachable!()
+            };
+
+            relatives.push((source.clone(), id.clone()));
+        }
+
+        relatives.sort_unstable();
+
+        Ok(relatives)
+    }
+}
+
+/// Roots of the facets to which search results are restricted
+pub struct FacetRoots<'a> {
+    pub provenances: &'a Facet,
+    pub licenses: &'a Facet,
+    /// Whether the resources of a dataset are `/verfügbar`, `/defekt` or `/unbekannt` as they were not checked
+    pub availability: &'a Facet,
+    /// Organisations which published the datasets, e.g. `/Umweltbundesamt`
+    pub publishers: &'a Facet,
+    /// Themes below `/dcat` following DCAT-AP or below `/umwelt` for our own categories
+    pub themes: &'a Facet,
+    /// How often the datasets are updated, e.g. `/täglich` or `/nie`
+    pub update_frequencies: &'a Facet,
+    /// Titles of the reporting obligations the datasets contribute to
+    pub reporting_obligations: &'a Facet,
+}
+
+/// Order of search results
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Sort {
+    /// By score, boosted by the number of accesses
+    #[default]
+    Relevance,
+    /// Datasets first seen most recently come first.
+    FirstSeen,
+    /// Datasets modified most recently come first.
+    Modified,
+}
+
+pub struct Results<I> {
+    pub count: usize,
+    pub iter: I,
+    pub provenances: FacetCounts,
+    pub licenses: FacetCounts,
+    pub availability: FacetCounts,
+    pub publishers: FacetCounts,
+    pub themes: FacetCounts,
+    pub update_frequencies: FacetCounts,
+    pub reporting_obligations: FacetCounts,
+}
+
+pub struct Indexer {
+    writer: IndexWriter,
+    fields: Fields,
+}
+
+impl Indexer {
+    /// Starts indexing either all sources if none are given or only the given ones, replacing their previously indexed documents.
+    pub fn start(data_path: &Path, sources: &[String]) -> Result<Self> {
+        Self::start_in_dir(&data_path.join("index"), sources)
+    }
+
+    /// Starts building a candidate index of all sources from scratch, e.g. to evaluate changes of the schema.
+    pub fn start_candidate(data_path: &Path) -> Result<Self> {
+        let index_path = data_path.join("index.candidate");
+
+        match remove_dir_all(&index_path) {
+            Err(err) if err.kind() != ErrorKind::NotFound => return Err(err.into()),
+            _ => (),
+        }
+
+        Self::start_in_dir(&index_path, &[])
+    }
+
+    fn start_in_dir(index_path: &Path, sources: &[String]) -> Result<Self> {
+        create_dir_all(index_path)?;
+
+        let schema = schema();
+        let fields = Fields::new(&schema);
+
+        let index = Index::open_or_create(MmapDirectory::open(index_path)?, schema)?;
+        register_tokenizers(&index);
+
+        let writer = index.writer(128 << 20)?;
+
+        if sources.is_empty() {
+            writer.delete_all_documents()?;
+        } else {
+            for source in sources {
+                writer.delete_term(Term::from_field_text(fields.source, source));
+            }
+        }
+
+        Ok(Self { writer, fields })
+    }
+
+    pub fn add_document(
+        &self,
+        source: String,
+        id: String,
+        dataset: Dataset,
+        accesses: u64,
+        source_info: Option<&SourceInfo>,
+        canonical: Option<String>,
+    ) -> Result<()> {
+        // Resources licensed differently make the dataset available under each of these licenses.
+        let licenses = dataset
+            .licenses()
+            .into_iter()
+            .map(|license| Facet::from_path(license.facet()))
+            .collect::<Vec<_>>();
+
+        let availability = match dataset.links_available() {
+            Some(true) => "verfügbar",
+            Some(false) => "defekt",
+            None => "unbekannt",
+        };
+
+        let mut doc = Document::default();
+
+        doc.add_text(self.fields.source, source);
+        doc.add_text(self.fields.id, id);
+
+        // Duplicates share the `source/id` of their canonical record.
+        if let Some(canonical) = canonical {
+            doc.add_text(self.fields.canonical, canonical);
+        }
+
+        doc.add_text(self.fields.title, dataset.title);
+
+        if let Some(description) = dataset.description {
+            doc.add_text(self.fields.description, description);
+        }
+
+        if let Some(comment) = dataset.comment {
+            doc.add_text(self.fields.comment, comment);
+        }
+
+        // The provenance configured for the source takes precedence over the one stored when harvesting
+        // so that changes to the configuration only require reindexing.
+        let provenance = source_info.map_or_else(
+            || provenance_facet(&dataset.provenance),
+            SourceInfo::provenance_facet,
+        );
+
+        doc.add_facet(self.fields.provenance, provenance);
+
+        for license in licenses {
+            doc.add_facet(self.fields.license, license);
+        }
+
+        doc.add_facet(self.fields.availability, Facet::from_path([availability]));
+
+        for reporting_obligation in &dataset.reporting_obligations {
+            doc.add_facet(
+                self.fields.reporting_obligations,
+                Facet::from_path([&reporting_obligation.title]),
+            );
+        }
+
+        // Datasets without a publisher are only found below the root facet.
+        if let Some(publisher) = &dataset.publisher {
+            doc.add_facet(self.fields.publisher, Facet::from_path([publisher.facet()]));
+        }
+
+        for theme in &dataset.themes {
+            doc.add_facet(self.fields.themes, Facet::from_path(theme.facet()));
+        }
+
+        if let Some(update_frequency) = dataset.update_frequency {
+            doc.add_facet(
+                self.fields.update_frequency,
+                Facet::from_path([update_frequency.facet()]),
+            );
+        }
+
+        for tag in dataset.tags {
+            tag.with_tokens(|tokens| {
+                for token in tokens {
+                    doc.add_text(self.fields.tags, token.to_owned());
+                }
+            });
+        }
+
+        // Citations are matched exactly, e.g. `legal_basis:"§ 44 BImSchG"`.
+        for legal_basis in dataset.legal_basis {
+            doc.add_text(self.fields.legal_basis, legal_basis);
+        }
+
+        for identifier in dataset.identifiers {
+            doc.add_text(self.fields.identifiers, identifier.term());
+        }
+
+        // Relations between datasets of our catalogue are indexed as `source/id` so that they can be followed in reverse.
+        for relation in dataset.relations {
+            let field = match relation.r#type {
+                RelationType::IsPartOf => self.fields.is_part_of,
+                RelationType::HasPart => self.fields.has_part,
+                RelationType::IsVersionOf | RelationType::HasVersion => continue,
+            };
+
+            if let RelationTarget::Dataset { source, id } = relation.target {
+                doc.add_text(field, format!("{source}/{id}"));
+            }
+        }
+
+        doc.add_u64(self.fields.accesses, accesses);
+
+        // Datasets without a first seen date sort last.
+        let first_seen = match dataset.first_seen {
+            Some(PartialDate::Full(date)) => date.to_julian_day() as u64,
+            _ => 0,
+        };
+
+        doc.add_u64(self.fields.first_seen, first_seen);
+
+        // Partial modification dates are taken to refer to their first day while datasets without one sort last.
+        let modified = dataset
+            .modified
+            .and_then(PartialDate::first_day)
+            .map_or(0, |date| date.to_julian_day() as u64);
+
+        doc.add_u64(self.fields.modified, modified);
+
+        let (trust, visibility) = source_info.map_or_else(
+            || (default_trust(), Visibility::default()),
+            |source_info| (source_info.trust, source_info.visibility),
+        );
+
+        doc.add_f64(self.fields.trust, trust);
+
+        doc.add_text(self.fields.visibility, visibility.as_str());
+
+        self.writer.add_document(doc)?;
+
+        Ok(())
+    }
+
+    pub fn commit(mut self) -> Result<()> {
+        self.writer.commit()?;
+
+        Ok(())
+    }
+}
+
+/// Leading sentences repeated across the descriptions of a single source
+///
+/// Many portals prepend identical paragraphs like "Dieser Datensatz wird bereitgestellt von ..."
+/// which distort term statistics. These are removed from the indexed text while the stored
+/// datasets are left untouched.
+#[derive(Default)]
+pub struct Boilerplate {
+    sentences: HashSet<String>,
+}
+
+impl Boilerplate {
+    /// Sentences must occur at least this often and ...
+    const MIN_COUNT: usize = 10;
+    /// ... at least in this fraction of all descriptions to be considered boilerplate.
+    const MIN_RATIO: f64 = 0.05;
+    /// Only this many sentences at the start of each description are considered.
+    const MAX_SENTENCES: usize = 3;
+
+    pub fn detect<'a, I>(descriptions: I) -> Self
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let mut total = 0;
+        let mut counts = HashMap::<&str, usize>::new();
+
+        for description in descriptions {
+            total += 1;
+
+            let mut description = description;
+
+            for _ in 0..Self::MAX_SENTENCES {
+                match leading_sentence(description) {
+                    Some(sentence) => {
+                        *counts.entry(sentence).or_default() += 1;
+
+                        description = description.trim_start()[sentence.len()..].trim_start();
+                    }
+                    None => break,
+                }
+            }
+        }
+
+        let min_count = Self::MIN_COUNT.max((Self::MIN_RATIO * total as f64) as usize);
+
+        let sentences = counts
+            .into_iter()
+            .filter(|(_, count)| *count >= min_count)
+            .map(|(sentence, _)| sentence.to_owned())
+            .collect();
+
+        Self { sentences }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sentences.is_empty()
+    }
+
+    pub fn strip<'a>(&self, mut description: &'a str) -> &'a str {
+        while let Some(sentence) = leading_sentence(description) {
+            if !self.sentences.contains(sentence) {
+                break;
+            }
+
+            description = description.trim_start()[sentence.len()..].trim_start();
+        }
+
+        description
+    }
+}
+
+fn leading_sentence(text: &str) -> Option<&str> {
+    let text = text.trim_start();
+
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((pos, char)) = chars.next() {
+        let end = match char {
+            '.' | '!' | '?' => match chars.peek() {
+                Some((_, next)) if next.is_whitespace() => pos + char.len_utf8(),
+                _ => continue,
+            },
+            '\n' => pos,
+            _ => continue,
+        };
+
+        // Avoid treating the whole description as boilerplate.
+        return if end < text.len() && end != 0 {
+            Some(&text[..end])
+        } else {
+            None
+        };
+    }
+
+    None
+}
+
+struct Fields {
+    source: Field,
+    id: Field,
+    canonical: Field,
+    title: Field,
+    description: Field,
+    comment: Field,
+    provenance: Field,
+    license: Field,
+    availability: Field,
+    publisher: Field,
+    themes: Field,
+    update_frequency: Field,
+    reporting_obligations: Field,
+    tags: Field,
+    legal_basis: Field,
+    identifiers: Field,
+    is_part_of: Field,
+    has_part: Field,
+    accesses: Field,
+    first_seen: Field,
+    modified: Field,
+    trust: Field,
+    visibility: Field,
+}
+
+impl Fields {
+    fn new(schema: &Schema) -> Self {
+        let source = schema.get_field("source").unwrap();
+        let id = schema.get_field("id").unwrap();
+
+        let canonical = schema.get_field("canonical").unwrap();
+
+        let title = schema.get_field("title").unwrap();
+        let description = schema.get_field("description").unwrap();
+        let comment = schema.get_field("comment").unwrap();
+
+        let provenance = schema.get_field("provenance").unwrap();
+        let license = schema.get_field("license").unwrap();
+        let availability = schema.get_field("availability").unwrap();
+        let publisher = schema.get_field("publisher").unwrap();
+        let themes = schema.get_field("themes").unwrap();
+        let update_frequency = schema.get_field("update_frequency").unwrap();
+        let reporting_obligations = schema.get_field("reporting_obligations").unwrap();
+
+        let tags = schema.get_field("tags").unwrap();
+
+        let legal_basis = schema.get_field("legal_basis").unwrap();
+
+        let identifiers = schema.get_field("identifiers").unwrap();
+
+        let is_part_of = schema.get_field("is_part_of").unwrap();
+        let has_part = schema.get_field("has_part").unwrap();
+
+        let accesses = schema.get_field("accesses").unwrap();
+
+        let first_seen = schema.get_field("first_seen").unwrap();
+
+        let modified = schema.get_field("modified").unwrap();
+
+        let trust = schema.get_field("trust").unwrap();
+
+        let visibility = schema.get_field("visibility").unwrap();
+
+        Self {
+            source,
+            id,
+            canonical,
+            title,
+            description,
+            comment,
+            provenance,
+            license,
+            availability,
+            publisher,
+            themes,
+            update_frequency,
+            reporting_obligations,
+            tags,
+            legal_basis,
+            identifiers,
+            is_part_of,
+            has_part,
+            accesses,
+            first_seen,
+            modified,
+            trust,
+            visibility,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::env::temp_dir;
+    use std::fs::remove_dir_all;
+    use std::process;
+
+    use crate::dataset::{License, Publisher};
+
+    #[test]
+    fn leading_sentence_requires_remainder() {
+        assert_eq!(
+            leading_sentence("Erster Satz. Zweiter Satz."),
+            Some("Erster Satz.")
+        );
+        assert_eq!(leading_sentence("Nur ein Satz."), None);
+        assert_eq!(leading_sentence("Version 1.2 ist aktuell"), None);
+    }
+
+    #[test]
+    fn boilerplate_is_stripped_repeatedly() {
+        let descriptions = (0..20)
+            .map(|idx| format!("Bereitgestellt von X. Lizenz siehe Y. Messwerte {idx}."))
+            .collect::<Vec<_>>();
+
+        let boilerplate = Boilerplate::detect(descriptions.iter().map(String::as_str));
+
+        assert_eq!(boilerplate.strip(&descriptions[3]), "Messwerte 3.");
+        assert_eq!(
+            boilerplate.strip("Andere Einleitung. Bereitgestellt von X. Rest"),
+            "Andere Einleitung. Bereitgestellt von X. Rest"
+        );
+    }
+
+    #[test]
+    fn datasets_without_publisher_are_found() {
+        let data_path = temp_dir().join(format!("umwelt-info-index-{}", process::id()));
+
+        let dataset = |publisher: Option<&str>| Dataset {
+            title: "Pegel".to_owned(),
+            description: None,
+            summary: None,
+            comment: None,
+            provenance: Default::default(),
+            publisher: publisher.map(|name| Publisher {
+                name: name.to_owned(),
+                url: None,
+            }),
+            license: License::Unknown,
+            legal_basis: Vec::new(),
+            reporting_obligations: Vec::new(),
+            contacts: Vec::new(),
+            tags: Vec::new(),
+            themes: Vec::new(),
+            region: None,
+            language: None,
+            bbox: None,
+            time_range: None,
+            issued: None,
+            modified: None,
+            update_frequency: None,
+            last_checked: None,
+            first_seen: None,
+            identifiers: Default::default(),
+            relations: Vec::new(),
+            source_url: String::new(),
+            resources: Default::default(),
+        };
+
+        let mut dataset2 = dataset(Some("BfG"));
+        dataset2
+            .identifiers
+            .push(Identifier::Doi("10.5675/BfG_Pegel".to_owned()));
+
+        let indexer = Indexer::start(&data_path, &[]).unwrap();
+        indexer
+            .add_document("a".to_owned(), "1".to_owned(), dataset(None), 0, None, None)
+            .unwrap();
+        indexer
+            .add_document("a".to_owned(), "2".to_owned(), dataset2, 0, None, None)
+            .unwrap();
+        indexer.commit().unwrap();
+
+        let searcher = Searcher::open(&data_path).unwrap();
+
+        let count = |query: &str, publishers: &Facet| {
+            let roots = FacetRoots {
+                provenances: &Facet::root(),
+                licenses: &Facet::root(),
+                availability: &Facet::root(),
+                publishers,
+                themes: &Facet::root(),
+                update_frequencies: &Facet::root(),
+                reporting_obligations: &Facet::root(),
+            };
+
+            searcher
+                .search(query, &roots, Sort::Relevance, 10, 0, false)
+                .unwrap()
+                .count
+        };
+
+        assert_eq!(count("pegel", &Facet::root()), 2);
+        assert_eq!(count("pegel", &Facet::from_path(["BfG"])), 1);
+
+        assert_eq!(
+            count("https://doi.org/10.5675/bfg_pegel", &Facet::root()),
+            1
+        );
+
+        remove_dir_all(data_path).unwrap();
+    }
+
+    #[test]
+    fn datasets_are_sorted_by_modification() {
+        let data_path = temp_dir().join(format!("umwelt-info-index-{}", process::id()));
+
+        let dataset = |modified: Option<PartialDate>| Dataset {
+            title: "Pegel".to_owned(),
+            description: None,
+            summary: None,
+            comment: None,
+            provenance: Default::default(),
+            publisher: None,
+            license: License::Unknown,
+            legal_basis: Vec::new(),
+            reporting_obligations: Vec::new(),
+            contacts: Vec::new(),
+            tags: Vec::new(),
+            themes: Vec::new(),
+            region: None,
+            language: None,
+            bbox: None,
+            time_range: None,
+            issued: None,
+            modified,
+            update_frequency: None,
+            last_checked: None,
+            first_seen: None,
+            identifiers: Default::default(),
+            relations: Vec::new(),
+            source_url: String::new(),
+            resources: Default::default(),
+        };
+
+        let indexer = Indexer::start(&data_path, &[]).unwrap();
+        indexer
+            .add_document("a".to_owned(), "1".to_owned(), dataset(None), 0, None, None)
+            .unwrap();
+        indexer
+            .add_document(
+                "a".to_owned(),
+                "2".to_owned(),
+                dataset(Some(PartialDate::Year(2023))),
+                0,
+                None,
+                None,
+            )
+            .unwrap();
+        indexer.commit().unwrap();
+
+        let searcher = Searcher::open(&data_path).unwrap();
+
+        let roots = FacetRoots {
+            provenances: &Facet::root(),
+            licenses: &Facet::root(),
+            availability: &Facet::root(),
+            publishers: &Facet::root(),
+            themes: &Facet::root(),
+            update_frequencies: &Facet::root(),
+            reporting_obligations: &Facet::root(),
+        };
+
+        let (_source, id) = searcher
+            .search("pegel", &roots, Sort::Modified, 10, 0, false)
+            .unwrap()
+            .iter
+            .next()
+            .unwrap()
+            .unwrap();
+        assert_eq!(id, "2");
+
+        remove_dir_all(data_path).unwrap();
+    }
+}
@@ -0,0 +1,124 @@
+//! Types shared by all programs, i.e. datasets, the search index, metrics, statistics and the sources snapshot.
+pub mod dataset;
+pub mod duplicates;
+pub mod index;
+pub mod metrics;
+pub mod provenance;
+pub mod sources;
+pub mod stats;
+pub mod units;
+
+use std::borrow::Cow;
+use std::env::var_os;
+use std::fmt::Write;
+use std::path::PathBuf;
+
+use anyhow::{ensure, Result};
+use percent_encoding::percent_decode_str;
+
+pub fn data_path_from_env() -> PathBuf {
+    var_os("DATA_PATH")
+        .expect("Environment variable DATA_PATH not set")
+        .into()
+}
+
+/// Checks that a source name or the file name of a dataset can safely be used as a single path component.
+///
+/// Other characters are permitted as links percent-encode these names.
+pub fn validate_name(name: &str) -> Result<()> {
+    ensure!(
+        !name.is_empty() && name.len() <= 255,
+        "Invalid length of name {name:?}"
+    );
+
+    ensure!(
+        name != "." && name != "..",
+        "Name {name:?} refers to a directory"
+    );
+
+    ensure!(
+        !name.contains(|char: char| char == '/' || char == '\\' || char.is_control()),
+        "Name {name:?} contains path separators or control characters"
+    );
+
+    Ok(())
+}
+
+/// Encodes a dataset ID into the name of the file storing the dataset.
+///
+/// Path separators, control characters and the percent sign itself are percent-encoded
+/// as are IDs which would refer to a directory. Other characters are kept verbatim.
+pub fn encode_id(id: &str) -> Result<Cow<str>> {
+    fn is_reserved(char: char) -> bool {
+        matches!(char, '/' | '\\' | '%') || char.is_control()
+    }
+
+    let name = if id == "." || id == ".." {
+        Cow::Owned(id.replace('.', "%2E"))
+    } else if id.contains(is_reserved) {
+        let mut name = String::with_capacity(id.len());
+
+        for char in id.chars() {
+            if is_reserved(char) {
+                for byte in char.encode_utf8(&mut [0; 4]).bytes() {
+                    write!(name, "%{byte:02X}").unwrap();
+                }
+            } else {
+                name.push(char);
+            }
+        }
+
+        Cow::Owned(name)
+    } else {
+        Cow::Borrowed(id)
+    };
+
+    validate_name(&name)?;
+
+    Ok(name)
+}
+
+/// Decodes the name of the file storing a dataset into its ID, i.e. the inverse of [`encode_id`].
+pub fn decode_id(name: &str) -> Cow<str> {
+    percent_decode_str(name).decode_utf8_lossy()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_are_validated() {
+        validate_name("uba-gdi").unwrap();
+        validate_name("Wasserqualität der Elbe (2020)").unwrap();
+        validate_name("a%2Fb").unwrap();
+
+        validate_name("").unwrap_err();
+        validate_name("..").unwrap_err();
+        validate_name("../datasets").unwrap_err();
+        validate_name("a\\b").unwrap_err();
+        validate_name("a\0b").unwrap_err();
+        validate_name(&"a".repeat(256)).unwrap_err();
+    }
+
+    #[test]
+    fn ids_are_encoded() {
+        for (id, name) in [
+            ("uba-gdi", "uba-gdi"),
+            (
+                "Wasserqualität der Elbe (2020)",
+                "Wasserqualität der Elbe (2020)",
+            ),
+            ("a/b", "a%2Fb"),
+            ("a%2Fb", "a%252Fb"),
+            ("a\\b\n", "a%5Cb%0A"),
+            ("..", "%2E%2E"),
+        ] {
+            assert_eq!(encode_id(id).unwrap(), name);
+            assert_eq!(decode_id(name), id);
+        }
+
+        encode_id("").unwrap_err();
+        encode_id(&"/".repeat(100)).unwrap_err();
+    }
+}
@@ -0,0 +1,329 @@
+use std::io::{BufReader, Read, Write};
+use std::ops::AddAssign;
+use std::time::{Duration, SystemTime};
+
+use anyhow::Result;
+use bincode::{deserialize_from, serialize};
+use cap_std::fs::Dir;
+use hashbrown::HashMap;
+use serde::{Deserialize, Serialize};
+use serde_json::{from_str, to_vec_pretty};
+
+use crate::dataset::{Dataset, License, Tag};
+
+#[derive(Default, Debug, Serialize, Deserialize)]
+pub struct Metrics {
+    pub harvests: HashMap<String, Harvest>,
+    pub licenses: HashMap<String, HashMap<License, usize>>,
+    pub tags: HashMap<String, HashMap<Tag, usize>>,
+    /// Start of the last harvest of each source which completed without failures
+    pub last_success: HashMap<String, SystemTime>,
+    /// Requests sent to each host by all sources during the last harvest
+    pub hosts: HashMap<String, HostTraffic>,
+}
+
+impl Metrics {
+    pub fn read(dir: &Dir) -> Result<Self> {
+        let val = if let Ok(file) = dir.open("metrics") {
+            // Metrics are mostly recomputed by each run of harvester and indexer,
+            // so a changed format is not worth a migration.
+            deserialize_from(BufReader::new(file)).unwrap_or_else(|err| {
+                tracing::warn!("Discarding metrics in outdated format: {:#}", err);
+
+                // The start of the last successful harvests is required for delta harvesting though,
+                // so it is kept in a self-describing format which survives changes of the metrics.
+                let last_success = match read_last_success(dir) {
+                    Ok(last_success) => last_success,
+                    Err(err) => {
+                        tracing::warn!(
+                            "Failed to read start of last successful harvests: {:#}",
+                            err
+                        );
+
+                        Default::default()
+                    }
+                };
+
+                Self {
+                    last_success,
+                    ..Default::default()
+                }
+            })
+        } else {
+            Default::default()
+        };
+
+        Ok(val)
+    }
+
+    pub fn write(&self, dir: &Dir) -> Result<()> {
+        let buf = serialize(self)?;
+
+        let mut file = dir.create("metrics.new")?;
+        file.write_all(&buf)?;
+        dir.rename("metrics.new", dir, "metrics")?;
+
+        let buf = to_vec_pretty(&self.last_success)?;
+
+        let mut file = dir.create("last_success.json.new")?;
+        file.write_all(&buf)?;
+        dir.rename("last_success.json.new", dir, "last_success.json")?;
+
+        Ok(())
+    }
+
+    pub fn record_harvest(
+        &mut self,
+        source_name: String,
+        start: SystemTime,
+        duration: Duration,
+        count: usize,
+        counters: HarvestCounters,
+        error: Option<String>,
+    ) {
+        if error.is_none() && counters.failed == 0 {
+            self.last_success.insert(source_name.clone(), start);
+        }
+
+        self.harvests.insert(
+            source_name,
+            Harvest {
+                start,
+                duration,
+                count,
+                counters,
+                error,
+                findings: Vec::new(),
+            },
+        );
+    }
+
+    /// Attaches the findings of validating a source in report mode to its last harvest.
+    pub fn record_findings(&mut self, source_name: &str, findings: Vec<Finding>) {
+        if let Some(harvest) = self.harvests.get_mut(source_name) {
+            harvest.findings = findings;
+        }
+    }
+
+    pub fn clear_datasets(&mut self) {
+        self.licenses.clear();
+        self.tags.clear();
+    }
+
+    /// Clears the dataset metrics of a single source before it is indexed again.
+    pub fn clear_source(&mut self, source: &str) {
+        self.licenses.remove(source);
+        self.tags.remove(source);
+    }
+
+    pub fn record_dataset(&mut self, source: &str, dataset: &Dataset) {
+        *self
+            .licenses
+            .entry_ref(source)
+            .or_default()
+            .entry_ref(&dataset.license)
+            .or_default() += 1;
+
+        let tags = self.tags.entry_ref(source).or_default();
+
+        for tag in &dataset.tags {
+            *tags.entry_ref(tag).or_default() += 1;
+        }
+    }
+}
+
+fn read_last_success(dir: &Dir) -> Result<HashMap<String, SystemTime>> {
+    let val = if let Ok(mut file) = dir.open("last_success.json") {
+        let mut buf = String::new();
+        file.read_to_string(&mut buf)?;
+
+        from_str(&buf)?
+    } else {
+        Default::default()
+    };
+
+    Ok(val)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Harvest {
+    pub start: SystemTime,
+    pub duration: Duration,
+    /// Number of records reported by the source
+    pub count: usize,
+    pub counters: HarvestCounters,
+    /// Reason why the source could not be harvested at all
+    pub error: Option<String>,
+    /// Datasets violating the validation rules if the source is validated in report mode
+    pub findings: Vec<Finding>,
+}
+
+/// Validation rules violated by a single dataset
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Finding {
+    pub id: String,
+    pub violations: Vec<String>,
+}
+
+/// Accounting of the records of a single source during one harvest
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+pub struct HarvestCounters {
+    /// Records transmitted by the source
+    pub fetched: usize,
+    /// Records successfully translated into datasets
+    pub parsed: usize,
+    /// Records deliberately skipped, e.g. due to the source filter
+    pub filtered: usize,
+    /// Datasets written under a previously unused ID
+    pub written: usize,
+    /// Datasets which were merged with a previous one with the same ID but differing content
+    pub duplicates: usize,
+    /// Records yielded more than once with identical content
+    pub identical: usize,
+    /// Records which could not be fetched or translated
+    pub failed: usize,
+    /// Datasets kept from the previous harvest as they were not modified since
+    pub kept: usize,
+    /// Records skipped as their URL is disallowed by the robots.txt of the source
+    pub disallowed: usize,
+    /// Concurrency reached by adaptive fetching, zero if it was fixed
+    pub concurrency: usize,
+    /// Requests which were not sent as the harvest was aborted after consecutive failures
+    pub aborted: usize,
+    /// Records whose response was quarantined as they could not be translated
+    pub quarantined: usize,
+    /// Datasets which were not present after the previous harvest
+    pub added: usize,
+    /// Datasets which were present after the previous harvest but are not anymore
+    pub removed: usize,
+    /// Datasets whose stored payload differs from the previous harvest
+    pub changed: usize,
+    /// Datasets violating the validation rules of the source, whether skipped or not
+    pub invalid: usize,
+}
+
+impl HarvestCounters {
+    pub fn fetched(fetched: usize) -> Self {
+        Self {
+            fetched,
+            ..Default::default()
+        }
+    }
+
+    pub fn record(&mut self, res: Result<Outcome>) {
+        match res {
+            Ok(Outcome::Written { valid }) => {
+                self.parsed += 1;
+                self.written += 1;
+                self.invalid += usize::from(!valid);
+            }
+            Ok(Outcome::Duplicate { valid }) => {
+                self.parsed += 1;
+                self.duplicates += 1;
+                self.invalid += usize::from(!valid);
+            }
+            Ok(Outcome::Identical) => {
+                self.parsed += 1;
+                self.identical += 1;
+            }
+            Ok(Outcome::Disallowed) => self.disallowed += 1,
+            Ok(Outcome::Invalid) => {
+                self.parsed += 1;
+                self.invalid += 1;
+            }
+            Ok(Outcome::Filtered) => {
+                self.parsed += 1;
+                self.filtered += 1;
+            }
+            Err(err) => {
+                tracing::error!("{:#}", err);
+
+                self.failed += 1;
+            }
+        }
+    }
+}
+
+impl AddAssign for HarvestCounters {
+    fn add_assign(&mut self, other: Self) {
+        self.fetched += other.fetched;
+        self.parsed += other.parsed;
+        self.filtered += other.filtered;
+        self.written += other.written;
+        self.duplicates += other.duplicates;
+        self.identical += other.identical;
+        self.failed += other.failed;
+        self.kept += other.kept;
+        self.disallowed += other.disallowed;
+        self.concurrency = self.concurrency.max(other.concurrency);
+        self.aborted += other.aborted;
+        self.quarantined += other.quarantined;
+        self.added += other.added;
+        self.removed += other.removed;
+        self.changed += other.changed;
+        self.invalid += other.invalid;
+    }
+}
+
+/// Accounting of the requests sent to a single host across all sources during one harvest
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct HostTraffic {
+    /// Sources which sent requests to this host
+    pub sources: Vec<String>,
+    /// Requests sent to this host, not counting retries
+    pub requests: usize,
+    /// Bytes received in the bodies of the responses
+    pub bytes: u64,
+    /// Maximum number of requests if a budget is configured for this host
+    pub budget: Option<usize>,
+    /// Requests which were not sent as the budget was exhausted
+    pub refused: usize,
+}
+
+/// Result of writing a single dataset
+pub enum Outcome {
+    /// The dataset was written, possibly despite violating the validation rules of its source.
+    Written { valid: bool },
+    /// The dataset was merged with a previous one with the same ID yielded by the same source.
+    Duplicate { valid: bool },
+    /// The dataset was skipped as the source yielded it before with identical content.
+    Identical,
+    /// The dataset was not fetched as robots.txt disallows its URL.
+    Disallowed,
+    /// The dataset was skipped as it violates the validation rules of its source.
+    Invalid,
+    /// The dataset was skipped as it does not pass the filters of its source.
+    Filtered,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::env::temp_dir;
+    use std::fs::{create_dir_all, remove_dir_all};
+    use std::process;
+
+    use cap_std::ambient_authority;
+
+    #[test]
+    fn last_success_survives_outdated_format() {
+        let path = temp_dir().join(format!("umwelt-info-metrics-{}", process::id()));
+        create_dir_all(&path).unwrap();
+
+        let dir = Dir::open_ambient_dir(&path, ambient_authority()).unwrap();
+
+        let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+
+        let mut metrics = Metrics::default();
+        metrics.last_success.insert("uba-gdi".to_owned(), start);
+        metrics.write(&dir).unwrap();
+
+        dir.write("metrics", b"outdated").unwrap();
+
+        let metrics = Metrics::read(&dir).unwrap();
+        assert_eq!(metrics.last_success["uba-gdi"], start);
+
+        remove_dir_all(path).unwrap();
+    }
+}
@@ -0,0 +1,71 @@
+//! Normalization of provenances into stable facet paths
+//!
+//! Each source configures its provenance as a path like `/Bund/UBA/GDI`, listing the hierarchy from the
+//! most general level, e.g. country or land, down to the portal. To keep facets stable regardless of
+//! capitalization and spacing, components are lowercased and runs of whitespace are replaced by hyphens,
+//! e.g. `/Kommune/Stadt Leipzig` yields the facet `/kommune/stadt-leipzig`.
+use tantivy::schema::Facet;
+
+/// Builds the normalized facet of the given provenance path.
+///
+/// Empty components are skipped, so that `/Bund//UBA/` yields the same facet as `/Bund/UBA`.
+pub fn provenance_facet(provenance: &str) -> Facet {
+    Facet::from_path(provenance.split('/').filter_map(normalize_component))
+}
+
+/// Joins the levels of a hierarchy into a provenance path, replacing slashes contained within them.
+pub fn provenance_path<I, S>(levels: I) -> String
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut path = String::new();
+
+    for level in levels {
+        path.push('/');
+        path.push_str(&level.as_ref().trim().replace('/', "-"));
+    }
+
+    path
+}
+
+fn normalize_component(val: &str) -> Option<String> {
+    let val = val.split_whitespace().collect::<Vec<_>>().join("-");
+
+    if val.is_empty() {
+        return None;
+    }
+
+    Some(val.to_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_provenance_facets() {
+        assert_eq!(
+            provenance_facet("/Bund/UBA/GDI").to_string(),
+            "/bund/uba/gdi"
+        );
+        assert_eq!(
+            provenance_facet("/Kommune/Stadt  Leipzig").to_string(),
+            "/kommune/stadt-leipzig"
+        );
+        assert_eq!(provenance_facet("Bund//UBA/").to_string(), "/bund/uba");
+        assert_eq!(provenance_facet("/").to_string(), "/");
+        assert_eq!(
+            provenance_facet(&provenance_facet("/Bund/UBA").to_string()),
+            provenance_facet("/Bund/UBA")
+        );
+    }
+
+    #[test]
+    fn join_provenance_levels() {
+        assert_eq!(
+            provenance_path(["Land", "Sachsen", "Wasser/Abwasser "]),
+            "/Land/Sachsen/Wasser-Abwasser"
+        );
+    }
+}
@@ -0,0 +1,189 @@
+use std::io::{Read, Write};
+
+use anyhow::Result;
+use cap_std::fs::Dir;
+use serde::{Deserialize, Serialize};
+use serde_json::{from_str, to_vec_pretty};
+use tantivy::schema::Facet;
+
+use crate::provenance::provenance_facet;
+
+/// Machine-readable snapshot of the sources configured for the last harvest
+///
+/// It is written by the harvester to `$DATA_PATH/sources.json` so that the other programs
+/// do not need to parse `harvester.toml` to present sources to users.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Sources {
+    pub sources: Vec<Source>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Source {
+    pub name: String,
+    pub title: String,
+    pub group: Option<String>,
+    pub url: String,
+    pub provenance: String,
+    /// Weight by which the relevance of its datasets is multiplied, preferring originating agencies over aggregators
+    #[serde(default = "default_trust")]
+    pub trust: f64,
+    #[serde(default)]
+    pub visibility: Visibility,
+}
+
+/// Whether the datasets of a source are shown to the public
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Visibility {
+    #[default]
+    Public,
+    /// Datasets are only included in search results if requested using the admin token.
+    Hidden,
+    /// Datasets are shown but labelled as the source is still being evaluated.
+    Experimental,
+}
+
+impl Visibility {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Public => "public",
+            Self::Hidden => "hidden",
+            Self::Experimental => "experimental",
+        }
+    }
+}
+
+pub fn default_trust() -> f64 {
+    1.0
+}
+
+impl Sources {
+    pub fn read(dir: &Dir) -> Result<Self> {
+        let val = if let Ok(mut file) = dir.open("sources.json") {
+            let mut buf = String::new();
+            file.read_to_string(&mut buf)?;
+
+            from_str(&buf)?
+        } else {
+            Default::default()
+        };
+
+        Ok(val)
+    }
+
+    pub fn write(&self, dir: &Dir) -> Result<()> {
+        let buf = to_vec_pretty(self)?;
+
+        let mut file = dir.create("sources.json.new")?;
+        file.write_all(&buf)?;
+        dir.rename("sources.json.new", dir, "sources.json")?;
+
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Source> {
+        self.sources.iter().find(|source| source.name == name)
+    }
+
+    /// Yields the trust level of the given source or the default if it is unknown.
+    pub fn trust(&self, name: &str) -> f64 {
+        self.get(name)
+            .map_or_else(default_trust, |source| source.trust)
+    }
+
+    /// Yields the visibility of the given source which defaults to public if it is unknown.
+    pub fn visibility(&self, name: &str) -> Visibility {
+        self.get(name)
+            .map_or_else(Default::default, |source| source.visibility)
+    }
+
+    /// Yields the title of the source whose provenance matches the given facet value
+    /// or the name of the group if the facet value matches a group of sources.
+    pub fn provenance_title(&self, provenance: &impl ToString) -> Option<&str> {
+        let provenance = provenance_facet(&provenance.to_string());
+
+        self.sources
+            .iter()
+            .find(|source| source.provenance_facet() == provenance)
+            .map(|source| source.title.as_str())
+            .or_else(|| {
+                self.sources
+                    .iter()
+                    .filter_map(|source| source.group.as_deref())
+                    .find(|group| provenance_facet(group) == provenance)
+            })
+    }
+}
+
+impl Source {
+    /// Yields the facet under which the datasets of this source are indexed.
+    ///
+    /// It is the normalized provenance placed below the group of the source unless it already starts with it,
+    /// e.g. a source of the group `Bund` with the provenance `/UBA/GDI` yields `/bund/uba/gdi`.
+    pub fn provenance_facet(&self) -> Facet {
+        let provenance = provenance_facet(&self.provenance);
+
+        let Some(group) = &self.group else {
+            return provenance;
+        };
+
+        let group = provenance_facet(group);
+
+        if group.is_root() || group.is_prefix_of(&provenance) {
+            return provenance;
+        }
+
+        Facet::from_path(group.to_path().into_iter().chain(provenance.to_path()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(group: Option<&str>, provenance: &str) -> Source {
+        Source {
+            name: "gdi".to_owned(),
+            title: "UBA Geodateninfrastruktur".to_owned(),
+            group: group.map(ToOwned::to_owned),
+            url: "https://gdi.uba.de".to_owned(),
+            provenance: provenance.to_owned(),
+            trust: default_trust(),
+            visibility: Visibility::Public,
+        }
+    }
+
+    #[test]
+    fn provenances_are_placed_below_groups() {
+        assert_eq!(
+            source(Some("Bund"), "/Bund/UBA/GDI")
+                .provenance_facet()
+                .to_string(),
+            "/bund/uba/gdi"
+        );
+        assert_eq!(
+            source(Some("Bund"), "/UBA/GDI")
+                .provenance_facet()
+                .to_string(),
+            "/bund/uba/gdi"
+        );
+        assert_eq!(
+            source(None, "/UBA/GDI").provenance_facet().to_string(),
+            "/uba/gdi"
+        );
+    }
+
+    #[test]
+    fn groups_label_provenances() {
+        let sources = Sources {
+            sources: vec![source(Some("Bund"), "/UBA/GDI")],
+        };
+
+        assert_eq!(
+            sources.provenance_title(&"/bund/uba/gdi"),
+            Some("UBA Geodateninfrastruktur")
+        );
+        assert_eq!(sources.provenance_title(&"/bund"), Some("Bund"));
+        assert_eq!(sources.provenance_title(&"/land"), None);
+    }
+}
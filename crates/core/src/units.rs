@@ -0,0 +1,147 @@
+//! Tokenization which keeps measurement units and decimal numbers together, e.g. `µg/m³` or `PM2,5`
+//!
+//! Otherwise equivalent to tantivy's `SimpleTokenizer`, but separators are kept within a token
+//! if they join two known units like `mg/l` or two digits like `2,5`. The micro sign and the Greek letter mu,
+//! superscript and plain digits as well as decimal commas and points are normalized so that e.g.
+//! `PM2.5 μg/m3` matches `PM2,5 µg/m³`.
+use tantivy::tokenizer::{BoxTokenStream, Token, TokenStream, Tokenizer};
+
+/// Units which are joined when separated by a slash after normalization and lowercasing
+const UNITS: &[&str] = &[
+    "a", "bq", "cm", "d", "db", "g", "gwh", "h", "ha", "kg", "km", "km2", "kw", "kwh", "l", "m",
+    "m2", "m3", "mg", "min", "ml", "mm", "mmol", "mol", "mw", "mwh", "ng", "nm", "pg", "ppb",
+    "ppm", "s", "t", "w", "µg", "µm", "µmol", "µs",
+];
+
+#[derive(Clone)]
+pub struct UnitTokenizer;
+
+impl Tokenizer for UnitTokenizer {
+    fn token_stream<'a>(&self, text: &'a str) -> BoxTokenStream<'a> {
+        BoxTokenStream::from(UnitTokenStream {
+            tokens: tokenize(text).into_iter(),
+            token: Token::default(),
+        })
+    }
+}
+
+struct UnitTokenStream {
+    tokens: std::vec::IntoIter<Token>,
+    token: Token,
+}
+
+impl TokenStream for UnitTokenStream {
+    fn advance(&mut self) -> bool {
+        match self.tokens.next() {
+            Some(token) => {
+                self.token = token;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn token(&self) -> &Token {
+        &self.token
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.token
+    }
+}
+
+fn tokenize(text: &str) -> Vec<Token> {
+    let chars = text.char_indices().collect::<Vec<_>>();
+
+    let run_end = |start: usize| {
+        chars[start..]
+            .iter()
+            .position(|(_, char)| !char.is_alphanumeric())
+            .map_or(chars.len(), |len| start + len)
+    };
+
+    let is_unit = |start: usize, end: usize| {
+        let unit = chars[start..end]
+            .iter()
+            .flat_map(|(_, char)| normalize(*char).to_lowercase())
+            .collect::<String>();
+
+        UNITS.contains(&&*unit)
+    };
+
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+
+    while pos < chars.len() {
+        if !chars[pos].1.is_alphanumeric() {
+            pos += 1;
+            continue;
+        }
+
+        let start = pos;
+        let mut segment = pos;
+        pos = run_end(pos);
+
+        while let (Some((_, sep)), Some((_, next))) = (chars.get(pos), chars.get(pos + 1)) {
+            let joined = match sep {
+                ',' | '.' => chars[pos - 1].1.is_ascii_digit() && next.is_ascii_digit(),
+                '/' => {
+                    next.is_alphanumeric()
+                        && is_unit(segment, pos)
+                        && is_unit(pos + 1, run_end(pos + 1))
+                }
+                _ => false,
+            };
+
+            if !joined {
+                break;
+            }
+
+            segment = pos + 1;
+            pos = run_end(segment);
+        }
+
+        tokens.push(Token {
+            offset_from: chars[start].0,
+            offset_to: chars.get(pos).map_or(text.len(), |(offset, _)| *offset),
+            position: tokens.len(),
+            text: chars[start..pos]
+                .iter()
+                .map(|(_, char)| normalize(*char))
+                .collect(),
+            position_length: 1,
+        });
+    }
+
+    tokens
+}
+
+fn normalize(char: char) -> char {
+    match char {
+        'μ' => 'µ',
+        '¹' => '1',
+        '²' => '2',
+        '³' => '3',
+        ',' => '.',
+        char => char,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(text: &str) -> Vec<String> {
+        tokenize(text).into_iter().map(|token| token.text).collect()
+    }
+
+    #[test]
+    fn units_are_kept_together() {
+        assert_eq!(
+            texts("Feinstaub (PM2,5) in µg/m³, Nitrat in mg/l."),
+            ["Feinstaub", "PM2.5", "in", "µg/m3", "Nitrat", "in", "mg/l"]
+        );
+        assert_eq!(texts("PM2.5 μg/m3"), ["PM2.5", "µg/m3"]);
+        assert_eq!(texts("Luft/Wasser 2, 5."), ["Luft", "Wasser", "2", "5"]);
+    }
+}
@@ -0,0 +1,293 @@
+//! This harvester maps the protected areas published by the Bundesamt für Naturschutz (BfN) into our catalogue.
+//!
+//! The areas are paged through as GeoJSON using the WFS of the BfN. The `url` of the source is expected to point to the service,
+//! e.g. `https://geodienste.bfn.de/ogc/wfs/schutzgebiet`, and the `filter` to name the feature type, e.g. `bfn_sch_Schutzgebiet:Naturschutzgebiete`.
+//!
+//! | Original field   | Mapped field | Comment                                                                |
+//! | ---------------- | ------------ | ---------------------------------------------------------------------- |
+//! | id               | id           | Used only if KENNUNG is missing                                        |
+//! | NAME             | title        | Feature skipped if missing                                             |
+//! | KENNUNG          | id           | Official designation, e.g. `DE-1234-301` for FFH areas                 |
+//! | SCHUTZGEBIETSTYP | description  | Abbreviations like NSG or FFH are expanded and also added as tags      |
+//! | BUNDESLAND       | region       |                                                                        |
+//! | FLAECHE_HA       | description  |                                                                        |
+//! |                  | resources    | The feature itself via GetFeature as GML and GeoJSON                   |
+//! |                  | tags         | Naturschutz and Schutzgebiet are added to all areas                    |
+use std::time::SystemTime;
+
+use anyhow::{anyhow, Result};
+use bytes::Bytes;
+use cap_std::fs::Dir;
+use futures_util::future::{BoxFuture, FutureExt};
+use serde::Deserialize;
+use serde_json::from_slice;
+use smallvec::smallvec;
+
+use umwelt_info_core::dataset::{Dataset, License, Resource, Tag};
+
+use crate::{
+    client::Client, fetch_many, write_dataset, HarvestCounters, Harvester, Outcome, Source,
+};
+
+pub struct Bfn;
+
+impl Harvester for Bfn {
+    fn harvest<'a>(
+        &self,
+        dir: &'a Dir,
+        client: &'a Client,
+        source: &'a Source,
+        _since: Option<SystemTime>,
+    ) -> BoxFuture<'a, Result<(usize, HarvestCounters)>> {
+        harvest(dir, client, source).boxed()
+    }
+}
+
+pub async fn harvest(
+    dir: &Dir,
+    client: &Client,
+    source: &Source,
+) -> Result<(usize, HarvestCounters)> {
+    let type_name = source
+        .filter
+        .as_deref()
+        .ok_or_else(|| anyhow!("Feature type of source {} not configured", source.name))?;
+
+    let count = source.batch_size;
+
+    let (matched, counters) = fetch_areas(dir, client, source, type_name, 0, count).await?;
+    tracing::info!("Harvesting {} areas", matched);
+
+    let requests = (matched + count - 1) / count;
+    let start_index = (1..requests).map(|request| request * count);
+
+    let counters = fetch_many(source, counters, start_index, |start_index| {
+        fetch_areas(dir, client, source, type_name, start_index, count)
+    })
+    .await;
+
+    Ok((matched, counters))
+}
+
+#[tracing::instrument(skip(dir, client, source))]
+async fn fetch_areas(
+    dir: &Dir,
+    client: &Client,
+    source: &Source,
+    type_name: &str,
+    start_index: usize,
+    count: usize,
+) -> Result<(usize, HarvestCounters)> {
+    tracing::debug!("Fetching {} areas starting at {}", count, start_index);
+
+    let mut url = source.url.clone();
+
+    url.query_pairs_mut()
+        .append_pair("service", "WFS")
+        .append_pair("version", "2.0.0")
+        .append_pair("request", "GetFeature")
+        .append_pair("typeNames", type_name)
+        .append_pair("outputFormat", "application/json")
+        .append_pair("count", &count.to_string())
+        .append_pair("startIndex", &start_index.to_string());
+
+    let key = format!("{}-{}", source.name, start_index);
+
+    let body: Bytes = client
+        .make_request(&key, |client| client.get(url.clone()))
+        .await?;
+
+    let collection = from_slice::<FeatureCollection>(&body)?;
+
+    let matched = collection.number_matched;
+    let mut counters = HarvestCounters::fetched(collection.features.len());
+
+    for feature in collection.features {
+        counters.record(client.quarantine(
+            &key,
+            translate_dataset(dir, source, type_name, feature).await,
+        ));
+    }
+
+    Ok((matched, counters))
+}
+
+async fn translate_dataset(
+    dir: &Dir,
+    source: &Source,
+    type_name: &str,
+    feature: Feature,
+) -> Result<Outcome> {
+    let properties = feature.properties;
+
+    let title = properties
+        .name
+        .ok_or_else(|| anyhow!("Area {} has no name", feature.id))?;
+
+    let mut tags = vec![Tag::from("Naturschutz"), Tag::from("Schutzgebiet")];
+
+    let designation = properties
+        .designation
+        .as_deref()
+        .map(|designation| expand_designation(designation.trim()));
+
+    if let Some(designation) = designation {
+        tags.push(designation.into());
+    }
+
+    let description = match (designation, properties.area_ha) {
+        (Some(designation), Some(area_ha)) => Some(format!(
+            "{designation} mit einer Fläche von {area_ha:.1} ha"
+        )),
+        (Some(designation), None) => Some(designation.to_owned()),
+        (None, Some(area_ha)) => Some(format!("Schutzgebiet mit einer Fläche von {area_ha:.1} ha")),
+        (None, None) => None,
+    };
+
+    let resources = smallvec![
+        Resource::unknown(get_feature(source, type_name, &feature.id, None)),
+        Resource::unknown(get_feature(
+            source,
+            type_name,
+            &feature.id,
+            Some("application/json")
+        )),
+    ];
+
+    let dataset = Dataset {
+        title,
+        description,
+        summary: None,
+        comment: None,
+        provenance: source.provenance.clone(),
+        publisher: None,
+        license: License::Unknown,
+        legal_basis: Vec::new(),
+        reporting_obligations: Vec::new(),
+        contacts: Vec::new(),
+        tags,
+        themes: Vec::new(),
+        region: properties.federal_state,
+        language: None,
+        bbox: None,
+        time_range: None,
+        issued: None,
+        modified: None,
+        update_frequency: None,
+        last_checked: None,
+        first_seen: None,
+        identifiers: Default::default(),
+        relations: Vec::new(),
+        source_url: source.source_url().to_owned(),
+        resources,
+    };
+
+    let id = properties.designation_id.unwrap_or(feature.id);
+
+    write_dataset(dir, source, &id, dataset).await
+}
+
+/// Builds the URL to download a single area from the WFS, as GML if no output format is given.
+fn get_feature(source: &Source, type_name: &str, id: &str, output_format: Option<&str>) -> String {
+    let mut url = source.url.clone();
+
+    {
+        let mut query_pairs = url.query_pairs_mut();
+
+        query_pairs
+            .append_pair("service", "WFS")
+            .append_pair("version", "2.0.0")
+            .append_pair("request", "GetFeature")
+            .append_pair("typeNames", type_name)
+            .append_pair("resourceId", id);
+
+        if let Some(output_format) = output_format {
+            query_pairs.append_pair("outputFormat", output_format);
+        }
+    }
+
+    url.into()
+}
+
+/// Expands the abbreviations of designation types used by the BfN and the federal states.
+fn expand_designation(designation: &str) -> &str {
+    match designation {
+        "NSG" => "Naturschutzgebiet",
+        "LSG" => "Landschaftsschutzgebiet",
+        "NLP" => "Nationalpark",
+        "NNM" => "Nationales Naturmonument",
+        "BR" | "BSR" => "Biosphärenreservat",
+        "NP" | "NRP" => "Naturpark",
+        "FFH" => "FFH-Gebiet",
+        "SPA" | "VSG" => "Vogelschutzgebiet",
+        designation => designation,
+    }
+}
+
+#[derive(Deserialize)]
+struct FeatureCollection {
+    #[serde(rename = "numberMatched", alias = "totalFeatures")]
+    number_matched: usize,
+    features: Vec<Feature>,
+}
+
+#[derive(Deserialize)]
+struct Feature {
+    id: String,
+    properties: Properties,
+}
+
+#[derive(Deserialize)]
+struct Properties {
+    #[serde(rename = "NAME")]
+    name: Option<String>,
+    #[serde(rename = "KENNUNG")]
+    designation_id: Option<String>,
+    #[serde(rename = "SCHUTZGEBIETSTYP")]
+    designation: Option<String>,
+    #[serde(rename = "BUNDESLAND")]
+    federal_state: Option<String>,
+    #[serde(rename = "FLAECHE_HA")]
+    area_ha: Option<f64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json::from_str;
+
+    #[test]
+    fn parse_feature_collection() {
+        let collection = from_str::<FeatureCollection>(
+            r#"{
+                "type": "FeatureCollection",
+                "numberMatched": 8755,
+                "numberReturned": 1,
+                "features": [{
+                    "type": "Feature",
+                    "id": "Naturschutzgebiete.1",
+                    "geometry": null,
+                    "properties": {
+                        "NAME": "Lüneburger Heide",
+                        "KENNUNG": "NSG LÜ 002",
+                        "SCHUTZGEBIETSTYP": "NSG",
+                        "BUNDESLAND": "Niedersachsen",
+                        "FLAECHE_HA": 23440.2
+                    }
+                }]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(collection.number_matched, 8755);
+
+        let properties = &collection.features[0].properties;
+        assert_eq!(properties.designation_id.as_deref(), Some("NSG LÜ 002"));
+        assert_eq!(
+            expand_designation(properties.designation.as_deref().unwrap()),
+            "Naturschutzgebiet"
+        );
+        assert_eq!(expand_designation("Wildnisgebiet"), "Wildnisgebiet");
+    }
+}
@@ -0,0 +1,1032 @@
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::fmt;
+use std::io::Read;
+use std::time::SystemTime;
+
+use anyhow::{anyhow, Result};
+use bytes::Bytes;
+use cap_std::fs::Dir;
+use futures_util::future::{BoxFuture, FutureExt};
+use reqwest::{header::AUTHORIZATION, Client as HttpClient, RequestBuilder, StatusCode};
+use serde::{
+    de::{DeserializeSeed, Deserializer, Error as _, IgnoredAny, MapAccess, SeqAccess, Visitor},
+    Deserialize, Serialize,
+};
+use serde_json::{from_slice, from_str, value::RawValue, Deserializer as JsonDeserializer, Value};
+use smallvec::SmallVec;
+use tokio::{
+    sync::mpsc::{channel, Sender},
+    task::spawn_blocking,
+};
+use url::Url;
+
+use umwelt_info_core::dataset::{
+    Dataset, Identifier, Language, Publisher, Relation, RelationTarget, RelationType, Resource,
+    ResourceType, Theme, TimeRange, UpdateFrequency,
+};
+
+use crate::{
+    client::Client, date::parse_date, fetch_many, fetch_remaining_pages, modified_since,
+    write_dataset, HarvestCounters, Harvester, Outcome, Source,
+};
+
+pub struct Ckan;
+
+impl Harvester for Ckan {
+    fn harvest<'a>(
+        &self,
+        dir: &'a Dir,
+        client: &'a Client,
+        source: &'a Source,
+        since: Option<SystemTime>,
+    ) -> BoxFuture<'a, Result<(usize, HarvestCounters)>> {
+        harvest(dir, client, source, since).boxed()
+    }
+
+    fn supports_delta(&self) -> bool {
+        true
+    }
+}
+
+/// Harvests all packages or if `since` is given, only those modified since then.
+pub async fn harvest(
+    dir: &Dir,
+    client: &Client,
+    source: &Source,
+    since: Option<SystemTime>,
+) -> Result<(usize, HarvestCounters)> {
+    let rows = source.batch_size;
+
+    let fq = since.map(|since| format!("metadata_modified:[{} TO *]", modified_since(since)));
+    let fq = fq.as_deref();
+
+    let (count, counters) = match fetch_datasets(dir, client, source, fq, 0, rows).await {
+        Ok(res) => res,
+        Err(err) if is_search_unavailable(&err) => {
+            // Listing packages does not support filtering, so this always yields all of them.
+            tracing::warn!(
+                "Falling back to listing packages as searching them failed: {:#}",
+                err
+            );
+
+            return list_datasets(dir, client, source).await;
+        }
+        Err(err) => return Err(err),
+    };
+
+    let counters = fetch_remaining_pages(source, count, counters, |start, rows| {
+        fetch_datasets(dir, client, source, fq, start, rows)
+    })
+    .await;
+
+    Ok((count, counters))
+}
+
+/// Yields whether `package_search` is rejected as broken or disabled, i.e. with a `400`, `403` or `404` status.
+///
+/// Other failures like timeouts or server errors were already retried by the client and fail the harvest.
+fn is_search_unavailable(err: &anyhow::Error) -> bool {
+    err.chain()
+        .filter_map(|err| err.downcast_ref::<reqwest::Error>())
+        .any(|err| {
+            matches!(
+                err.status(),
+                Some(StatusCode::BAD_REQUEST | StatusCode::FORBIDDEN | StatusCode::NOT_FOUND)
+            )
+        })
+}
+
+#[tracing::instrument(skip(dir, client, source))]
+async fn fetch_datasets(
+    dir: &Dir,
+    client: &Client,
+    source: &Source,
+    fq: Option<&str>,
+    start: usize,
+    rows: usize,
+) -> Result<(usize, HarvestCounters)> {
+    tracing::debug!("Fetching {} datasets starting at {}", rows, start);
+
+    #[derive(Serialize)]
+    struct Params<'a> {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        fq: Option<&'a str>,
+        start: usize,
+        rows: usize,
+        include_private: bool,
+    }
+
+    let params = Params {
+        fq,
+        start,
+        rows,
+        include_private: source.api_key().is_some() || source.auth.sets_authorization(),
+    };
+
+    let key = format!("{}-{}", source.name, start);
+
+    // Pages can be hundreds of megabytes large, so packages are translated while the response is parsed.
+    let url = action_url(source, "package_search")?;
+    let api_key = source.api_key();
+
+    let reader = client
+        .make_streaming_request(&key, |client| {
+            action_request(client, &url, api_key.as_deref(), &params)
+        })
+        .await?;
+
+    let (sender, mut receiver) = channel(16);
+
+    let parser = spawn_blocking(move || stream_packages(reader, sender));
+
+    let mut counters = HarvestCounters::default();
+
+    while let Some(raw) = receiver.recv().await {
+        counters.fetched += 1;
+
+        let res = async {
+            let package = from_str::<Package>(raw.get())?;
+
+            client.preserve(&package.id, raw.get().as_bytes());
+
+            translate_dataset(dir, source, package).await
+        };
+
+        counters.record(client.quarantine(&key, res.await));
+    }
+
+    let count = parser.await??;
+
+    Ok((count, counters))
+}
+
+/// Parses a `package_search` response passing on each package as soon as it was read, yielding their total count.
+fn stream_packages<R>(reader: R, sender: Sender<Box<RawValue>>) -> Result<usize>
+where
+    R: Read,
+{
+    let mut deserializer = JsonDeserializer::from_reader(reader);
+
+    let count = PackageSearchSeed(&sender).deserialize(&mut deserializer)?;
+
+    deserializer.end()?;
+
+    Ok(count)
+}
+
+struct PackageSearchSeed<'a>(&'a Sender<Box<RawValue>>);
+
+impl<'de> DeserializeSeed<'de> for PackageSearchSeed<'_> {
+    type Value = usize;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for PackageSearchSeed<'_> {
+    type Value = usize;
+
+    fn expecting(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str("a response to package_search")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut success = false;
+        let mut error = None;
+        let mut count = None;
+
+        while let Some(key) = map.next_key::<String>()? {
+            match &*key {
+                "success" => success = map.next_value()?,
+                "error" => error = map.next_value::<Option<CkanError>>()?,
+                "result" => count = Some(map.next_value_seed(ResultSeed(self.0))?),
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+
+        match count {
+            Some(count) if success => Ok(count),
+            _ => Err(A::Error::custom(format!(
+                "Failed to call action: {}",
+                error
+                    .as_ref()
+                    .map_or("Malformed response", |err| &err.message)
+            ))),
+        }
+    }
+}
+
+struct ResultSeed<'a>(&'a Sender<Box<RawValue>>);
+
+impl<'de> DeserializeSeed<'de> for ResultSeed<'_> {
+    type Value = usize;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for ResultSeed<'_> {
+    type Value = usize;
+
+    fn expecting(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str("a result of package_search")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut count = None;
+
+        while let Some(key) = map.next_key::<String>()? {
+            match &*key {
+                "count" => count = Some(map.next_value()?),
+                "results" => map.next_value_seed(PackagesSeed(self.0))?,
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+
+        count.ok_or_else(|| A::Error::missing_field("count"))
+    }
+}
+
+struct PackagesSeed<'a>(&'a Sender<Box<RawValue>>);
+
+impl<'de> DeserializeSeed<'de> for PackagesSeed<'_> {
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de> Visitor<'de> for PackagesSeed<'_> {
+    type Value = ();
+
+    fn expecting(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str("a list of packages")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        while let Some(package) = seq.next_element()? {
+            self.0
+                .blocking_send(package)
+                .map_err(|_err| A::Error::custom("Packages are not received anymore"))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Enumerates all packages via `package_list` and fetches them one by one via `package_show`
+/// for those instances where `package_search` is broken or disabled.
+async fn list_datasets(
+    dir: &Dir,
+    client: &Client,
+    source: &Source,
+) -> Result<(usize, HarvestCounters)> {
+    let body = call_action(
+        client,
+        source,
+        &format!("{}-list", source.name),
+        "package_list",
+        &(),
+    )
+    .await?;
+
+    let names = from_slice::<Response<Vec<String>>>(&body)?.into_result()?;
+
+    let count = names.len();
+    tracing::info!("Harvesting {} datasets", count);
+
+    let counters = fetch_many(
+        source,
+        HarvestCounters::default(),
+        names.into_iter(),
+        |name| async move {
+            let mut counters = HarvestCounters::fetched(1);
+
+            counters.record(fetch_dataset(dir, client, source, &name).await);
+
+            Ok((1, counters))
+        },
+    )
+    .await;
+
+    Ok((count, counters))
+}
+
+#[tracing::instrument(skip(dir, client, source))]
+async fn fetch_dataset(dir: &Dir, client: &Client, source: &Source, name: &str) -> Result<Outcome> {
+    tracing::debug!("Fetching dataset");
+
+    #[derive(Serialize)]
+    struct Params<'a> {
+        id: &'a str,
+    }
+
+    let key = format!("{}-show-{}", source.name, name);
+
+    let body = call_action(client, source, &key, "package_show", &Params { id: name }).await?;
+
+    let res = async {
+        let raw = from_slice::<Response<&RawValue>>(&body)?.into_result()?;
+
+        let package = from_str::<Package>(raw.get())?;
+
+        client.preserve(&package.id, raw.get().as_bytes());
+
+        translate_dataset(dir, source, package).await
+    };
+
+    client.quarantine(&key, res.await)
+}
+
+async fn call_action<P>(
+    client: &Client,
+    source: &Source,
+    key: &str,
+    action: &str,
+    params: &P,
+) -> Result<Bytes>
+where
+    P: Serialize + ?Sized,
+{
+    let url = action_url(source, action)?;
+
+    let api_key = source.api_key();
+
+    let body = client
+        .make_request(key, |client| {
+            action_request(client, &url, api_key.as_deref(), params)
+        })
+        .await?;
+
+    Ok(body)
+}
+
+fn action_url(source: &Source, action: &str) -> Result<Url> {
+    let url = source.url.join("api/3/action/")?.join(action)?;
+
+    Ok(url)
+}
+
+fn action_request<P>(
+    client: &HttpClient,
+    url: &Url,
+    api_key: Option<&str>,
+    params: &P,
+) -> RequestBuilder
+where
+    P: Serialize + ?Sized,
+{
+    let mut request = client.get(url.clone()).query(params);
+
+    if let Some(api_key) = api_key {
+        request = request.header(AUTHORIZATION, api_key);
+    }
+
+    request
+}
+
+pub(super) async fn translate_dataset(
+    dir: &Dir,
+    source: &Source,
+    package: Package<'_>,
+) -> Result<Outcome> {
+    let license = package.license().into();
+
+    let issued = package.extra("issued").and_then(parse_date);
+
+    // ckanext-dcat stores `dct:modified` as `modified` while CKAN itself tracks when the package was last changed.
+    let modified = package
+        .extra("modified")
+        .or(package.metadata_modified.as_deref())
+        .and_then(parse_date);
+
+    let bbox = package.extra("spatial").and_then(parse_spatial);
+
+    // ckanext-dcat stores the start and end of `dct:temporal` as `temporal_start` and `temporal_end`.
+    let time_range = TimeRange::new(
+        package.extra("temporal_start").and_then(parse_date),
+        package.extra("temporal_end").and_then(parse_date),
+    );
+
+    let publisher = package.publisher();
+
+    let language = package.extra("language").and_then(Language::parse);
+
+    // DCAT-AP.de stores the legal basis as `legalbasisText`.
+    let legal_basis = package
+        .extra("legalbasisText")
+        .map(|text| vec![text.split_whitespace().collect::<Vec<_>>().join(" ")])
+        .unwrap_or_default();
+
+    // DCAT-AP.de stores `dct:identifier` as `identifier` while some portals add a dedicated `doi` extra.
+    let mut identifiers = SmallVec::new();
+
+    for key in ["doi", "identifier"] {
+        if let Some(identifier) = package.extra(key).and_then(Identifier::parse) {
+            if !identifiers.contains(&identifier) {
+                identifiers.push(identifier);
+            }
+        }
+    }
+
+    let relations = package.relations(&source.name);
+
+    // ckanext-dcat stores `dct:accrualPeriodicity` as `frequency`.
+    let update_frequency = ["frequency", "accrual_periodicity"]
+        .into_iter()
+        .find_map(|key| package.extra(key).and_then(UpdateFrequency::parse));
+
+    // Keep the order curated by the publisher which is not necessarily the order of the response.
+    let mut resources = package.resources;
+    resources.sort_by_key(|resource| resource.position);
+
+    let resources = resources
+        .into_iter()
+        .map(|resource| Resource {
+            r#type: resource
+                .format
+                .as_deref()
+                .map_or(ResourceType::Unknown, Into::into),
+            url: resource.url,
+            title: resource.name.filter(|name| !name.trim().is_empty()),
+            description: resource
+                .description
+                .filter(|description| !description.trim().is_empty()),
+            license: resource
+                .license
+                .as_deref()
+                .filter(|license| !license.is_empty())
+                .map(Into::into),
+            content_type: None,
+            format: resource
+                .format
+                .as_deref()
+                .map(str::trim)
+                .filter(|format| !format.is_empty())
+                .map(ToOwned::to_owned),
+            content_length: resource.size,
+            checksum: resource.hash.filter(|hash| !hash.trim().is_empty()),
+            link: None,
+        })
+        .collect();
+
+    let tags = package
+        .tags
+        .iter()
+        .map(|tag| tag.name.as_ref())
+        .chain(package.groups.iter().map(|group| {
+            group
+                .title
+                .as_deref()
+                .filter(|title| !title.is_empty())
+                .unwrap_or(&group.name)
+        }))
+        .map(Into::into)
+        .collect();
+
+    // GovData names its groups after the DCAT-AP data themes, e.g. `envi`.
+    let mut themes = Vec::new();
+
+    for group in &package.groups {
+        if let Some(theme) =
+            Theme::parse(&group.name).or_else(|| group.title.as_deref().and_then(Theme::parse))
+        {
+            if !themes.contains(&theme) {
+                themes.push(theme);
+            }
+        }
+    }
+
+    let dataset = Dataset {
+        title: package.title,
+        description: package.notes,
+        summary: None,
+        comment: None,
+        provenance: source.provenance.clone(),
+        publisher,
+        license,
+        legal_basis,
+        reporting_obligations: Vec::new(),
+        contacts: Vec::new(),
+        tags,
+        themes,
+        region: None,
+        language,
+        bbox,
+        time_range,
+        issued,
+        modified,
+        update_frequency,
+        last_checked: None,
+        first_seen: None,
+        identifiers,
+        relations,
+        source_url: source.source_url().replace("{{name}}", &package.name),
+        resources,
+    };
+
+    write_dataset(dir, source, &package.id, dataset).await
+}
+
+/// Derives the bounding box of the GeoJSON geometry stored in the `spatial` extra by the ckanext-spatial extension.
+fn parse_spatial(spatial: &str) -> Option<[f64; 4]> {
+    fn extend(coordinates: &Value, bbox: &mut Option<[f64; 4]>) {
+        let Some(values) = coordinates.as_array() else {
+            return;
+        };
+
+        let position = (
+            values.first().and_then(Value::as_f64),
+            values.get(1).and_then(Value::as_f64),
+        );
+
+        if let (Some(lon), Some(lat)) = position {
+            *bbox = Some(match *bbox {
+                Some([west, south, east, north]) => {
+                    [west.min(lon), south.min(lat), east.max(lon), north.max(lat)]
+                }
+                None => [lon, lat, lon, lat],
+            });
+        } else {
+            for value in values {
+                extend(value, bbox);
+            }
+        }
+    }
+
+    let geometry = from_str::<Value>(spatial).ok()?;
+
+    let mut bbox = None;
+    extend(geometry.get("coordinates")?, &mut bbox);
+
+    bbox
+}
+
+#[derive(Deserialize)]
+struct Response<'a, T> {
+    success: bool,
+    #[serde(borrow)]
+    error: Option<CkanError<'a>>,
+    result: Option<T>,
+}
+
+impl<T> Response<'_, T> {
+    fn into_result(self) -> Result<T> {
+        match self.result {
+            Some(result) if self.success => Ok(result),
+            _ => Err(anyhow!(
+                "Failed to call action: {}",
+                self.error
+                    .as_ref()
+                    .map_or("Malformed response", |err| &err.message)
+            )),
+        }
+    }
+}
+
+#[derive(Default, Deserialize)]
+pub(super) struct Package<'a> {
+    #[serde(borrow)]
+    id: Cow<'a, str>,
+    #[serde(borrow)]
+    name: Cow<'a, str>,
+    title: String,
+    notes: Option<String>,
+    #[serde(borrow)]
+    license_id: Option<Cow<'a, str>>,
+    #[serde(borrow)]
+    metadata_modified: Option<Cow<'a, str>>,
+    #[serde(default, borrow)]
+    resources: Vec<CkanResource<'a>>,
+    #[serde(default, borrow)]
+    tags: Vec<CkanTag<'a>>,
+    #[serde(default, borrow)]
+    groups: Vec<CkanGroup<'a>>,
+    #[serde(default, borrow)]
+    extras: Vec<CkanExtra<'a>>,
+    #[serde(borrow)]
+    organization: Option<CkanOrganization<'a>>,
+}
+
+impl Package<'_> {
+    fn extra(&self, key: &str) -> Option<&str> {
+        self.extras
+            .iter()
+            .find(|extra| extra.key == key)
+            .map(|extra| extra.value.trim())
+            .filter(|value| !value.is_empty())
+    }
+
+    /// Relations stored by ckanext-dcat as JSON-encoded lists of URIs or as plain values by other portals
+    fn relations(&self, source: &str) -> Vec<Relation> {
+        let mut relations = Vec::new();
+
+        for (key, r#type) in [
+            ("is_part_of", RelationType::IsPartOf),
+            ("has_part", RelationType::HasPart),
+            ("is_version_of", RelationType::IsVersionOf),
+            ("has_version", RelationType::HasVersion),
+        ] {
+            let Some(val) = self.extra(key) else {
+                continue;
+            };
+
+            let vals = match from_str::<Vec<String>>(val) {
+                Ok(vals) => vals,
+                Err(_err) => vec![val.to_owned()],
+            };
+
+            for val in vals {
+                if let Some(target) = RelationTarget::parse(source, &val) {
+                    let relation = Relation { r#type, target };
+
+                    if !relations.contains(&relation) {
+                        relations.push(relation);
+                    }
+                }
+            }
+        }
+
+        relations
+    }
+
+    /// The organization owning the package, linked via the `publisher_url` extra defined by DCAT-AP.de if available.
+    fn publisher(&self) -> Option<Publisher> {
+        let organization = self.organization.as_ref()?;
+
+        let name = organization
+            .title
+            .as_deref()
+            .filter(|title| !title.trim().is_empty())
+            .unwrap_or(&organization.name)
+            .trim()
+            .to_owned();
+
+        if name.is_empty() {
+            return None;
+        }
+
+        let url = self.extra("publisher_url").map(ToOwned::to_owned);
+
+        Some(Publisher { name, url })
+    }
+
+    fn license(&self) -> Option<&str> {
+        if let Some(license_id) = &self.license_id {
+            if !license_id.is_empty() {
+                return Some(license_id);
+            }
+        }
+
+        match self.resources.len().cmp(&1) {
+            Ordering::Less => None,
+            Ordering::Equal => self.resources[0].license.as_deref(),
+            Ordering::Greater => {
+                let (head, tail) = self.resources.split_first().unwrap();
+
+                if tail.iter().all(|resource| resource.license == head.license) {
+                    head.license.as_deref()
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
+
+#[derive(Default, Deserialize)]
+struct CkanResource<'a> {
+    url: String,
+    name: Option<String>,
+    description: Option<String>,
+    position: Option<usize>,
+    #[serde(borrow)]
+    license: Option<Cow<'a, str>>,
+    #[serde(borrow)]
+    format: Option<Cow<'a, str>>,
+    /// Given as a number or a string by different versions of CKAN
+    #[serde(default, deserialize_with = "deserialize_size")]
+    size: Option<u64>,
+    hash: Option<String>,
+}
+
+fn deserialize_size<'de, D>(deserializer: D) -> Result<Option<u64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let size = match Option::<Value>::deserialize(deserializer)? {
+        Some(Value::Number(size)) => size.as_u64(),
+        Some(Value::String(size)) => size.trim().parse().ok(),
+        _ => None,
+    };
+
+    Ok(size)
+}
+
+#[derive(Deserialize)]
+struct CkanTag<'a> {
+    #[serde(borrow)]
+    name: Cow<'a, str>,
+}
+
+#[derive(Deserialize)]
+struct CkanGroup<'a> {
+    #[serde(borrow)]
+    name: Cow<'a, str>,
+    #[serde(borrow)]
+    title: Option<Cow<'a, str>>,
+}
+
+#[derive(Deserialize)]
+struct CkanOrganization<'a> {
+    #[serde(borrow)]
+    name: Cow<'a, str>,
+    #[serde(borrow)]
+    title: Option<Cow<'a, str>>,
+}
+
+#[derive(Deserialize)]
+struct CkanExtra<'a> {
+    #[serde(borrow)]
+    key: Cow<'a, str>,
+    #[serde(borrow)]
+    value: Cow<'a, str>,
+}
+
+#[derive(Deserialize)]
+struct CkanError<'a> {
+    #[serde(borrow)]
+    message: Cow<'a, str>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_license_no_resources() {
+        let package = Package::default();
+
+        assert_eq!(package.license(), None);
+    }
+
+    #[test]
+    fn non_empty_license_no_resources() {
+        let package = Package {
+            license_id: Some("foobar".into()),
+            ..Default::default()
+        };
+
+        assert_eq!(package.license(), Some("foobar"));
+    }
+
+    #[test]
+    fn empty_license_single_resource() {
+        let package = Package {
+            license_id: Some("".into()),
+            resources: vec![CkanResource {
+                license: Some("foobar".into()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        assert_eq!(package.license(), Some("foobar"));
+    }
+
+    #[test]
+    fn empty_license_multiple_matching_resources() {
+        let package = Package {
+            license_id: Some("".into()),
+            resources: vec![
+                CkanResource {
+                    license: Some("foobar".into()),
+                    ..Default::default()
+                },
+                CkanResource {
+                    license: Some("foobar".into()),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+
+        assert_eq!(package.license(), Some("foobar"));
+    }
+
+    #[test]
+    fn empty_license_multiple_distinct_resources() {
+        let package = Package {
+            license_id: None,
+            resources: vec![
+                CkanResource {
+                    license: Some("foo".into()),
+                    ..Default::default()
+                },
+                CkanResource {
+                    license: Some("bar".into()),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+
+        assert_eq!(package.license(), None);
+    }
+
+    #[test]
+    fn non_empty_license_multiple_distinct_resources() {
+        let package = Package {
+            license_id: Some("foobar".into()),
+            resources: vec![
+                CkanResource {
+                    license: Some("foo".into()),
+                    ..Default::default()
+                },
+                CkanResource {
+                    license: Some("bar".into()),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+
+        assert_eq!(package.license(), Some("foobar"));
+    }
+
+    #[test]
+    fn spatial_extent() {
+        assert_eq!(
+            parse_spatial(r#"{"type":"Point","coordinates":[13.4,52.5]}"#),
+            Some([13.4, 52.5, 13.4, 52.5])
+        );
+
+        assert_eq!(
+            parse_spatial(
+                r#"{"type":"Polygon","coordinates":[[[5.9,47.3],[15.0,47.3],[15.0,55.1],[5.9,55.1],[5.9,47.3]]]}"#
+            ),
+            Some([5.9, 47.3, 15.0, 55.1])
+        );
+
+        assert_eq!(parse_spatial("Berlin"), None);
+    }
+
+    #[test]
+    fn failed_action() {
+        let response = from_slice::<Response<Vec<String>>>(
+            br#"{"success":false,"error":{"message":"Access denied","__type":"Authorization Error"}}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            response.into_result().unwrap_err().to_string(),
+            "Failed to call action: Access denied"
+        );
+    }
+
+    #[test]
+    fn streamed_packages() {
+        let (sender, mut receiver) = channel(16);
+
+        let count = stream_packages(
+            &br#"{"help":"","success":true,"result":{"count":42,"results":[{"id":"foo"},{"id":"bar"}],"sort":"score desc"}}"#[..],
+            sender,
+        )
+        .unwrap();
+
+        assert_eq!(count, 42);
+        assert_eq!(receiver.try_recv().unwrap().get(), r#"{"id":"foo"}"#);
+        assert_eq!(receiver.try_recv().unwrap().get(), r#"{"id":"bar"}"#);
+        receiver.try_recv().unwrap_err();
+    }
+
+    #[test]
+    fn streamed_failed_action() {
+        let (sender, _receiver) = channel(16);
+
+        let err = stream_packages(
+            &br#"{"success":false,"error":{"message":"Access denied","__type":"Authorization Error"}}"#[..],
+            sender,
+        )
+        .unwrap_err();
+
+        assert!(err
+            .to_string()
+            .starts_with("Failed to call action: Access denied"));
+    }
+
+    #[test]
+    fn successful_action() {
+        let response =
+            from_slice::<Response<Vec<String>>>(br#"{"success":true,"result":["foo","bar"]}"#)
+                .unwrap();
+
+        assert_eq!(response.into_result().unwrap(), ["foo", "bar"]);
+    }
+
+    #[test]
+    fn publisher_from_organization() {
+        let package = from_str::<Package>(
+            r#"{
+                "id": "0a1b2c",
+                "name": "pegel-elbe",
+                "title": "Pegelstände der Elbe",
+                "organization": {"name": "bfg", "title": " "},
+                "extras": [{"key": "publisher_url", "value": "https://www.bafg.de"}]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            package.publisher(),
+            Some(Publisher {
+                name: "bfg".to_owned(),
+                url: Some("https://www.bafg.de".to_owned()),
+            })
+        );
+
+        assert_eq!(Package::default().publisher(), None);
+    }
+
+    #[test]
+    fn relations_from_extras() {
+        let package = from_str::<Package>(
+            r#"{
+                "id": "0a1b2c",
+                "name": "pegel-elbe-2020",
+                "title": "Pegelstände der Elbe 2020",
+                "extras": [
+                    {"key": "is_part_of", "value": "3d4e5f"},
+                    {"key": "has_version", "value": "[\"https://example.org/pegel-elbe-2020-v2\"]"}
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            package.relations("govdata"),
+            [
+                Relation {
+                    r#type: RelationType::IsPartOf,
+                    target: RelationTarget::Dataset {
+                        source: "govdata".to_owned(),
+                        id: "3d4e5f".to_owned(),
+                    },
+                },
+                Relation {
+                    r#type: RelationType::HasVersion,
+                    target: RelationTarget::Url(
+                        "https://example.org/pegel-elbe-2020-v2".to_owned()
+                    ),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn resource_sizes_are_parsed_leniently() {
+        let resources = from_str::<Vec<CkanResource>>(
+            r#"[
+                {"url": "https://example.org/a.csv", "size": 1024, "hash": "sha256:0a1b"},
+                {"url": "https://example.org/b.csv", "size": "2048"},
+                {"url": "https://example.org/c.csv", "size": ""},
+                {"url": "https://example.org/d.csv", "size": null}
+            ]"#,
+        )
+        .unwrap();
+
+        let sizes = resources
+            .iter()
+            .map(|resource| resource.size)
+            .collect::<Vec<_>>();
+
+        assert_eq!(sizes, [Some(1024), Some(2048), None, None]);
+        assert_eq!(resources[0].hash.as_deref(), Some("sha256:0a1b"));
+    }
+}
@@ -0,0 +1,1423 @@
+use std::env::var;
+use std::fmt;
+use std::future::Future;
+use std::hash::Hasher;
+use std::io::{Read, Write};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use anyhow::{anyhow, bail, Error, Result};
+use async_compression::tokio::{bufread::ZstdDecoder, write::ZstdEncoder};
+use bytes::Bytes;
+use cap_std::fs::{Dir, OpenOptions};
+use hashbrown::HashMap;
+use parking_lot::Mutex;
+use reqwest::{
+    header::{
+        HeaderMap, HeaderName, HeaderValue, AUTHORIZATION, CONTENT_LENGTH, CONTENT_TYPE, ETAG,
+        IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED,
+    },
+    Client as HttpClient, Proxy, Request, RequestBuilder, StatusCode,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{from_slice, from_str, to_vec};
+use siphasher::sip128::{Hasher128, SipHasher};
+use tokio::time::{sleep, sleep_until, Duration, Instant};
+use tokio::{
+    fs::File as AsyncFile,
+    io::{AsyncReadExt, AsyncWriteExt, BufReader},
+};
+use url::Url;
+use zstd::stream::read::Decoder as ZstdReader;
+
+use umwelt_info_core::{encode_id, metrics::HostTraffic};
+
+use crate::Source;
+
+/// The product token matched against the `User-agent` lines of robots.txt files
+const PRODUCT_TOKEN: &str = "umwelt.info";
+
+#[derive(Clone)]
+pub struct Client {
+    replay: bool,
+    resume: bool,
+    http_client: HttpClient,
+    dir: Arc<Dir>,
+    old_dir: Option<Arc<Dir>>,
+    robots: Arc<Mutex<HashMap<String, Arc<Robots>>>>,
+    credentials: Option<Arc<Credentials>>,
+    quarantine_dir: Arc<Dir>,
+    quarantine: Option<Arc<Quarantine>>,
+    records_dir: Arc<Dir>,
+    records: Option<Arc<Dir>>,
+    hosts: Arc<Hosts>,
+    source_name: Option<Arc<str>>,
+    cache: Option<Arc<Cache>>,
+}
+
+/// Stored responses and errors of the records of a single source which could not be translated
+struct Quarantine {
+    dir: Dir,
+    count: AtomicUsize,
+}
+
+impl Client {
+    pub fn start(dir: &Dir, hosts: Vec<Host>) -> Result<Self> {
+        let replay = var("REPLAY_RESPONSES").is_ok();
+        let resume = var("RESUME_RESPONSES").is_ok();
+
+        let http_client = build_http_client(None, None, HeaderMap::new())?;
+
+        let mut old_dir = None;
+
+        if resume {
+            // Continue an interrupted run which already moved the responses of its predecessor.
+            if dir.exists("responses.old") {
+                old_dir = Some(Arc::new(dir.open_dir("responses.old")?));
+            }
+
+            if !dir.exists("responses") {
+                dir.create_dir("responses")?;
+            }
+        } else if !replay {
+            // Keep the responses of the previous run so that conditional requests can reuse them.
+            let _ = dir.remove_dir_all("responses.old");
+
+            if dir.exists("responses") {
+                dir.rename("responses", dir, "responses.old")?;
+
+                old_dir = Some(Arc::new(dir.open_dir("responses.old")?));
+            }
+
+            dir.create_dir("responses")?;
+        }
+
+        if !dir.exists("quarantine") {
+            dir.create_dir("quarantine")?;
+        }
+
+        let quarantine_dir = Arc::new(dir.open_dir("quarantine")?);
+
+        if !dir.exists("records") {
+            dir.create_dir("records")?;
+        }
+
+        let records_dir = Arc::new(dir.open_dir("records")?);
+
+        let cache = match var("RESPONSE_CACHE_TTL") {
+            Ok(ttl) => {
+                let ttl = Duration::from_secs(ttl.parse()?);
+
+                Some(Arc::new(Cache::open(dir, ttl)?))
+            }
+            Err(_err) => None,
+        };
+
+        let dir = Arc::new(dir.open_dir("responses")?);
+
+        Ok(Self {
+            replay,
+            resume,
+            dir,
+            old_dir,
+            http_client,
+            robots: Default::default(),
+            credentials: None,
+            quarantine_dir,
+            quarantine: None,
+            records_dir,
+            records: None,
+            hosts: Arc::new(Hosts {
+                limits: hosts,
+                traffic: Default::default(),
+            }),
+            source_name: None,
+            cache,
+        })
+    }
+
+    /// Yields a client which applies the authentication, proxy, user agent and headers configured for the given source to all its requests.
+    pub fn for_source(&self, source: &Source) -> Result<Self> {
+        let credentials = source.auth.resolve()?.map(Arc::new);
+
+        let _ = self.quarantine_dir.remove_dir_all(&source.name);
+        self.quarantine_dir.create_dir(&source.name)?;
+
+        let quarantine = Arc::new(Quarantine {
+            dir: self.quarantine_dir.open_dir(&source.name)?,
+            count: AtomicUsize::new(0),
+        });
+
+        // Records are kept across runs as delta harvests and inactive sources keep their datasets.
+        if !self.records_dir.exists(&source.name) {
+            self.records_dir.create_dir(&source.name)?;
+        }
+
+        let records = Arc::new(self.records_dir.open_dir(&source.name)?);
+
+        let http_client = if source.proxy.is_some()
+            || source.user_agent.is_some()
+            || !source.headers.is_empty()
+        {
+            let mut headers = HeaderMap::new();
+
+            for (name, val) in &source.headers {
+                headers.insert(
+                    HeaderName::from_bytes(name.as_bytes())?,
+                    HeaderValue::from_str(val)?,
+                );
+            }
+
+            build_http_client(source.proxy.as_ref(), source.user_agent.as_deref(), headers)?
+        } else {
+            self.http_client.clone()
+        };
+
+        Ok(Self {
+            credentials,
+            http_client,
+            quarantine: Some(quarantine),
+            records: Some(records),
+            source_name: Some(source.name.as_str().into()),
+            ..self.clone()
+        })
+    }
+
+    /// Keeps the response stored under `key` together with the error if translating a record contained in it failed.
+    ///
+    /// The response is stored compressed as `quarantine/<source>/<key>.zst` and the errors of all its records
+    /// are appended to `quarantine/<source>/<key>.errors`, so that mapping bugs can be reproduced offline.
+    pub fn quarantine<T>(&self, key: &str, res: Result<T>) -> Result<T> {
+        if let (Err(err), Some(quarantine)) = (&res, &self.quarantine) {
+            // Requests which failed did not store a response.
+            if self.dir.exists(key) {
+                if let Err(err) = quarantine.record(&self.dir, key, err) {
+                    tracing::warn!("Failed to quarantine response {key}: {:#}", err);
+                }
+            }
+        }
+
+        res
+    }
+
+    /// Keeps the original record from which the dataset `id` was translated, e.g. a CKAN package or an ISO document.
+    ///
+    /// The record is stored compressed as `records/<source>/<id>.zst` so that it can be compared
+    /// with the translated dataset without harvesting the source again.
+    pub fn preserve(&self, id: &str, record: &[u8]) {
+        if let Some(records) = &self.records {
+            let res = encode_id(id).and_then(|name| {
+                let buf = zstd::encode_all(record, 0)?;
+                records.write(format!("{name}.zst"), buf)?;
+                Ok(())
+            });
+
+            if let Err(err) = res {
+                tracing::warn!("Failed to preserve record of dataset {id}: {:#}", err);
+            }
+        }
+    }
+
+    /// Removes the records of the source of this client for which the given directory contains no dataset anymore.
+    pub fn prune_records(&self, datasets: &Dir) -> Result<usize> {
+        let Some(records) = &self.records else {
+            return Ok(0);
+        };
+
+        let mut pruned = 0;
+
+        for entry in records.entries()? {
+            let name = entry?.file_name();
+
+            let keep = name
+                .to_str()
+                .and_then(|name| name.strip_suffix(".zst"))
+                .map_or(false, |id| datasets.exists(id));
+
+            if !keep {
+                records.remove_file(&name)?;
+
+                pruned += 1;
+            }
+        }
+
+        Ok(pruned)
+    }
+
+    /// Yields the number of records quarantined for the source of this client.
+    pub fn quarantined(&self) -> usize {
+        self.quarantine
+            .as_ref()
+            .map_or(0, |quarantine| quarantine.count.load(Ordering::Relaxed))
+    }
+
+    /// Yields the requests sent to each host by all clients derived from this one.
+    pub fn traffic(&self) -> HashMap<String, HostTraffic> {
+        self.hosts.traffic.lock().clone()
+    }
+
+    /// Checks whether the robots.txt of the origin of `url` allows fetching it.
+    ///
+    /// The robots.txt files are fetched once per origin and cached. If the path is allowed,
+    /// this waits until the crawl delay requested by the origin has passed since the previous check.
+    pub async fn allowed_by_robots(&self, url: &Url) -> bool {
+        if self.replay {
+            return true;
+        }
+
+        let origin = url.origin().ascii_serialization();
+
+        let robots = self.robots.lock().get(&origin).cloned();
+
+        let robots = match robots {
+            Some(robots) => robots,
+            None => {
+                // Concurrent checks might fetch the same robots.txt, but only the first one is kept.
+                let robots = Arc::new(fetch_robots(&self.http_client, url).await);
+
+                self.robots.lock().entry(origin).or_insert(robots).clone()
+            }
+        };
+
+        let mut path = url.path().to_owned();
+
+        if let Some(query) = url.query() {
+            path.push('?');
+            path.push_str(query);
+        }
+
+        if !robots.allows(&path) {
+            tracing::warn!("Fetching {url} is disallowed by robots.txt");
+
+            return false;
+        }
+
+        if let Some(crawl_delay) = robots.crawl_delay {
+            let deadline = {
+                let mut next_request = robots.next_request.lock();
+
+                let now = Instant::now();
+                let deadline = next_request.map_or(now, |next_request| next_request.max(now));
+                *next_request = Some(deadline + crawl_delay);
+
+                deadline
+            };
+
+            sleep_until(deadline).await;
+        }
+
+        true
+    }
+
+    /// Sends the request built by `action` and stores its response under the given `key`.
+    ///
+    /// If a response with an `ETag` or `Last-Modified` header was stored under the same key by the previous run,
+    /// the request is made conditional and the previous response is reused if the server reports it as unmodified.
+    pub async fn make_request<A, T>(&self, key: &str, mut action: A) -> Result<T>
+    where
+        A: FnMut(&HttpClient) -> RequestBuilder,
+        T: Response,
+    {
+        if self.replay || self.resume {
+            if let Ok(buf) = read_response(&self.dir, key).await {
+                return T::from_buf(buf);
+            } else if self.replay {
+                tracing::warn!("Failed to replay {key}");
+            }
+        }
+
+        // Build the request once up front to look it up in the cache and account for it against the budget of its host.
+        let request = action(&self.http_client).build()?;
+
+        let hash = self.cache.as_ref().map(|_cache| Cache::hash(&request));
+
+        if self.lookup_cache(hash.as_deref(), key) {
+            let buf = read_response(&self.dir, key).await?;
+
+            return T::from_buf(buf);
+        }
+
+        let host = self
+            .hosts
+            .admit(request.url(), self.source_name.as_deref())?;
+
+        let validators = self
+            .old_dir
+            .as_deref()
+            .and_then(|old_dir| Validators::read(old_dir, key));
+
+        let fetched = retry_request(|| {
+            let mut request = action(&self.http_client);
+
+            if let Some(credentials) = &self.credentials {
+                request = credentials.apply(request);
+            }
+
+            send_request::<T>(request, validators.as_ref())
+        })
+        .await?;
+
+        let (response, validators) = match fetched {
+            Fetched::Modified(response, validators) => {
+                self.hosts.received(&host, response.as_ref().len());
+
+                (response, validators)
+            }
+            Fetched::NotModified => {
+                tracing::debug!("Reusing unmodified response {key}");
+
+                let old_dir = self.old_dir.as_deref().unwrap();
+
+                old_dir.hard_link(key, &self.dir, key)?;
+                old_dir.hard_link(Validators::key(key), &self.dir, Validators::key(key))?;
+
+                let buf = read_response(&self.dir, key).await?;
+
+                return T::from_buf(buf);
+            }
+        };
+
+        let file = self.dir.create(key)?;
+
+        let mut file = ZstdEncoder::new(AsyncFile::from_std(file.into_std()));
+        file.write_all(response.as_ref()).await?;
+        file.shutdown().await?;
+
+        if let Some(validators) = validators {
+            validators.write(&self.dir, key)?;
+        }
+
+        self.store_cache(hash.as_deref(), key);
+
+        Ok(response)
+    }
+
+    /// Like [`Self::make_request`], but streams the response into storage instead of buffering it in memory.
+    ///
+    /// Yields a reader decompressing the stored response so that very large responses can be parsed incrementally.
+    pub async fn make_streaming_request<A>(
+        &self,
+        key: &str,
+        mut action: A,
+    ) -> Result<StoredResponse>
+    where
+        A: FnMut(&HttpClient) -> RequestBuilder,
+    {
+        if self.replay || self.resume {
+            if self.dir.exists(key) {
+                return open_response(&self.dir, key);
+            } else if self.replay {
+                tracing::warn!("Failed to replay {key}");
+            }
+        }
+
+        // Build the request once up front to look it up in the cache and account for it against the budget of its host.
+        let request = action(&self.http_client).build()?;
+
+        let hash = self.cache.as_ref().map(|_cache| Cache::hash(&request));
+
+        if self.lookup_cache(hash.as_deref(), key) {
+            return open_response(&self.dir, key);
+        }
+
+        let host = self
+            .hosts
+            .admit(request.url(), self.source_name.as_deref())?;
+
+        let validators = self
+            .old_dir
+            .as_deref()
+            .and_then(|old_dir| Validators::read(old_dir, key));
+
+        let fetched = retry_request(|| {
+            let mut request = action(&self.http_client);
+
+            if let Some(credentials) = &self.credentials {
+                request = credentials.apply(request);
+            }
+
+            stream_request(request, validators.as_ref(), &self.dir, key, |bytes| {
+                self.hosts.received(&host, bytes)
+            })
+        })
+        .await?;
+
+        match fetched {
+            Fetched::Modified((), validators) => {
+                if let Some(validators) = validators {
+                    validators.write(&self.dir, key)?;
+                }
+
+                self.store_cache(hash.as_deref(), key);
+            }
+            Fetched::NotModified => {
+                tracing::debug!("Reusing unmodified response {key}");
+
+                let old_dir = self.old_dir.as_deref().unwrap();
+
+                old_dir.hard_link(key, &self.dir, key)?;
+                old_dir.hard_link(Validators::key(key), &self.dir, Validators::key(key))?;
+            }
+        }
+
+        open_response(&self.dir, key)
+    }
+
+    /// Makes a cached response to the request with the given hash available under `key` if it has not expired yet.
+    fn lookup_cache(&self, hash: Option<&str>, key: &str) -> bool {
+        let (Some(cache), Some(hash)) = (&self.cache, hash) else {
+            return false;
+        };
+
+        match cache.lookup(hash, &self.dir, key) {
+            Ok(found) => {
+                if found {
+                    tracing::debug!("Reusing cached response {key}");
+                }
+
+                found
+            }
+            Err(err) => {
+                tracing::warn!("Failed to look up cached response {key}: {:#}", err);
+
+                false
+            }
+        }
+    }
+
+    fn store_cache(&self, hash: Option<&str>, key: &str) {
+        let (Some(cache), Some(hash)) = (&self.cache, hash) else {
+            return;
+        };
+
+        if let Err(err) = cache.store(hash, &self.dir, key) {
+            tracing::warn!("Failed to cache response {key}: {:#}", err);
+        }
+    }
+}
+
+/// Responses stored under a hash of their request which are reused by later runs until they expire
+///
+/// The cache is kept as `cache/<hash>` where the hash covers the method, the URL and the body of the request.
+struct Cache {
+    dir: Dir,
+    ttl: Duration,
+}
+
+impl Cache {
+    /// Opens the cache directory and removes all expired responses from it.
+    fn open(dir: &Dir, ttl: Duration) -> Result<Self> {
+        if !dir.exists("cache") {
+            dir.create_dir("cache")?;
+        }
+
+        let cache = Self {
+            dir: dir.open_dir("cache")?,
+            ttl,
+        };
+
+        let mut expired = 0;
+
+        for entry in cache.dir.entries()? {
+            let entry = entry?;
+
+            if !cache.fresh(&entry.metadata()?) {
+                cache.dir.remove_file(entry.file_name())?;
+
+                expired += 1;
+            }
+        }
+
+        tracing::debug!("Removed {} expired responses from cache", expired);
+
+        Ok(cache)
+    }
+
+    fn hash(request: &Request) -> String {
+        let mut hasher = SipHasher::new();
+
+        hasher.write(request.method().as_str().as_bytes());
+        hasher.write_u8(0);
+        hasher.write(request.url().as_str().as_bytes());
+        hasher.write_u8(0);
+
+        if let Some(body) = request.body().and_then(|body| body.as_bytes()) {
+            hasher.write(body);
+        }
+
+        format!("{:032x}", hasher.finish128().as_u128())
+    }
+
+    fn fresh(&self, metadata: &cap_std::fs::Metadata) -> bool {
+        metadata
+            .modified()
+            .ok()
+            .and_then(|modified| modified.into_std().elapsed().ok())
+            .map_or(false, |age| age < self.ttl)
+    }
+
+    fn lookup(&self, hash: &str, responses: &Dir, key: &str) -> Result<bool> {
+        match self.dir.metadata(hash) {
+            Ok(metadata) if self.fresh(&metadata) => (),
+            _ => return Ok(false),
+        }
+
+        let _ = responses.remove_file(key);
+        self.dir.hard_link(hash, responses, key)?;
+
+        Ok(true)
+    }
+
+    fn store(&self, hash: &str, responses: &Dir, key: &str) -> Result<()> {
+        let _ = self.dir.remove_file(hash);
+        responses.hard_link(key, &self.dir, hash)?;
+
+        Ok(())
+    }
+}
+
+/// Limits shared by all sources sending requests to a domain which can be configured via `[[hosts]]` tables
+#[derive(Debug, Deserialize)]
+pub struct Host {
+    /// Domain to which the limits apply, including all its subdomains
+    pub domain: String,
+    /// Maximum number of requests sent to the domain during a single harvest
+    pub max_requests: Option<usize>,
+}
+
+impl Host {
+    fn matches(&self, host: &str) -> bool {
+        host.strip_suffix(&*self.domain)
+            .map_or(false, |prefix| prefix.is_empty() || prefix.ends_with('.'))
+    }
+}
+
+/// Accounting of the requests sent to each host across all sources
+struct Hosts {
+    limits: Vec<Host>,
+    traffic: Mutex<HashMap<String, HostTraffic>>,
+}
+
+impl Hosts {
+    /// Counts a request to the host of `url`, failing if its budget is exhausted.
+    ///
+    /// Hosts matching a configured domain are accounted under that domain.
+    /// Yields the name under which the request was accounted.
+    fn admit(&self, url: &Url, source_name: Option<&str>) -> Result<String> {
+        let host = url.host_str().unwrap_or_default();
+
+        let limit = self.limits.iter().find(|limit| limit.matches(host));
+
+        let name = limit.map_or(host, |limit| &limit.domain).to_owned();
+
+        let mut traffic = self.traffic.lock();
+        let traffic = traffic.entry(name.clone()).or_default();
+
+        if let Some(source_name) = source_name {
+            if !traffic.sources.iter().any(|source| source == source_name) {
+                traffic.sources.push(source_name.to_owned());
+            }
+        }
+
+        if let Some(max_requests) = limit.and_then(|limit| limit.max_requests) {
+            traffic.budget = Some(max_requests);
+
+            if traffic.requests >= max_requests {
+                traffic.refused += 1;
+
+                bail!("Budget of {max_requests} requests to {name} is exhausted");
+            }
+        }
+
+        traffic.requests += 1;
+
+        Ok(name)
+    }
+
+    fn received(&self, name: &str, bytes: usize) {
+        if let Some(traffic) = self.traffic.lock().get_mut(name) {
+            traffic.bytes += bytes as u64;
+        }
+    }
+}
+
+/// Authentication which can be configured per source via a `[sources.auth]` table
+///
+/// All values name environment variables from which the actual secrets are read when the harvest starts.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Auth {
+    /// Username and password for HTTP basic authentication
+    basic: Option<BasicAuth>,
+    /// Token sent via `Authorization: Bearer`
+    bearer: Option<String>,
+    /// Additional headers sent with every request
+    headers: HashMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BasicAuth {
+    username: String,
+    password: String,
+}
+
+impl Auth {
+    /// Whether the `Authorization` header is set, i.e. via basic or bearer authentication or explicitly.
+    pub(super) fn sets_authorization(&self) -> bool {
+        self.basic.is_some()
+            || self.bearer.is_some()
+            || self
+                .headers
+                .keys()
+                .any(|name| name.eq_ignore_ascii_case(AUTHORIZATION.as_str()))
+    }
+
+    fn resolve(&self) -> Result<Option<Credentials>> {
+        if self.basic.is_none() && self.bearer.is_none() && self.headers.is_empty() {
+            return Ok(None);
+        }
+
+        let resolve = |name: &str| {
+            var(name).map_err(|_err| anyhow!("Environment variable {name} is not set"))
+        };
+
+        let basic = self
+            .basic
+            .as_ref()
+            .map(|basic| Ok::<_, Error>((resolve(&basic.username)?, resolve(&basic.password)?)))
+            .transpose()?;
+
+        let bearer = self.bearer.as_deref().map(resolve).transpose()?;
+
+        let mut headers = HeaderMap::new();
+
+        for (name, val) in &self.headers {
+            headers.insert(
+                HeaderName::from_bytes(name.as_bytes())?,
+                HeaderValue::from_str(&resolve(val)?)?,
+            );
+        }
+
+        Ok(Some(Credentials {
+            basic,
+            bearer,
+            headers,
+        }))
+    }
+}
+
+struct Credentials {
+    basic: Option<(String, String)>,
+    bearer: Option<String>,
+    headers: HeaderMap,
+}
+
+impl Credentials {
+    fn apply(&self, mut request: RequestBuilder) -> RequestBuilder {
+        if let Some((username, password)) = &self.basic {
+            request = request.basic_auth(username, Some(password));
+        }
+
+        if let Some(bearer) = &self.bearer {
+            request = request.bearer_auth(bearer);
+        }
+
+        request.headers(self.headers.clone())
+    }
+}
+
+impl Quarantine {
+    fn record(&self, responses: &Dir, key: &str, err: &Error) -> Result<()> {
+        let response = format!("{key}.zst");
+
+        if !self.dir.exists(&response) {
+            responses.hard_link(key, &self.dir, &response)?;
+        }
+
+        let mut errors = self.dir.open_with(
+            format!("{key}.errors"),
+            OpenOptions::new().create(true).append(true),
+        )?;
+
+        writeln!(errors, "{:#}", err)?;
+
+        self.count.fetch_add(1, Ordering::Relaxed);
+
+        Ok(())
+    }
+}
+
+fn build_http_client(
+    proxy: Option<&Url>,
+    user_agent: Option<&str>,
+    headers: HeaderMap,
+) -> Result<HttpClient> {
+    let user_agent = match user_agent {
+        Some(user_agent) => user_agent.to_owned(),
+        None => format!("{PRODUCT_TOKEN} harvester"),
+    };
+
+    let mut builder = HttpClient::builder()
+        .user_agent(user_agent)
+        .default_headers(headers)
+        .timeout(Duration::from_secs(300));
+
+    if let Some(proxy) = proxy {
+        builder = builder.proxy(Proxy::all(proxy.clone())?);
+    }
+
+    let http_client = builder.build()?;
+
+    Ok(http_client)
+}
+
+/// Reader decompressing a response stored by [`Client::make_streaming_request`]
+pub type StoredResponse = ZstdReader<'static, std::io::BufReader<std::fs::File>>;
+
+pub trait Response: AsRef<[u8]> + Sized {
+    /// Builds the response from the buffer which is stored on disk.
+    fn from_buf(buf: Vec<u8>) -> Result<Self>;
+
+    /// Builds the response when it is received, considering only its body by default.
+    ///
+    /// `Self::as_ref` must yield the buffer from which [`Self::from_buf`] rebuilds the response when it is replayed.
+    fn from_parts(_status: StatusCode, _headers: &HeaderMap, buf: Vec<u8>) -> Result<Self> {
+        Self::from_buf(buf)
+    }
+
+    /// Decides whether a response with the given status is passed on instead of failing the request.
+    fn accept(status: StatusCode, _buf: &[u8]) -> bool {
+        status.is_success()
+    }
+}
+
+impl Response for Bytes {
+    fn from_buf(buf: Vec<u8>) -> Result<Self> {
+        Ok(buf.into())
+    }
+}
+
+impl Response for String {
+    fn from_buf(buf: Vec<u8>) -> Result<Self> {
+        let text = String::from_utf8(buf)?;
+
+        Ok(text)
+    }
+}
+
+/// The status and the `Content-Type` and `Content-Length` headers of a response, e.g. to a `HEAD` request
+///
+/// Only these are stored as JSON while the body is ignored. Responses are accepted regardless of their status.
+pub struct ContentHeaders {
+    pub status: u16,
+    pub content_type: Option<String>,
+    pub content_length: Option<u64>,
+    buf: Vec<u8>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct StoredContentHeaders {
+    status: u16,
+    content_type: Option<String>,
+    content_length: Option<u64>,
+}
+
+impl AsRef<[u8]> for ContentHeaders {
+    fn as_ref(&self) -> &[u8] {
+        &self.buf
+    }
+}
+
+impl Response for ContentHeaders {
+    fn from_buf(buf: Vec<u8>) -> Result<Self> {
+        let stored = from_slice::<StoredContentHeaders>(&buf)?;
+
+        Ok(Self {
+            status: stored.status,
+            content_type: stored.content_type,
+            content_length: stored.content_length,
+            buf,
+        })
+    }
+
+    fn from_parts(status: StatusCode, headers: &HeaderMap, _buf: Vec<u8>) -> Result<Self> {
+        let header = |name| {
+            headers
+                .get(name)
+                .and_then(|val: &HeaderValue| val.to_str().ok())
+                .map(str::trim)
+        };
+
+        let stored = StoredContentHeaders {
+            status: status.as_u16(),
+            content_type: header(CONTENT_TYPE).map(ToOwned::to_owned),
+            content_length: header(CONTENT_LENGTH).and_then(|val| val.parse().ok()),
+        };
+
+        let buf = to_vec(&stored)?;
+
+        Ok(Self {
+            status: stored.status,
+            content_type: stored.content_type,
+            content_length: stored.content_length,
+            buf,
+        })
+    }
+
+    fn accept(_status: StatusCode, _buf: &[u8]) -> bool {
+        true
+    }
+}
+
+enum Fetched<T> {
+    Modified(T, Option<Validators>),
+    NotModified,
+}
+
+fn make_conditional(
+    mut request: RequestBuilder,
+    validators: Option<&Validators>,
+) -> RequestBuilder {
+    if let Some(validators) = validators {
+        if let Some(etag) = &validators.etag {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+
+        if let Some(last_modified) = &validators.last_modified {
+            request = request.header(IF_MODIFIED_SINCE, last_modified);
+        }
+    }
+
+    request
+}
+
+async fn send_request<T>(
+    request: RequestBuilder,
+    validators: Option<&Validators>,
+) -> Result<Fetched<T>>
+where
+    T: Response,
+{
+    let response = make_conditional(request, validators).send().await?;
+
+    let status = response.status();
+
+    if status == StatusCode::NOT_MODIFIED && validators.is_some() {
+        return Ok(Fetched::NotModified);
+    }
+
+    let err = response.error_for_status_ref().err();
+
+    let validators = Validators::from_headers(response.headers());
+
+    let headers = response.headers().clone();
+
+    let buf = response.bytes().await?;
+
+    if !T::accept(status, &buf) {
+        return Err(err.map_or_else(|| anyhow!("Unexpected status {status}"), Into::into));
+    }
+
+    let response = T::from_parts(status, &headers, buf.into())?;
+
+    // Only successful responses are worth revalidating.
+    let validators = validators.filter(|_| status.is_success());
+
+    Ok(Fetched::Modified(response, validators))
+}
+
+/// Writes the response body into storage chunk by chunk, reporting the number of bytes received.
+async fn stream_request<R>(
+    request: RequestBuilder,
+    validators: Option<&Validators>,
+    dir: &Dir,
+    key: &str,
+    mut received: R,
+) -> Result<Fetched<()>>
+where
+    R: FnMut(usize),
+{
+    let mut response = make_conditional(request, validators).send().await?;
+
+    let status = response.status();
+
+    if status == StatusCode::NOT_MODIFIED && validators.is_some() {
+        return Ok(Fetched::NotModified);
+    }
+
+    response.error_for_status_ref()?;
+
+    let validators = Validators::from_headers(response.headers());
+
+    // The response is only stored under its key when complete,
+    // so that resuming an interrupted harvest does not pick up a truncated response.
+    let partial_key = format!("{key}.partial");
+
+    let file = dir.create(&partial_key)?;
+
+    let mut file = ZstdEncoder::new(AsyncFile::from_std(file.into_std()));
+
+    while let Some(chunk) = response.chunk().await? {
+        received(chunk.len());
+
+        file.write_all(&chunk).await?;
+    }
+
+    file.shutdown().await?;
+
+    dir.rename(&partial_key, dir, key)?;
+
+    Ok(Fetched::Modified((), validators))
+}
+
+fn open_response(dir: &Dir, key: &str) -> Result<StoredResponse> {
+    let file = dir.open(key)?;
+
+    let reader = ZstdReader::new(file.into_std())?;
+
+    Ok(reader)
+}
+
+async fn read_response(dir: &Dir, key: &str) -> Result<Vec<u8>> {
+    let file = dir.open(key)?;
+
+    let mut file = ZstdDecoder::new(BufReader::new(AsyncFile::from_std(file.into_std())));
+
+    let mut buf = Vec::new();
+    file.read_to_end(&mut buf).await?;
+
+    Ok(buf)
+}
+
+/// The `ETag` and `Last-Modified` headers of a stored response
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+struct Validators {
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
+
+impl Validators {
+    fn key(key: &str) -> String {
+        format!("{key}.validators")
+    }
+
+    fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let header = |name| {
+            headers
+                .get(name)
+                .and_then(|val| val.to_str().ok())
+                .map(ToOwned::to_owned)
+        };
+
+        let etag = header(ETAG);
+        let last_modified = header(LAST_MODIFIED);
+
+        if etag.is_none() && last_modified.is_none() {
+            return None;
+        }
+
+        Some(Self {
+            etag,
+            last_modified,
+        })
+    }
+
+    /// Yields the validators stored under the given key if the response itself is available as well.
+    fn read(dir: &Dir, key: &str) -> Option<Self> {
+        if !dir.exists(key) {
+            return None;
+        }
+
+        let mut buf = String::new();
+        dir.open(Self::key(key))
+            .ok()?
+            .read_to_string(&mut buf)
+            .ok()?;
+
+        from_str(&buf).ok()
+    }
+
+    fn write(&self, dir: &Dir, key: &str) -> Result<()> {
+        dir.write(Self::key(key), to_vec(self)?)?;
+
+        Ok(())
+    }
+}
+
+async fn fetch_robots(http_client: &HttpClient, url: &Url) -> Robots {
+    let res = async {
+        let response = http_client.get(url.join("/robots.txt")?).send().await?;
+
+        let status = response.status();
+
+        // As per RFC 9309, unavailable robots.txt files allow everything whereas unreachable ones disallow everything.
+        let robots = if status.is_success() {
+            Robots::parse(&response.text().await?)
+        } else if status.is_client_error() {
+            Robots::default()
+        } else {
+            return Err(anyhow!("Unexpected status {status}"));
+        };
+
+        Ok::<_, Error>(robots)
+    };
+
+    match res.await {
+        Ok(robots) => robots,
+        Err(err) => {
+            tracing::warn!(
+                "Failed to fetch robots.txt for {}, disallowing everything: {:#}",
+                url.origin().ascii_serialization(),
+                err
+            );
+
+            Robots {
+                rules: vec![Rule {
+                    allow: false,
+                    pattern: "/".to_owned(),
+                }],
+                ..Default::default()
+            }
+        }
+    }
+}
+
+/// The rules of a robots.txt file applying to our product token
+#[derive(Debug, Default)]
+struct Robots {
+    rules: Vec<Rule>,
+    crawl_delay: Option<Duration>,
+    next_request: Mutex<Option<Instant>>,
+}
+
+#[derive(Debug, Clone)]
+struct Rule {
+    allow: bool,
+    pattern: String,
+}
+
+impl Robots {
+    /// Parses the groups matching our product token or the `*` wildcard if there are none.
+    fn parse(text: &str) -> Self {
+        let mut groups = Vec::<(Vec<String>, Vec<Rule>, Option<Duration>)>::new();
+        let mut in_agents = false;
+
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap();
+
+            let Some((key, val)) = line.split_once(':') else {
+                continue;
+            };
+
+            let key = key.trim().to_ascii_lowercase();
+            let val = val.trim();
+
+            if key == "user-agent" {
+                if !in_agents {
+                    groups.push(Default::default());
+                    in_agents = true;
+                }
+
+                groups.last_mut().unwrap().0.push(val.to_ascii_lowercase());
+
+                continue;
+            }
+
+            in_agents = false;
+
+            let Some((_agents, rules, crawl_delay)) = groups.last_mut() else {
+                continue;
+            };
+
+            match &*key {
+                "allow" | "disallow" if !val.is_empty() => rules.push(Rule {
+                    allow: key == "allow",
+                    pattern: val.to_owned(),
+                }),
+                "crawl-delay" => {
+                    if let Ok(val) = val.parse::<f64>() {
+                        *crawl_delay = Duration::try_from_secs_f64(val).ok();
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        let matching = |agent: &str| {
+            let mut robots = Self::default();
+
+            for (agents, rules, crawl_delay) in &groups {
+                if agents.iter().any(|other| other == agent) {
+                    robots.rules.extend(rules.iter().cloned());
+                    robots.crawl_delay = robots.crawl_delay.or(*crawl_delay);
+                }
+            }
+
+            robots
+        };
+
+        let robots = matching(PRODUCT_TOKEN);
+
+        if !robots.rules.is_empty() || robots.crawl_delay.is_some() {
+            return robots;
+        }
+
+        matching("*")
+    }
+
+    /// The longest matching rule decides, with allow rules winning ties.
+    fn allows(&self, path: &str) -> bool {
+        self.rules
+            .iter()
+            .filter(|rule| rule.matches(path))
+            .max_by_key(|rule| (rule.pattern.len(), rule.allow))
+            .map_or(true, |rule| rule.allow)
+    }
+}
+
+impl Rule {
+    /// Matches the path against the pattern which may contain `*` wildcards and end with a `$` anchor.
+    fn matches(&self, path: &str) -> bool {
+        let (pattern, anchored) = match self.pattern.strip_suffix('$') {
+            Some(pattern) => (pattern, true),
+            None => (&*self.pattern, false),
+        };
+
+        let mut parts = pattern.split('*');
+
+        let Some(mut rest) = path.strip_prefix(parts.next().unwrap()) else {
+            return false;
+        };
+
+        let mut parts = parts.peekable();
+
+        if parts.peek().is_none() {
+            return !anchored || rest.is_empty();
+        }
+
+        while let Some(part) = parts.next() {
+            if anchored && parts.peek().is_none() {
+                return rest.ends_with(part);
+            }
+
+            match rest.find(part) {
+                Some(pos) => rest = &rest[pos + part.len()..],
+                None => return false,
+            }
+        }
+
+        true
+    }
+}
+async fn retry_request<A, F, T, E>(mut action: A) -> Result<T>
+where
+    A: FnMut() -> F,
+    F: Future<Output = Result<T, E>>,
+    E: Into<Error> + fmt::Display,
+{
+    let mut attempts = 0;
+    let mut duration = Duration::from_secs(1);
+
+    loop {
+        match action().await {
+            Ok(val) => return Ok(val),
+            Err(err) => {
+                if attempts < 3 {
+                    tracing::warn!("Request failed but will be retried: {:#}", err);
+
+                    sleep(duration).await;
+
+                    attempts += 1;
+                    duration *= 10;
+                } else {
+                    return Err(err.into());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use anyhow::anyhow;
+    use tokio::time::{pause, Instant};
+
+    #[test]
+    fn validators_from_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("text/xml"));
+
+        assert_eq!(Validators::from_headers(&headers), None);
+
+        headers.insert(ETAG, HeaderValue::from_static("\"abc\""));
+
+        assert_eq!(
+            Validators::from_headers(&headers),
+            Some(Validators {
+                etag: Some("\"abc\"".to_owned()),
+                last_modified: None,
+            })
+        );
+    }
+
+    #[test]
+    fn robots_rules_are_matched() {
+        let robots = Robots::parse(
+            r#"
+User-agent: *
+Disallow: /
+
+User-agent: umwelt.info
+User-agent: other
+Disallow: /private # comment
+Allow: /private/public
+Disallow: /*.pdf$
+Crawl-delay: 2.5
+"#,
+        );
+
+        assert_eq!(robots.crawl_delay, Some(Duration::from_millis(2500)));
+
+        assert!(robots.allows("/"));
+        assert!(robots.allows("/datasets?page=2"));
+        assert!(!robots.allows("/private/data"));
+        assert!(robots.allows("/private/public/data"));
+        assert!(!robots.allows("/files/report.pdf"));
+        assert!(robots.allows("/files/report.pdf?download"));
+
+        let robots = Robots::parse("User-agent: *\nDisallow: /search\nDisallow:\n");
+
+        assert!(robots.allows("/dataset/1"));
+        assert!(!robots.allows("/search?q=Wasser"));
+    }
+
+    #[test]
+    fn content_headers_are_replayed() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            CONTENT_TYPE,
+            HeaderValue::from_static("text/csv; charset=utf-8"),
+        );
+        headers.insert(CONTENT_LENGTH, HeaderValue::from_static("1234"));
+
+        let received = ContentHeaders::from_parts(StatusCode::OK, &headers, Vec::new()).unwrap();
+
+        assert_eq!(
+            received.content_type.as_deref(),
+            Some("text/csv; charset=utf-8")
+        );
+        assert_eq!(received.content_length, Some(1234));
+
+        let replayed = ContentHeaders::from_buf(received.as_ref().to_owned()).unwrap();
+
+        assert_eq!(replayed.status, 200);
+        assert_eq!(replayed.content_type, received.content_type);
+        assert_eq!(replayed.content_length, received.content_length);
+    }
+
+    #[test]
+    fn cache_hash_covers_method_url_and_body() {
+        let client = HttpClient::new();
+
+        let hash = |request: RequestBuilder| Cache::hash(&request.build().unwrap());
+
+        let foo = hash(client.post("https://example.org/").body("foo"));
+
+        assert_eq!(foo, hash(client.post("https://example.org/").body("foo")));
+        assert_ne!(foo, hash(client.post("https://example.org/").body("bar")));
+        assert_ne!(foo, hash(client.post("https://example.com/").body("foo")));
+        assert_ne!(foo, hash(client.put("https://example.org/").body("foo")));
+    }
+
+    #[test]
+    fn hosts_match_subdomains() {
+        let host = Host {
+            domain: "example.org".to_owned(),
+            max_requests: None,
+        };
+
+        assert!(host.matches("example.org"));
+        assert!(host.matches("data.example.org"));
+        assert!(!host.matches("anotherexample.org"));
+        assert!(!host.matches("example.org.net"));
+    }
+
+    #[test]
+    fn hosts_enforce_shared_budget() {
+        let hosts = Hosts {
+            limits: vec![Host {
+                domain: "example.org".to_owned(),
+                max_requests: Some(2),
+            }],
+            traffic: Default::default(),
+        };
+
+        let foo = Url::parse("https://foo.example.org/").unwrap();
+        let bar = Url::parse("https://bar.example.org/").unwrap();
+
+        assert_eq!(hosts.admit(&foo, Some("foo")).unwrap(), "example.org");
+        assert_eq!(hosts.admit(&bar, Some("bar")).unwrap(), "example.org");
+        hosts.admit(&foo, Some("foo")).unwrap_err();
+
+        hosts.received("example.org", 42);
+
+        let traffic = &hosts.traffic.lock()["example.org"];
+        assert_eq!(traffic.sources, ["foo", "bar"]);
+        assert_eq!(traffic.requests, 2);
+        assert_eq!(traffic.bytes, 42);
+        assert_eq!(traffic.budget, Some(2));
+        assert_eq!(traffic.refused, 1);
+    }
+
+    #[tokio::test]
+    async fn retry_request_fowards_success() {
+        pause();
+        let start = Instant::now();
+
+        retry_request::<_, _, _, Error>(|| async { Ok(Bytes::new()) })
+            .await
+            .unwrap();
+
+        assert_eq!(start.elapsed().as_secs(), 0);
+    }
+
+    #[tokio::test]
+    async fn retry_request_fowards_failure() {
+        pause();
+        let start = Instant::now();
+
+        retry_request::<_, _, Bytes, _>(|| async { Err(anyhow!("failure")) })
+            .await
+            .unwrap_err();
+
+        assert_eq!(start.elapsed().as_secs(), 1 + 10 + 100);
+    }
+
+    #[tokio::test]
+    async fn retry_request_retries_three_times() {
+        pause();
+        let start = Instant::now();
+
+        let mut count = 0;
+
+        retry_request(|| {
+            count += 1;
+
+            async move {
+                if count > 3 {
+                    Ok(Bytes::new())
+                } else {
+                    Err(anyhow!("failure"))
+                }
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(start.elapsed().as_secs(), 1 + 10 + 100);
+    }
+}
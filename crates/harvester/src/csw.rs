@@ -0,0 +1,1281 @@
+//! This harvester maps ISO 19139 metadata records retrieved via CSW into our catalogue.
+//!
+//! | Original field                              | Mapped field     | Comment                                                       |
+//! | ------------------------------------------- | ---------------- | ------------------------------------------------------------- |
+//! | fileIdentifier                              | id               |                                                               |
+//! | citation/title                              | title            |                                                               |
+//! | abstract                                    | description      |                                                               |
+//! | resourceConstraints                         | license          | JSON-encoded license in otherConstraints as per GDI-DE        |
+//! | descriptiveKeywords                         | tags             | CharacterString or Anchor                                     |
+//! | topicCategory                               | themes           | Mapped onto DCAT-AP themes and our own categories             |
+//! | extent/description                          | region           | Preferred over geographic identifiers                         |
+//! | extent/geographicElement                    |                  | Code of the first geographic identifier                       |
+//! | citation/identifier                         | identifiers      | Code of MD_Identifier, typed as DOI or URN if possible        |
+//! | parentIdentifier                            | relations        | Part of the dataset with that file identifier                 |
+//! | aggregationInfo                             | relations        | Associations of type largerWorkCitation or isComposedOf       |
+//! | resourceMaintenance                         | update_frequency | Code list value of MD_MaintenanceFrequencyCode                |
+//! | citation/date                               | issued           | Publication date preferred over creation and revision date    |
+//! | citation/date                               | modified         | Revision date                                                 |
+//! | extent/geographicElement                    | bbox             | Union of all bounding boxes                                   |
+//! | extent/temporalElement                      |                  | Beginning of temporal extent if no citation date is available |
+//! | language                                    | language         | Code list value of LanguageCode                               |
+//! | pointOfContact                              | contacts         | Organisation name preferred over individual name              |
+//! | pointOfContact                              | publisher        | Organisation with role publisher preferred over owner         |
+//! | distributionInfo/transferOptions/onLine     | resources        | Type inferred from file extension                             |
+//! | onLine/name                                 | title            | Of the resource                                               |
+//! | onLine/description                          | description      | Of the resource                                               |
+//! | distributionInfo/distributionFormat         | format           | Of all resources if only a single format is given             |
+use std::borrow::Cow;
+use std::error::Error as StdError;
+use std::fmt;
+use std::time::SystemTime;
+
+use anyhow::Result;
+use askama::Template;
+use cap_std::fs::Dir;
+use futures_util::future::{BoxFuture, FutureExt};
+use reqwest::{header::CONTENT_TYPE, StatusCode};
+use serde::Deserialize;
+use serde_json::from_str as from_json_str;
+use serde_roxmltree::{from_doc as from_xml_doc, roxmltree::Document};
+use smallvec::SmallVec;
+
+use umwelt_info_core::dataset::{
+    Contact, Dataset, Identifier, Language, PartialDate, Publisher, Relation, RelationTarget,
+    RelationType, Resource, ResourceType, Tag, Theme, UpdateFrequency,
+};
+
+use crate::{
+    client::{Client, Response},
+    date::parse_date,
+    fetch_remaining_pages, modified_since, write_dataset, HarvestCounters, Harvester, Outcome,
+    Source,
+};
+
+pub struct Csw;
+
+impl Harvester for Csw {
+    fn harvest<'a>(
+        &self,
+        dir: &'a Dir,
+        client: &'a Client,
+        source: &'a Source,
+        since: Option<SystemTime>,
+    ) -> BoxFuture<'a, Result<(usize, HarvestCounters)>> {
+        harvest(dir, client, source, since).boxed()
+    }
+
+    fn supports_delta(&self) -> bool {
+        true
+    }
+}
+
+/// Harvests all records or if `since` is given, only those modified since then.
+pub async fn harvest(
+    dir: &Dir,
+    client: &Client,
+    source: &Source,
+    since: Option<SystemTime>,
+) -> Result<(usize, HarvestCounters)> {
+    let max_records = source.batch_size;
+
+    let modified = since.map(|since| format!("Modified >= '{}'", modified_since(since)));
+
+    let constraint = match (&source.csw.constraint, modified) {
+        (Some(constraint), Some(modified)) => Some(format!("({constraint}) AND {modified}")),
+        (Some(constraint), None) => Some(constraint.clone()),
+        (None, modified) => modified,
+    };
+    let constraint = constraint.as_deref();
+
+    let (count, counters) = fetch_datasets(dir, client, source, constraint, max_records, 1).await?;
+
+    // Positions of records are one-based.
+    let counters = fetch_remaining_pages(source, count, counters, |offset, max_records| {
+        fetch_datasets(dir, client, source, constraint, max_records, 1 + offset)
+    })
+    .await;
+
+    Ok((count, counters))
+}
+
+#[tracing::instrument(skip(dir, client, source))]
+async fn fetch_datasets(
+    dir: &Dir,
+    client: &Client,
+    source: &Source,
+    constraint: Option<&str>,
+    max_records: usize,
+    start_pos: usize,
+) -> Result<(usize, HarvestCounters)> {
+    tracing::debug!(
+        "Fetching {} datasets starting at {}",
+        max_records,
+        start_pos
+    );
+
+    let body = GetRecordsRequest {
+        max_records,
+        start_pos,
+        options: &source.csw,
+        constraint,
+    }
+    .render()
+    .unwrap();
+
+    let key = format!("{}-{}", source.name, start_pos);
+
+    let TextOrExceptionReport(body) = client
+        .make_request(&key, |client| {
+            client
+                .post(source.url.clone())
+                .header(CONTENT_TYPE, "application/xml")
+                .body(body.clone())
+        })
+        .await?;
+
+    let document = Document::parse(&body)?;
+
+    if document.root_element().has_tag_name("ExceptionReport") {
+        let report = from_xml_doc::<ExceptionReport>(&document)?;
+
+        return Err(report.into());
+    }
+
+    let response = from_xml_doc::<GetRecordsResponse>(&document)?;
+
+    let count = response.results.num_records_matched;
+    let mut counters = HarvestCounters::fetched(response.results.records.len());
+
+    let raw_records = document
+        .descendants()
+        .filter(|node| node.has_tag_name("MD_Metadata"));
+
+    for (record, raw_record) in response.results.records.into_iter().zip(raw_records) {
+        client.preserve(
+            record.file_identifier.text,
+            body[raw_record.range()].as_bytes(),
+        );
+
+        counters.record(client.quarantine(&key, translate_dataset(dir, source, record).await));
+    }
+
+    Ok((count, counters))
+}
+
+pub(super) async fn translate_dataset(
+    dir: &Dir,
+    source: &Source,
+    record: Record<'_>,
+) -> Result<Outcome> {
+    let identifier = record.file_identifier.text;
+
+    // Formats are not tied to individual transfer options, so they are only used when unambiguous.
+    let format = match record
+        .distribution_info
+        .iter()
+        .flat_map(|distribution_info| &distribution_info.inner.formats)
+        .filter_map(|format| format.inner.as_ref())
+        .filter_map(|format| format.name.text())
+        .collect::<Vec<_>>()[..]
+    {
+        [format] => Some(format.to_owned()),
+        _ => None,
+    };
+
+    let resources = record
+        .distribution_info
+        .iter()
+        .flat_map(|distribution_info| &distribution_info.inner.transfer_options)
+        .filter_map(|transfer_options| transfer_options.inner.as_ref())
+        .flat_map(|transfer_options| &transfer_options.online)
+        .filter_map(|online| online.inner.as_ref())
+        .map(|online| {
+            let url = online.linkage.url.trim();
+
+            Resource {
+                r#type: url
+                    .rsplit_once('.')
+                    .map_or(ResourceType::Unknown, |(_, extension)| extension.into()),
+                url: url.to_owned(),
+                title: online
+                    .name
+                    .as_ref()
+                    .and_then(CharacterString::text)
+                    .map(ToOwned::to_owned),
+                description: online
+                    .description
+                    .as_ref()
+                    .and_then(CharacterString::text)
+                    .map(ToOwned::to_owned),
+                license: None,
+                content_type: None,
+                format: format.clone(),
+                content_length: None,
+                checksum: None,
+                link: None,
+            }
+        })
+        .collect();
+
+    let identification = record.identification_info.identification();
+
+    let license = identification.license().as_deref().into();
+    let tags = identification.tags();
+    let themes = identification.themes();
+    let region = identification.region();
+    let bbox = identification.bbox();
+    let issued = identification.issued();
+    let modified = identification.modified();
+    let contacts = identification.contacts();
+    let publisher = identification.publisher();
+    let language = identification.language();
+    let identifiers = identification.identifiers();
+    let update_frequency = identification.update_frequency();
+
+    let mut relations = identification.relations(&source.name);
+
+    if let Some(parent) = record
+        .parent_identifier
+        .as_ref()
+        .and_then(CharacterString::text)
+        .and_then(|parent| RelationTarget::parse(&source.name, parent))
+    {
+        let relation = Relation {
+            r#type: RelationType::IsPartOf,
+            target: parent,
+        };
+
+        if !relations.contains(&relation) {
+            relations.push(relation);
+        }
+    }
+
+    let title = identification.citation.inner.title.text;
+    let description = identification.r#abstract.text;
+
+    let dataset = Dataset {
+        title,
+        description,
+        summary: None,
+        comment: None,
+        provenance: source.provenance.clone(),
+        publisher,
+        license,
+        legal_basis: Vec::new(),
+        reporting_obligations: Vec::new(),
+        contacts,
+        tags,
+        themes,
+        region,
+        language,
+        bbox,
+        time_range: None,
+        issued,
+        modified,
+        update_frequency,
+        last_checked: None,
+        first_seen: None,
+        identifiers,
+        relations,
+        source_url: source.source_url().replace("{{id}}", identifier),
+        resources,
+    };
+
+    write_dataset(dir, source, identifier, dataset).await
+}
+
+/// Yields the body of error responses as well if they contain an `ows:ExceptionReport` so that it can be reported.
+struct TextOrExceptionReport(String);
+
+impl AsRef<[u8]> for TextOrExceptionReport {
+    fn as_ref(&self) -> &[u8] {
+        self.0.as_bytes()
+    }
+}
+
+impl Response for TextOrExceptionReport {
+    fn from_buf(buf: Vec<u8>) -> Result<Self> {
+        String::from_buf(buf).map(Self)
+    }
+
+    fn accept(status: StatusCode, buf: &[u8]) -> bool {
+        status.is_success()
+            || buf
+                .windows(b"ExceptionReport".len())
+                .any(|window| window == b"ExceptionReport")
+    }
+}
+
+#[derive(Template)]
+#[template(path = "csw_get_records.xml")]
+struct GetRecordsRequest<'a> {
+    max_records: usize,
+    start_pos: usize,
+    options: &'a Options,
+    /// The configured constraint combined with the one selecting modified records
+    constraint: Option<&'a str>,
+}
+
+/// Parameters of the `GetRecords` requests which can be configured per source via a `[sources.csw]` table
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct Options {
+    output_schema: String,
+    element_set_name: String,
+    type_names: String,
+    /// Optional CQL constraint to filter the records
+    constraint: Option<String>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            output_schema: "csw:Record".to_owned(),
+            element_set_name: "full".to_owned(),
+            type_names: "csw:Record".to_owned(),
+            constraint: None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct GetRecordsResponse<'a> {
+    #[serde(rename = "SearchResults", borrow)]
+    results: SearchResults<'a>,
+}
+
+/// Typed form of the `ows:ExceptionReport` documents sent by CSW servers in place of a response
+#[derive(Debug, Deserialize)]
+pub struct ExceptionReport {
+    #[serde(rename = "Exception", default)]
+    pub exceptions: Vec<Exception>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Exception {
+    #[serde(rename = "exceptionCode")]
+    pub code: String,
+    pub locator: Option<String>,
+    #[serde(rename = "ExceptionText")]
+    pub text: Option<String>,
+}
+
+impl fmt::Display for ExceptionReport {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str("CSW server reported ")?;
+
+        if self.exceptions.is_empty() {
+            return fmt.write_str("an unspecified exception");
+        }
+
+        for (index, exception) in self.exceptions.iter().enumerate() {
+            if index != 0 {
+                fmt.write_str("; ")?;
+            }
+
+            write!(fmt, "{}", exception.code)?;
+
+            if let Some(locator) = &exception.locator {
+                write!(fmt, " at {}", locator)?;
+            }
+
+            if let Some(text) = &exception.text {
+                write!(fmt, ": {}", text.trim())?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl StdError for ExceptionReport {}
+
+#[derive(Debug, Deserialize)]
+struct SearchResults<'a> {
+    #[serde(rename = "numberOfRecordsMatched")]
+    num_records_matched: usize,
+    #[serde(rename = "MD_Metadata", borrow)]
+    records: Vec<Record<'a>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Record<'a> {
+    #[serde(rename = "fileIdentifier", borrow)]
+    file_identifier: FileIdentifier<'a>,
+    #[serde(rename = "identificationInfo", borrow)]
+    identification_info: IdentificationInfo<'a>,
+    #[serde(rename = "parentIdentifier")]
+    parent_identifier: Option<CharacterString>,
+    #[serde(rename = "distributionInfo")]
+    distribution_info: Option<DistributionInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FileIdentifier<'a> {
+    #[serde(rename = "CharacterString")]
+    text: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+enum IdentificationInfo<'a> {
+    #[serde(rename = "MD_DataIdentification", borrow)]
+    Data(Identification<'a>),
+    #[serde(rename = "SV_ServiceIdentification", borrow)]
+    Service(Identification<'a>),
+}
+
+impl<'a> IdentificationInfo<'a> {
+    fn identification(self) -> Identification<'a> {
+        match self {
+            Self::Data(identification) => identification,
+            Self::Service(identification) => identification,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Identification<'a> {
+    #[serde(borrow)]
+    citation: Citation<'a>,
+    r#abstract: Abstract,
+    #[serde(rename = "resourceConstraints", default, borrow)]
+    resource_constraints: Vec<ResourceConstraints<'a>>,
+    #[serde(rename = "descriptiveKeywords", default)]
+    descriptive_keywords: Vec<DescriptiveKeywords>,
+    #[serde(rename = "pointOfContact", default, borrow)]
+    points_of_contact: Vec<PointOfContact<'a>>,
+    #[serde(rename = "extent", default)]
+    extents: Vec<Extent>,
+    #[serde(rename = "language", default, borrow)]
+    languages: Vec<LanguageElement<'a>>,
+    #[serde(rename = "topicCategory", default)]
+    topic_categories: Vec<TopicCategory>,
+    #[serde(rename = "aggregationInfo", default, borrow)]
+    aggregation_info: Vec<AggregationInfo<'a>>,
+    #[serde(rename = "resourceMaintenance", default, borrow)]
+    resource_maintenance: Vec<ResourceMaintenance<'a>>,
+}
+
+impl Identification<'_> {
+    /// Extract the license ID for Open Data licenses
+    ///
+    /// Based on section 3.6 from [Konventionen zu Metadaten][https://www.gdi-de.org/download/AK_Metadaten_Konventionen_zu_Metadaten.pdf].
+    fn license(&self) -> Option<Cow<str>> {
+        for resource_constraints in &self.resource_constraints {
+            if let Some(legal_constraints) = &resource_constraints.legal_constraints {
+                for use_constraints in &legal_constraints.use_constraints {
+                    if use_constraints.restriction_code.value == "otherRestrictions" {
+                        for other_constraints in &legal_constraints.other_constraints {
+                            if let Some(text) = &other_constraints.text {
+                                if let Ok(license) = from_json_str::<License>(text) {
+                                    return Some(license.id);
+                                }
+                            }
+                        }
+
+                        break;
+                    }
+                }
+            }
+        }
+
+        None
+    }
+
+    fn tags(&self) -> Vec<Tag> {
+        let mut tags = Vec::<Tag>::new();
+
+        for descriptive_keywords in &self.descriptive_keywords {
+            if let Some(keywords) = &descriptive_keywords.inner {
+                for keyword in &keywords.keywords {
+                    if let Some(text) = keyword.text() {
+                        let tag = text.into();
+
+                        if !tags.contains(&tag) {
+                            tags.push(tag);
+                        }
+                    }
+                }
+            }
+        }
+
+        tags
+    }
+
+    fn extents(&self) -> impl Iterator<Item = &ExExtent> {
+        self.extents
+            .iter()
+            .filter_map(|extent| extent.inner.as_ref())
+    }
+
+    fn region(&self) -> Option<String> {
+        self.extents()
+            .find_map(|extent| extent.description.as_ref().and_then(CharacterString::text))
+            .or_else(|| {
+                self.extents()
+                    .flat_map(|extent| &extent.geographic_elements)
+                    .filter_map(|element| element.description.as_ref())
+                    .find_map(|description| description.identifier.inner.code.text())
+            })
+            .map(ToOwned::to_owned)
+    }
+
+    /// Combines all bounding boxes into one enclosing them.
+    fn bbox(&self) -> Option<[f64; 4]> {
+        self.extents()
+            .flat_map(|extent| &extent.geographic_elements)
+            .filter_map(|element| element.bounding_box.as_ref())
+            .filter_map(BoundingBox::bbox)
+            .reduce(|lhs, rhs| {
+                [
+                    lhs[0].min(rhs[0]),
+                    lhs[1].min(rhs[1]),
+                    lhs[2].max(rhs[2]),
+                    lhs[3].max(rhs[3]),
+                ]
+            })
+    }
+
+    fn issued(&self) -> Option<PartialDate> {
+        ["publication", "creation", "revision"]
+            .iter()
+            .find_map(|date_type| {
+                self.citation
+                    .inner
+                    .dates
+                    .iter()
+                    .filter(|date| date.inner.date_type.code.value == *date_type)
+                    .find_map(|date| date.inner.date.value())
+            })
+            .or_else(|| {
+                self.extents()
+                    .flat_map(|extent| &extent.temporal_elements)
+                    .filter_map(|element| element.inner.as_ref())
+                    .find_map(|extent| extent.extent.begin())
+            })
+            .and_then(parse_date)
+    }
+
+    fn modified(&self) -> Option<PartialDate> {
+        self.citation
+            .inner
+            .dates
+            .iter()
+            .filter(|date| date.inner.date_type.code.value == "revision")
+            .find_map(|date| date.inner.date.value())
+            .and_then(parse_date)
+    }
+
+    fn contacts(&self) -> Vec<Contact> {
+        self.points_of_contact
+            .iter()
+            .filter_map(|point_of_contact| point_of_contact.party.as_ref())
+            .filter_map(|party| {
+                let name = party
+                    .organisation_name
+                    .as_ref()
+                    .and_then(CharacterString::text)
+                    .or_else(|| {
+                        party
+                            .individual_name
+                            .as_ref()
+                            .and_then(CharacterString::text)
+                    })?
+                    .to_owned();
+
+                let emails = party
+                    .contact_info
+                    .iter()
+                    .filter_map(|contact_info| contact_info.inner.address.as_ref())
+                    .flat_map(|address| &address.inner.emails)
+                    .filter_map(CharacterString::text)
+                    .map(ToOwned::to_owned)
+                    .collect();
+
+                Some(Contact { name, emails })
+            })
+            .collect()
+    }
+
+    /// Topic categories are also what GeoNetwork exposes as `topicCat`.
+    fn themes(&self) -> Vec<Theme> {
+        let mut themes = Vec::new();
+
+        for topic_category in &self.topic_categories {
+            if let Some(code) = &topic_category.code {
+                for theme in Theme::from_iso_topic(code) {
+                    if !themes.contains(theme) {
+                        themes.push(*theme);
+                    }
+                }
+            }
+        }
+
+        themes
+    }
+
+    fn identifiers(&self) -> SmallVec<[Identifier; 1]> {
+        let mut identifiers = SmallVec::new();
+
+        for identifier in &self.citation.inner.identifiers {
+            if let Some(identifier) = identifier
+                .inner
+                .as_ref()
+                .and_then(|identifier| identifier.code.text())
+                .and_then(Identifier::parse)
+            {
+                if !identifiers.contains(&identifier) {
+                    identifiers.push(identifier);
+                }
+            }
+        }
+
+        identifiers
+    }
+
+    fn relations(&self, source: &str) -> Vec<Relation> {
+        let mut relations = Vec::new();
+
+        for aggregation_info in &self.aggregation_info {
+            let Some(aggregate) = &aggregation_info.inner else {
+                continue;
+            };
+
+            let r#type = match aggregate.association_type.code.value {
+                "largerWorkCitation" => RelationType::IsPartOf,
+                "isComposedOf" => RelationType::HasPart,
+                _ => continue,
+            };
+
+            if let Some(target) = aggregate
+                .identifier
+                .as_ref()
+                .and_then(|identifier| identifier.inner.as_ref())
+                .and_then(|identifier| identifier.code.text())
+                .and_then(|code| RelationTarget::parse(source, code))
+            {
+                let relation = Relation { r#type, target };
+
+                if !relations.contains(&relation) {
+                    relations.push(relation);
+                }
+            }
+        }
+
+        relations
+    }
+
+    fn update_frequency(&self) -> Option<UpdateFrequency> {
+        self.resource_maintenance
+            .iter()
+            .filter_map(|resource_maintenance| resource_maintenance.inner.as_ref())
+            .filter_map(|maintenance| maintenance.frequency.as_ref())
+            .filter_map(|frequency| frequency.code.as_ref())
+            .find_map(|code| UpdateFrequency::parse(code.value))
+    }
+
+    fn language(&self) -> Option<Language> {
+        self.languages
+            .iter()
+            .filter_map(|language| language.code.as_ref())
+            .find_map(|code| Language::parse(code.value))
+    }
+
+    fn publisher(&self) -> Option<Publisher> {
+        let parties = || {
+            self.points_of_contact
+                .iter()
+                .filter_map(|point_of_contact| point_of_contact.party.as_ref())
+        };
+
+        let party = ["publisher", "owner"].into_iter().find_map(|role| {
+            parties().find(|party| {
+                party
+                    .role
+                    .as_ref()
+                    .map_or(false, |role1| role1.code.value == role)
+            })
+        })?;
+
+        let name = party
+            .organisation_name
+            .as_ref()
+            .and_then(CharacterString::text)?
+            .to_owned();
+
+        let url = party
+            .contact_info
+            .as_ref()
+            .and_then(|contact_info| contact_info.inner.online_resource.as_ref())
+            .and_then(|online| online.inner.as_ref())
+            .map(|online| online.linkage.url.trim().to_owned());
+
+        Some(Publisher { name, url })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Citation<'a> {
+    #[serde(rename = "CI_Citation", borrow)]
+    inner: CitationInner<'a>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CitationInner<'a> {
+    title: Title,
+    #[serde(rename = "date", default, borrow)]
+    dates: Vec<CitationDate<'a>>,
+    #[serde(rename = "identifier", default)]
+    identifiers: Vec<IdentifierElement>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResourceMaintenance<'a> {
+    #[serde(rename = "MD_MaintenanceInformation", borrow)]
+    inner: Option<MaintenanceInformation<'a>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MaintenanceInformation<'a> {
+    #[serde(rename = "maintenanceAndUpdateFrequency", borrow)]
+    frequency: Option<MaintenanceFrequency<'a>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MaintenanceFrequency<'a> {
+    #[serde(rename = "MD_MaintenanceFrequencyCode", borrow)]
+    code: Option<CodeListValue<'a>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AggregationInfo<'a> {
+    #[serde(rename = "MD_AggregateInformation", borrow)]
+    inner: Option<AggregateInformation<'a>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AggregateInformation<'a> {
+    #[serde(rename = "aggregateDataSetIdentifier")]
+    identifier: Option<IdentifierElement>,
+    #[serde(rename = "associationType", borrow)]
+    association_type: AssociationType<'a>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AssociationType<'a> {
+    #[serde(rename = "DS_AssociationTypeCode", borrow)]
+    code: CodeListValue<'a>,
+}
+
+#[derive(Debug, Deserialize)]
+struct IdentifierElement {
+    #[serde(rename = "MD_Identifier")]
+    inner: Option<MdIdentifier>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CitationDate<'a> {
+    #[serde(rename = "CI_Date", borrow)]
+    inner: CiDate<'a>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CiDate<'a> {
+    #[serde(borrow)]
+    date: DateValue<'a>,
+    #[serde(rename = "dateType", borrow)]
+    date_type: DateType<'a>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DateValue<'a> {
+    #[serde(rename = "Date", borrow)]
+    date: Option<&'a str>,
+    #[serde(rename = "DateTime", borrow)]
+    date_time: Option<&'a str>,
+}
+
+impl<'a> DateValue<'a> {
+    fn value(&self) -> Option<&'a str> {
+        self.date.or(self.date_time).map(str::trim)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct DateType<'a> {
+    #[serde(rename = "CI_DateTypeCode", borrow)]
+    code: CodeListValue<'a>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Title {
+    #[serde(rename = "CharacterString")]
+    text: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Abstract {
+    #[serde(rename = "CharacterString")]
+    text: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResourceConstraints<'a> {
+    #[serde(rename = "MD_LegalConstraints", borrow)]
+    legal_constraints: Option<LegalConstraints<'a>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LegalConstraints<'a> {
+    #[serde(rename = "useConstraints", default, borrow)]
+    use_constraints: Vec<UseConstraints<'a>>,
+    #[serde(rename = "otherConstraints", default, borrow)]
+    other_constraints: Vec<OtherConstraints<'a>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct UseConstraints<'a> {
+    #[serde(rename = "MD_RestrictionCode", borrow)]
+    restriction_code: CodeListValue<'a>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CodeListValue<'a> {
+    #[serde(rename = "codeListValue", borrow)]
+    value: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+struct OtherConstraints<'a> {
+    #[serde(rename = "CharacterString", borrow)]
+    text: Option<&'a str>,
+}
+
+#[derive(Debug, Deserialize)]
+struct License<'a> {
+    #[serde(borrow)]
+    id: Cow<'a, str>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CharacterString {
+    #[serde(rename = "CharacterString")]
+    text: Option<String>,
+}
+
+impl CharacterString {
+    fn text(&self) -> Option<&str> {
+        self.text
+            .as_deref()
+            .map(str::trim)
+            .filter(|text| !text.is_empty())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct DescriptiveKeywords {
+    #[serde(rename = "MD_Keywords")]
+    inner: Option<Keywords>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Keywords {
+    #[serde(rename = "keyword", default)]
+    keywords: Vec<Keyword>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Keyword {
+    #[serde(rename = "CharacterString")]
+    text: Option<String>,
+    #[serde(rename = "Anchor")]
+    anchor: Option<String>,
+}
+
+impl Keyword {
+    fn text(&self) -> Option<&str> {
+        self.text
+            .as_deref()
+            .or(self.anchor.as_deref())
+            .map(str::trim)
+            .filter(|text| !text.is_empty())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct PointOfContact<'a> {
+    #[serde(rename = "CI_ResponsibleParty", borrow)]
+    party: Option<ResponsibleParty<'a>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResponsibleParty<'a> {
+    #[serde(rename = "individualName")]
+    individual_name: Option<CharacterString>,
+    #[serde(rename = "organisationName")]
+    organisation_name: Option<CharacterString>,
+    #[serde(rename = "contactInfo")]
+    contact_info: Option<ContactInfo>,
+    #[serde(borrow)]
+    role: Option<Role<'a>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TopicCategory {
+    #[serde(rename = "MD_TopicCategoryCode")]
+    code: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LanguageElement<'a> {
+    #[serde(rename = "LanguageCode", borrow)]
+    code: Option<CodeListValue<'a>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Role<'a> {
+    #[serde(rename = "CI_RoleCode", borrow)]
+    code: CodeListValue<'a>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ContactInfo {
+    #[serde(rename = "CI_Contact")]
+    inner: CiContact,
+}
+
+#[derive(Debug, Deserialize)]
+struct CiContact {
+    address: Option<Address>,
+    #[serde(rename = "onlineResource")]
+    online_resource: Option<OnLine>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Address {
+    #[serde(rename = "CI_Address")]
+    inner: CiAddress,
+}
+
+#[derive(Debug, Deserialize)]
+struct CiAddress {
+    #[serde(rename = "electronicMailAddress", default)]
+    emails: Vec<CharacterString>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Extent {
+    #[serde(rename = "EX_Extent")]
+    inner: Option<ExExtent>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ExExtent {
+    description: Option<CharacterString>,
+    #[serde(rename = "geographicElement", default)]
+    geographic_elements: Vec<GeographicElement>,
+    #[serde(rename = "temporalElement", default)]
+    temporal_elements: Vec<TemporalElement>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GeographicElement {
+    #[serde(rename = "EX_GeographicDescription")]
+    description: Option<GeographicDescription>,
+    #[serde(rename = "EX_GeographicBoundingBox")]
+    bounding_box: Option<BoundingBox>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BoundingBox {
+    #[serde(rename = "westBoundLongitude")]
+    west: Decimal,
+    #[serde(rename = "southBoundLatitude")]
+    south: Decimal,
+    #[serde(rename = "eastBoundLongitude")]
+    east: Decimal,
+    #[serde(rename = "northBoundLatitude")]
+    north: Decimal,
+}
+
+impl BoundingBox {
+    fn bbox(&self) -> Option<[f64; 4]> {
+        Some([
+            self.west.value()?,
+            self.south.value()?,
+            self.east.value()?,
+            self.north.value()?,
+        ])
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Decimal {
+    #[serde(rename = "Decimal")]
+    text: Option<String>,
+}
+
+impl Decimal {
+    fn value(&self) -> Option<f64> {
+        self.text.as_deref()?.trim().parse().ok()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct GeographicDescription {
+    #[serde(rename = "geographicIdentifier")]
+    identifier: GeographicIdentifier,
+}
+
+#[derive(Debug, Deserialize)]
+struct GeographicIdentifier {
+    #[serde(rename = "MD_Identifier")]
+    inner: MdIdentifier,
+}
+
+#[derive(Debug, Deserialize)]
+struct MdIdentifier {
+    code: CharacterString,
+}
+
+#[derive(Debug, Deserialize)]
+struct TemporalElement {
+    #[serde(rename = "EX_TemporalExtent")]
+    inner: Option<TemporalExtent>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TemporalExtent {
+    extent: TimeExtent,
+}
+
+#[derive(Debug, Deserialize)]
+struct TimeExtent {
+    #[serde(rename = "TimePeriod")]
+    period: Option<TimePeriod>,
+    #[serde(rename = "TimeInstant")]
+    instant: Option<TimeInstant>,
+}
+
+impl TimeExtent {
+    fn begin(&self) -> Option<&str> {
+        self.period
+            .as_ref()
+            .and_then(|period| period.begin.as_deref())
+            .or_else(|| {
+                self.instant
+                    .as_ref()
+                    .and_then(|instant| instant.position.as_deref())
+            })
+            .map(str::trim)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct TimePeriod {
+    #[serde(rename = "beginPosition")]
+    begin: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TimeInstant {
+    #[serde(rename = "timePosition")]
+    position: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DistributionInfo {
+    #[serde(rename = "MD_Distribution")]
+    inner: Distribution,
+}
+
+#[derive(Debug, Deserialize)]
+struct Distribution {
+    #[serde(rename = "distributionFormat", default)]
+    formats: Vec<DistributionFormat>,
+    #[serde(rename = "transferOptions", default)]
+    transfer_options: Vec<TransferOptions>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DistributionFormat {
+    #[serde(rename = "MD_Format")]
+    inner: Option<Format>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Format {
+    name: CharacterString,
+}
+
+#[derive(Debug, Deserialize)]
+struct TransferOptions {
+    #[serde(rename = "MD_DigitalTransferOptions")]
+    inner: Option<DigitalTransferOptions>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DigitalTransferOptions {
+    #[serde(rename = "onLine", default)]
+    online: Vec<OnLine>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OnLine {
+    #[serde(rename = "CI_OnlineResource")]
+    inner: Option<OnlineResource>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OnlineResource {
+    linkage: Linkage,
+    name: Option<CharacterString>,
+    description: Option<CharacterString>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Linkage {
+    #[serde(rename = "URL")]
+    url: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::env::temp_dir;
+    use std::fs::{create_dir_all, remove_dir_all};
+    use std::process;
+
+    use cap_std::ambient_authority;
+    use time::{Date, Month};
+    use toml::from_str as from_toml_str;
+
+    #[tokio::test]
+    async fn iso_records_are_translated() {
+        let text = r#"
+<gmd:MD_Metadata xmlns:gmd="http://www.isotc211.org/2005/gmd" xmlns:gco="http://www.isotc211.org/2005/gco" xmlns:gmx="http://www.isotc211.org/2005/gmx">
+  <gmd:fileIdentifier>
+    <gco:CharacterString>6a0e1c2d-pegel</gco:CharacterString>
+  </gmd:fileIdentifier>
+  <gmd:identificationInfo>
+    <gmd:MD_DataIdentification>
+      <gmd:citation>
+        <gmd:CI_Citation>
+          <gmd:title>
+            <gco:CharacterString>Pegelmessstellen</gco:CharacterString>
+          </gmd:title>
+          <gmd:date>
+            <gmd:CI_Date>
+              <gmd:date>
+                <gco:DateTime>2023-05-06T12:00:00</gco:DateTime>
+              </gmd:date>
+              <gmd:dateType>
+                <gmd:CI_DateTypeCode codeList="http://standards.iso.org/iso/19139/resources/gmxCodelists.xml#CI_DateTypeCode" codeListValue="revision" />
+              </gmd:dateType>
+            </gmd:CI_Date>
+          </gmd:date>
+          <gmd:date>
+            <gmd:CI_Date>
+              <gmd:date>
+                <gco:Date>2021-03-04</gco:Date>
+              </gmd:date>
+              <gmd:dateType>
+                <gmd:CI_DateTypeCode codeList="http://standards.iso.org/iso/19139/resources/gmxCodelists.xml#CI_DateTypeCode" codeListValue="publication" />
+              </gmd:dateType>
+            </gmd:CI_Date>
+          </gmd:date>
+        </gmd:CI_Citation>
+      </gmd:citation>
+      <gmd:abstract>
+        <gco:CharacterString>Lage der Pegelmessstellen an Bundeswasserstraßen</gco:CharacterString>
+      </gmd:abstract>
+      <gmd:descriptiveKeywords>
+        <gmd:MD_Keywords>
+          <gmd:keyword>
+            <gco:CharacterString>Pegel</gco:CharacterString>
+          </gmd:keyword>
+          <gmd:keyword>
+            <gmx:Anchor xlink:href="http://inspire.ec.europa.eu/theme/hy" xmlns:xlink="http://www.w3.org/1999/xlink">Gewässernetz</gmx:Anchor>
+          </gmd:keyword>
+          <gmd:keyword>
+            <gco:CharacterString>Pegel</gco:CharacterString>
+          </gmd:keyword>
+        </gmd:MD_Keywords>
+      </gmd:descriptiveKeywords>
+    </gmd:MD_DataIdentification>
+  </gmd:identificationInfo>
+  <gmd:distributionInfo>
+    <gmd:MD_Distribution>
+      <gmd:transferOptions>
+        <gmd:MD_DigitalTransferOptions>
+          <gmd:onLine>
+            <gmd:CI_OnlineResource>
+              <gmd:linkage>
+                <gmd:URL> https://example.org/pegel.csv </gmd:URL>
+              </gmd:linkage>
+              <gmd:name>
+                <gco:CharacterString>Messstellen</gco:CharacterString>
+              </gmd:name>
+            </gmd:CI_OnlineResource>
+          </gmd:onLine>
+        </gmd:MD_DigitalTransferOptions>
+      </gmd:transferOptions>
+    </gmd:MD_Distribution>
+  </gmd:distributionInfo>
+</gmd:MD_Metadata>
+"#;
+
+        let path = temp_dir().join(format!("umwelt-info-csw-{}", process::id()));
+        create_dir_all(&path).unwrap();
+
+        let dir = Dir::open_ambient_dir(&path, ambient_authority()).unwrap();
+
+        let source = from_toml_str::<Source>(
+            r#"
+name = "test"
+type = "csw"
+url = "http://localhost"
+provenance = "/test"
+"#,
+        )
+        .unwrap();
+
+        let document = Document::parse(text).unwrap();
+        let record = from_xml_doc::<Record>(&document).unwrap();
+
+        translate_dataset(&dir, &source, record).await.unwrap();
+
+        let dataset = Dataset::read(dir.open("6a0e1c2d-pegel").unwrap()).unwrap();
+
+        remove_dir_all(&path).unwrap();
+
+        assert_eq!(dataset.title, "Pegelmessstellen");
+        assert_eq!(
+            dataset.description.as_deref(),
+            Some("Lage der Pegelmessstellen an Bundeswasserstraßen")
+        );
+        assert_eq!(
+            dataset.tags,
+            [Tag::from("Pegel"), Tag::from("Gewässernetz")]
+        );
+        assert_eq!(
+            dataset.issued,
+            Some(PartialDate::Full(
+                Date::from_calendar_date(2021, Month::March, 4).unwrap()
+            ))
+        );
+        assert_eq!(
+            dataset.modified,
+            Some(PartialDate::Full(
+                Date::from_calendar_date(2023, Month::May, 6).unwrap()
+            ))
+        );
+
+        assert_eq!(dataset.resources.len(), 1);
+        assert_eq!(dataset.resources[0].url, "https://example.org/pegel.csv");
+        assert_eq!(dataset.resources[0].title.as_deref(), Some("Messstellen"));
+        assert!(matches!(dataset.resources[0].r#type, ResourceType::Csv));
+    }
+}
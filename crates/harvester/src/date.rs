@@ -0,0 +1,205 @@
+//! Lenient parsing of the date formats used by German sources.
+//!
+//! Besides the ISO 8601 formats understood by [`PartialDate`], this accepts
+//!
+//! | Format             | Example            |
+//! | ------------------ | ------------------ |
+//! | `D.M.YYYY`         | `1.2.2023 12:00`   |
+//! | `MM.YYYY`          | `02.2023`          |
+//! | `D. Monat YYYY`    | `1. Februar 2023`  |
+//! | `Monat YYYY`       | `Feb. 2023`        |
+//! | `YYYYMMDD`         | `20230201`         |
+use time::{Date, Month};
+
+use umwelt_info_core::dataset::PartialDate;
+
+/// Parses a possibly partial date, yielding `None` if none of the known formats match.
+pub fn parse_date(val: &str) -> Option<PartialDate> {
+    let val = val.trim();
+
+    if let Ok(date) = val.parse() {
+        return Some(date);
+    }
+
+    // Ignore the time of day following the date.
+    let date = val.split_whitespace().next()?;
+
+    if date.contains('.') && !date.ends_with('.') {
+        return parse_dotted(date);
+    }
+
+    if date.len() == 8 && date.bytes().all(|byte| byte.is_ascii_digit()) {
+        let year = date[..4].parse().ok()?;
+        let month = parse_month_number(&date[4..6])?;
+        let day = date[6..].parse().ok()?;
+
+        return full_date(year, month, day);
+    }
+
+    parse_month_name(val)
+}
+
+fn parse_dotted(val: &str) -> Option<PartialDate> {
+    let parts = val.split('.').collect::<Vec<_>>();
+
+    match parts[..] {
+        [day, month, year] => full_date(
+            parse_year(year)?,
+            parse_month_number(month)?,
+            day.parse().ok()?,
+        ),
+        [month, year] => Some(PartialDate::YearMonth(
+            parse_year(year)?,
+            parse_month_number(month)?,
+        )),
+        _ => None,
+    }
+}
+
+fn parse_month_name(val: &str) -> Option<PartialDate> {
+    let tokens = val
+        .split(|char: char| char.is_whitespace() || char == ',')
+        .filter(|token| !token.is_empty())
+        .collect::<Vec<_>>();
+
+    match tokens[..] {
+        [month, year] => Some(PartialDate::YearMonth(
+            parse_year(year)?,
+            month_from_name(month)?,
+        )),
+        [day, month, year] => full_date(
+            parse_year(year)?,
+            month_from_name(month)?,
+            day.strip_suffix('.')?.parse().ok()?,
+        ),
+        _ => None,
+    }
+}
+
+fn parse_year(val: &str) -> Option<i32> {
+    if val.len() != 4 {
+        return None;
+    }
+
+    val.parse().ok()
+}
+
+fn parse_month_number(val: &str) -> Option<Month> {
+    val.parse::<u8>().ok()?.try_into().ok()
+}
+
+fn month_from_name(val: &str) -> Option<Month> {
+    let val = val.trim_end_matches('.').to_lowercase();
+
+    let month = match &*val {
+        "januar" | "jänner" | "jan" | "jän" => Month::January,
+        "februar" | "feber" | "feb" => Month::February,
+        "märz" | "maerz" | "mär" | "mrz" => Month::March,
+        "april" | "apr" => Month::April,
+        "mai" => Month::May,
+        "juni" | "jun" => Month::June,
+        "juli" | "jul" => Month::July,
+        "august" | "aug" => Month::August,
+        "september" | "sept" | "sep" => Month::September,
+        "oktober" | "okt" => Month::October,
+        "november" | "nov" => Month::November,
+        "dezember" | "dez" => Month::December,
+        _ => return None,
+    };
+
+    Some(month)
+}
+
+fn full_date(year: i32, month: Month, day: u8) -> Option<PartialDate> {
+    Date::from_calendar_date(year, month, day)
+        .ok()
+        .map(PartialDate::Full)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn full(year: i32, month: Month, day: u8) -> Option<PartialDate> {
+        full_date(year, month, day)
+    }
+
+    #[test]
+    fn iso_dates() {
+        assert_eq!(parse_date("2023"), Some(PartialDate::Year(2023)));
+        assert_eq!(
+            parse_date("2023-02"),
+            Some(PartialDate::YearMonth(2023, Month::February))
+        );
+        assert_eq!(parse_date("2023-02-01"), full(2023, Month::February, 1));
+        assert_eq!(
+            parse_date("2023-02-01T12:00:00.000Z"),
+            full(2023, Month::February, 1)
+        );
+        assert_eq!(
+            parse_date(" 2023/02/01 12:00:00 "),
+            full(2023, Month::February, 1)
+        );
+    }
+
+    #[test]
+    fn dotted_dates() {
+        assert_eq!(parse_date("01.02.2023"), full(2023, Month::February, 1));
+        assert_eq!(parse_date("1.2.2023"), full(2023, Month::February, 1));
+        assert_eq!(
+            parse_date("01.02.2023 12:34:56"),
+            full(2023, Month::February, 1)
+        );
+        assert_eq!(
+            parse_date("02.2023"),
+            Some(PartialDate::YearMonth(2023, Month::February))
+        );
+    }
+
+    #[test]
+    fn compact_dates() {
+        assert_eq!(parse_date("20230201"), full(2023, Month::February, 1));
+    }
+
+    #[test]
+    fn month_names() {
+        assert_eq!(
+            parse_date("Februar 2023"),
+            Some(PartialDate::YearMonth(2023, Month::February))
+        );
+        assert_eq!(
+            parse_date("Feb. 2023"),
+            Some(PartialDate::YearMonth(2023, Month::February))
+        );
+        assert_eq!(
+            parse_date("März, 2023"),
+            Some(PartialDate::YearMonth(2023, Month::March))
+        );
+        assert_eq!(
+            parse_date("Maerz 2023"),
+            Some(PartialDate::YearMonth(2023, Month::March))
+        );
+        assert_eq!(
+            parse_date("Jänner 2023"),
+            Some(PartialDate::YearMonth(2023, Month::January))
+        );
+        assert_eq!(
+            parse_date("1. Februar 2023"),
+            full(2023, Month::February, 1)
+        );
+        assert_eq!(parse_date("24. Dez. 2022"), full(2022, Month::December, 24));
+        assert_eq!(parse_date("31. SEPTEMBER 2022"), None);
+    }
+
+    #[test]
+    fn invalid_dates() {
+        assert_eq!(parse_date(""), None);
+        assert_eq!(parse_date("unbekannt"), None);
+        assert_eq!(parse_date("32.01.2023"), None);
+        assert_eq!(parse_date("01.13.2023"), None);
+        assert_eq!(parse_date("01.02.23"), None);
+        assert_eq!(parse_date("Frühjahr 2023"), None);
+        assert_eq!(parse_date("1 Februar 2023"), None);
+        assert_eq!(parse_date("20231301"), None);
+    }
+}
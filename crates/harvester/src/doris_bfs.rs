@@ -0,0 +1,486 @@
+//! This harvester prefers the OAI-PMH interface of the DSpace instance behind DORIS
+//! and falls back to scraping its HTML pages only if that interface is unavailable.
+use std::time::SystemTime;
+
+use anyhow::{anyhow, ensure, Result};
+use cap_std::fs::Dir;
+use futures_util::future::{BoxFuture, FutureExt};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+use serde_roxmltree::{from_doc, roxmltree::Document};
+use smallvec::{smallvec, SmallVec};
+use url::Url;
+
+use umwelt_info_core::dataset::{Dataset, Identifier, License};
+
+use crate::{
+    client::Client, fetch_many, write_dataset, HarvestCounters, Harvester, Outcome, Source,
+};
+
+pub struct DorisBfs;
+
+impl Harvester for DorisBfs {
+    fn harvest<'a>(
+        &self,
+        dir: &'a Dir,
+        client: &'a Client,
+        source: &'a Source,
+        _since: Option<SystemTime>,
+    ) -> BoxFuture<'a, Result<(usize, HarvestCounters)>> {
+        harvest(dir, client, source).boxed()
+    }
+}
+
+pub async fn harvest(
+    dir: &Dir,
+    client: &Client,
+    source: &Source,
+) -> Result<(usize, HarvestCounters)> {
+    let (count, mut counters, mut token) = match fetch_records(dir, client, source, 0, None).await {
+        Ok(res) => res,
+        Err(err) => {
+            tracing::warn!(
+                "Falling back to scraping as OAI-PMH is unavailable: {:#}",
+                err
+            );
+
+            return scrape(dir, client, source).await;
+        }
+    };
+
+    tracing::info!("Harvesting {} datasets", count);
+
+    let mut request = 1;
+
+    while let Some(token1) = token {
+        match fetch_records(dir, client, source, request, Some(&token1)).await {
+            Ok((_count, counters1, token1)) => {
+                counters += counters1;
+                token = token1;
+            }
+            Err(err) => {
+                tracing::error!("{:#}", err);
+
+                // Without the next resumption token, the remaining records cannot be requested.
+                counters.failed += count.saturating_sub(counters.fetched);
+                break;
+            }
+        }
+
+        request += 1;
+    }
+
+    Ok((count, counters))
+}
+
+#[tracing::instrument(skip(dir, client, source, token))]
+async fn fetch_records(
+    dir: &Dir,
+    client: &Client,
+    source: &Source,
+    request: usize,
+    token: Option<&str>,
+) -> Result<(usize, HarvestCounters, Option<String>)> {
+    tracing::debug!("Fetching records");
+
+    let url = source.url.join("/oai/request")?;
+
+    #[derive(Serialize)]
+    struct Params<'a> {
+        verb: &'a str,
+        #[serde(rename = "metadataPrefix", skip_serializing_if = "Option::is_none")]
+        metadata_prefix: Option<&'a str>,
+        #[serde(rename = "resumptionToken", skip_serializing_if = "Option::is_none")]
+        resumption_token: Option<&'a str>,
+    }
+
+    let params = Params {
+        verb: "ListRecords",
+        metadata_prefix: token.is_none().then_some("oai_dc"),
+        resumption_token: token,
+    };
+
+    let key = format!("{}-oai-{}", source.name, request);
+
+    let body: String = client
+        .make_request(&key, |client| client.get(url.clone()).query(&params))
+        .await?;
+
+    let document = Document::parse(&body)?;
+
+    let response = from_doc::<OaiPmh>(&document)?;
+
+    if let Some(error) = response.error {
+        return Err(anyhow!("OAI-PMH request failed with code {}", error.code));
+    }
+
+    let records = response
+        .list_records
+        .ok_or_else(|| anyhow!("Missing ListRecords element"))?
+        .records;
+
+    let (token, count) = match document
+        .descendants()
+        .find(|node| node.has_tag_name("resumptionToken"))
+    {
+        Some(node) => (
+            node.text()
+                .map(str::trim)
+                .filter(|token| !token.is_empty())
+                .map(ToOwned::to_owned),
+            node.attribute("completeListSize")
+                .and_then(|count| count.parse().ok()),
+        ),
+        None => (None, None),
+    };
+
+    let count = count.unwrap_or(records.len());
+    let mut counters = HarvestCounters::fetched(records.len());
+
+    let raw_records = document
+        .descendants()
+        .filter(|node| node.has_tag_name("record"));
+
+    for (record, raw_record) in records.into_iter().zip(raw_records) {
+        let raw_record = &body[raw_record.range()];
+
+        match record.metadata {
+            Some(metadata) => counters.record(client.quarantine(
+                &key,
+                translate_record(dir, client, source, metadata.dc, raw_record).await,
+            )),
+            // Deleted records carry only a header.
+            None => counters.filtered += 1,
+        }
+    }
+
+    Ok((count, counters, token))
+}
+
+async fn translate_record(
+    dir: &Dir,
+    client: &Client,
+    source: &Source,
+    metadata: DublinCore<'_>,
+    raw_record: &str,
+) -> Result<Outcome> {
+    let identifier = metadata
+        .identifiers
+        .iter()
+        .map(|identifier| identifier.trim())
+        .find(|identifier| identifier.starts_with("urn:"))
+        .ok_or_else(|| anyhow!("Missing identifier"))?;
+
+    client.preserve(identifier, raw_record.as_bytes());
+
+    // DSpace assigns URNs resolvable via the DNB which double as our identifier.
+    let identifiers = metadata
+        .identifiers
+        .iter()
+        .filter_map(|identifier| Identifier::parse(identifier))
+        .filter(Identifier::is_persistent)
+        .collect();
+
+    let source_url = metadata
+        .identifiers
+        .iter()
+        .map(|identifier| identifier.trim())
+        .find(|identifier| identifier.starts_with("http"))
+        .unwrap_or_else(|| source.source_url());
+
+    let title = metadata
+        .titles
+        .first()
+        .ok_or_else(|| anyhow!("Missing title"))?
+        .trim()
+        .to_owned();
+
+    let description = metadata
+        .descriptions
+        .first()
+        .map(|description| description.trim().to_owned());
+
+    let dataset = Dataset {
+        title,
+        description,
+        summary: None,
+        comment: None,
+        provenance: source.provenance.clone(),
+        publisher: None,
+        license: License::DorisBfs,
+        legal_basis: Vec::new(),
+        reporting_obligations: Vec::new(),
+        contacts: Vec::new(),
+        tags: Vec::new(),
+        themes: Vec::new(),
+        region: None,
+        language: None,
+        bbox: None,
+        time_range: None,
+        issued: None,
+        modified: None,
+        update_frequency: None,
+        last_checked: None,
+        first_seen: None,
+        identifiers,
+        relations: Vec::new(),
+        source_url: source_url.to_owned(),
+        resources: SmallVec::new(),
+    };
+
+    write_dataset(dir, source, identifier, dataset).await
+}
+
+async fn scrape(dir: &Dir, client: &Client, source: &Source) -> Result<(usize, HarvestCounters)> {
+    let rpp = source.batch_size;
+
+    let (count, counters) = fetch_datasets(dir, client, source, rpp, 0).await?;
+    tracing::info!("Harvesting {} datasets", count);
+
+    let requests = (count + rpp - 1) / rpp;
+    let offset = (1..requests).map(|request| request * rpp);
+
+    let counters = fetch_many(source, counters, offset, |offset| {
+        fetch_datasets(dir, client, source, rpp, offset)
+    })
+    .await;
+
+    Ok((count, counters))
+}
+
+#[tracing::instrument(skip(dir, client, source))]
+async fn fetch_datasets(
+    dir: &Dir,
+    client: &Client,
+    source: &Source,
+    rpp: usize,
+    offset: usize,
+) -> Result<(usize, HarvestCounters)> {
+    tracing::debug!("Fetching {} datasets starting at {}", rpp, offset);
+
+    let url = source.url.join("/jspui/browse")?;
+
+    #[derive(Serialize)]
+    struct Params {
+        rpp: usize,
+        offset: usize,
+    }
+
+    let body: String = client
+        .make_request(&format!("{}-browse-{}", source.name, offset), |client| {
+            client.get(url.clone()).query(&Params { rpp, offset })
+        })
+        .await?;
+
+    let count;
+    let handles;
+
+    {
+        let document = Html::parse_document(&body);
+
+        count = parse_count(&document)?;
+        handles = parse_handles(&document)?;
+    }
+
+    ensure!(
+        !handles.is_empty(),
+        "Could not parse handles at offset {}",
+        offset
+    );
+
+    let mut counters = HarvestCounters::fetched(handles.len());
+
+    for handle in &handles {
+        counters.record(fetch_dataset(dir, client, source, handle).await);
+    }
+
+    Ok((count, counters))
+}
+
+async fn fetch_dataset(
+    dir: &Dir,
+    client: &Client,
+    source: &Source,
+    handle: &str,
+) -> Result<Outcome> {
+    tracing::debug!("Fetching dataset at {}", handle);
+
+    let url = source.url.join(handle)?;
+
+    if !client.allowed_by_robots(&url).await {
+        return Ok(Outcome::Disallowed);
+    }
+
+    let key = format!(
+        "{}-handle-{}",
+        source.name,
+        handle.rsplit('/').next().unwrap()
+    );
+
+    let body: String = client
+        .make_request(&key, |client| client.get(url.clone()))
+        .await?;
+
+    client.quarantine(&key, translate_page(dir, source, url, &body).await)
+}
+
+async fn translate_page(dir: &Dir, source: &Source, url: Url, body: &str) -> Result<Outcome> {
+    let identifier;
+    let title;
+    let r#abstract;
+
+    {
+        let document = Html::parse_document(body);
+
+        identifier = document
+            .select(&SELECTORS.identifier_selector)
+            .filter_map(|element| element.value().attr("content"))
+            .find(|identifier| identifier.starts_with("urn:"))
+            .ok_or_else(|| anyhow!("Missing identifier"))?
+            .to_owned();
+
+        title = document
+            .select(&SELECTORS.title_selector)
+            .next()
+            .and_then(|element| element.value().attr("content"))
+            .ok_or_else(|| anyhow!("Missing title"))?
+            .to_owned();
+
+        r#abstract = document
+            .select(&SELECTORS.abstract_selector)
+            .next()
+            .and_then(|element| element.value().attr("content"))
+            .map(ToOwned::to_owned);
+    }
+
+    let dataset = Dataset {
+        title,
+        description: r#abstract,
+        summary: None,
+        comment: None,
+        provenance: source.provenance.clone(),
+        publisher: None,
+        license: License::DorisBfs,
+        legal_basis: Vec::new(),
+        reporting_obligations: Vec::new(),
+        contacts: Vec::new(),
+        tags: Vec::new(),
+        themes: Vec::new(),
+        region: None,
+        language: None,
+        bbox: None,
+        time_range: None,
+        issued: None,
+        modified: None,
+        update_frequency: None,
+        last_checked: None,
+        first_seen: None,
+        identifiers: smallvec![Identifier::Urn(identifier.clone())],
+        relations: Vec::new(),
+        source_url: url.into(),
+        resources: SmallVec::new(),
+    };
+
+    write_dataset(dir, source, &identifier, dataset).await
+}
+
+fn parse_count(document: &Html) -> Result<usize> {
+    let element = document
+        .select(&SELECTORS.range_selector)
+        .next()
+        .ok_or_else(|| anyhow!("Missing number of documents"))?;
+
+    let text = element.text().collect::<String>();
+
+    let captures = SELECTORS
+        .range_regex
+        .captures(&text)
+        .ok_or_else(|| anyhow!("Could not parse number of documents"))?;
+
+    let count = captures[3].parse::<usize>()?;
+
+    Ok(count)
+}
+
+fn parse_handles(document: &Html) -> Result<Vec<String>> {
+    let mut handles = Vec::new();
+
+    for element in document.select(&SELECTORS.handle_selector) {
+        handles.push(
+            element
+                .value()
+                .attr("href")
+                .ok_or_else(|| anyhow!("Missing handle reference"))?
+                .to_owned(),
+        );
+    }
+
+    Ok(handles)
+}
+
+#[derive(Debug, Deserialize)]
+struct OaiPmh<'a> {
+    #[serde(borrow)]
+    error: Option<OaiPmhError<'a>>,
+    #[serde(rename = "ListRecords", borrow)]
+    list_records: Option<ListRecords<'a>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OaiPmhError<'a> {
+    code: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+struct ListRecords<'a> {
+    #[serde(rename = "record", default, borrow)]
+    records: Vec<Record<'a>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Record<'a> {
+    #[serde(borrow)]
+    metadata: Option<Metadata<'a>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Metadata<'a> {
+    #[serde(borrow)]
+    dc: DublinCore<'a>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DublinCore<'a> {
+    #[serde(rename = "identifier", default, borrow)]
+    identifiers: Vec<&'a str>,
+    #[serde(rename = "title", default)]
+    titles: Vec<String>,
+    #[serde(rename = "description", default)]
+    descriptions: Vec<String>,
+}
+
+static SELECTORS: Lazy<Selectors> = Lazy::new(Selectors::default);
+
+struct Selectors {
+    range_selector: Selector,
+    range_regex: Regex,
+    handle_selector: Selector,
+    identifier_selector: Selector,
+    title_selector: Selector,
+    abstract_selector: Selector,
+}
+
+impl Default for Selectors {
+    fn default() -> Self {
+        Self {
+            range_selector: Selector::parse("div.browse_range").unwrap(),
+            range_regex: Regex::new(r#"Anzeige der Treffer (\d+) bis (\d+) von (\d+)"#).unwrap(),
+            handle_selector: Selector::parse("td[headers=t2] > a").unwrap(),
+            identifier_selector: Selector::parse(r#"head > meta[name="DC.identifier"]"#).unwrap(),
+            title_selector: Selector::parse(r#"head > meta[name="DC.title"]"#).unwrap(),
+            abstract_selector: Selector::parse(r#"head > meta[name="DCTERMS.abstract"]"#).unwrap(),
+        }
+    }
+}
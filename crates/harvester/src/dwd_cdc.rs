@@ -0,0 +1,438 @@
+//! This harvester walks the directory listings of the Climate Data Center (CDC) at opendata.dwd.de
+//! and groups the files of each directory into one dataset per station.
+//!
+//! | Original field             | Mapped field | Comment                                                 |
+//! | -------------------------- | ------------ | ------------------------------------------------------- |
+//! | directory path             | id           | Relative to the source URL, combined with Stations_id   |
+//! | Stations_id                | id           | Extracted from the file names                           |
+//! | Stationsname               | title        | Combined with the directory path                        |
+//! | Bundesland                 | region       |                                                         |
+//! | Stationshoehe              | description  |                                                         |
+//! | geoBreite, geoLaenge       | description  |                                                         |
+//! | von_datum, bis_datum       | description  |                                                         |
+//! | data files                 | resources    | All files containing the station ID                     |
+//! | DESCRIPTION_*.pdf          | resources    | Attached to all datasets of the directory               |
+//!
+//! Station metadata is taken from the `*Beschreibung_Stationen*.txt` file of each directory if present.
+//!
+//! The `url` of the source is expected to point to the root of the subtree to walk,
+//! e.g. `https://opendata.dwd.de/climate_environment/CDC/observations_germany/climate/daily/kl/`.
+//! All data is published under CC BY 4.0 as per the terms of use of DWD.
+use std::borrow::Cow;
+use std::str::from_utf8;
+use std::time::SystemTime;
+
+use anyhow::Result;
+use bytes::Bytes;
+use cap_std::fs::Dir;
+use futures_util::future::{BoxFuture, FutureExt};
+use hashbrown::HashMap;
+use once_cell::sync::Lazy;
+use scraper::{Html, Selector};
+use time::{macros::format_description, Date};
+use url::Url;
+
+use umwelt_info_core::dataset::{Dataset, License, Resource, ResourceType};
+
+use crate::{client::Client, fetch_many, write_dataset, HarvestCounters, Harvester, Source};
+
+pub struct DwdCdc;
+
+impl Harvester for DwdCdc {
+    fn harvest<'a>(
+        &self,
+        dir: &'a Dir,
+        client: &'a Client,
+        source: &'a Source,
+        _since: Option<SystemTime>,
+    ) -> BoxFuture<'a, Result<(usize, HarvestCounters)>> {
+        harvest(dir, client, source).boxed()
+    }
+}
+
+pub async fn harvest(
+    dir: &Dir,
+    client: &Client,
+    source: &Source,
+) -> Result<(usize, HarvestCounters)> {
+    let mut directories = Vec::new();
+    let mut pending = vec![source.url.clone()];
+    let mut listing_index = 0;
+
+    while let Some(url) = pending.pop() {
+        let key = format!("{}-listing-{}", source.name, listing_index);
+        listing_index += 1;
+
+        let (subdirectories, files) = fetch_listing(client, &key, &url).await?;
+
+        pending.extend(subdirectories);
+
+        if !files.is_empty() {
+            directories.push((url, files));
+        }
+    }
+
+    let count = directories.len();
+    tracing::info!("Harvesting {} directories", count);
+
+    let counters = fetch_many(
+        source,
+        HarvestCounters::default(),
+        directories.into_iter().enumerate(),
+        |(index, (url, files))| translate_directory(dir, client, source, index, url, files),
+    )
+    .await;
+
+    Ok((count, counters))
+}
+
+#[tracing::instrument(skip(client))]
+async fn fetch_listing(client: &Client, key: &str, url: &Url) -> Result<(Vec<Url>, Vec<String>)> {
+    let body: String = client
+        .make_request(key, |client| client.get(url.clone()))
+        .await?;
+
+    let document = Html::parse_document(&body);
+
+    let mut subdirectories = Vec::new();
+    let mut files = Vec::new();
+
+    for link in document.select(&LINK_SELECTOR) {
+        let href = link.value().attr("href").unwrap();
+
+        let link = match url.join(href) {
+            Ok(link) => link,
+            Err(err) => {
+                tracing::debug!("Skipping invalid link {}: {}", href, err);
+                continue;
+            }
+        };
+
+        // Skip parent directories, sorting parameters and links leaving the subtree.
+        if link.query().is_some() || link == *url || !link.as_str().starts_with(url.as_str()) {
+            continue;
+        }
+
+        if link.path().ends_with('/') {
+            subdirectories.push(link);
+        } else if let Some(file) = link
+            .path_segments()
+            .and_then(|mut segments| segments.next_back())
+        {
+            files.push(file.to_owned());
+        }
+    }
+
+    Ok((subdirectories, files))
+}
+
+#[tracing::instrument(skip(dir, client, source, files))]
+async fn translate_directory(
+    dir: &Dir,
+    client: &Client,
+    source: &Source,
+    index: usize,
+    url: Url,
+    files: Vec<String>,
+) -> Result<(usize, HarvestCounters)> {
+    let mut groups = HashMap::<u32, Vec<&str>>::new();
+    let mut descriptions = Vec::new();
+    let mut station_list = None;
+
+    for file in &files {
+        if let Some(station_id) = station_id(file) {
+            groups.entry(station_id).or_default().push(file);
+        } else if file.contains("Beschreibung_Stationen") && file.ends_with(".txt") {
+            station_list = Some(file);
+        } else if file.ends_with(".pdf") {
+            descriptions.push(Resource {
+                r#type: ResourceType::Pdf,
+                url: url.join(file)?.into(),
+                title: None,
+                description: None,
+                license: None,
+                content_type: None,
+                format: None,
+                content_length: None,
+                checksum: None,
+                link: None,
+            });
+        }
+    }
+
+    let stations = match station_list {
+        Some(station_list) => {
+            let station_list = url.join(station_list)?;
+
+            let body = client
+                .make_request(&format!("{}-stations-{}", source.name, index), |client| {
+                    client.get(station_list.clone())
+                })
+                .await?;
+
+            parse_stations(&decode(&body))
+        }
+        None => HashMap::new(),
+    };
+
+    let path = url.path().trim_matches('/');
+    let path = path
+        .strip_prefix("climate_environment/CDC/")
+        .unwrap_or(path);
+
+    let prefix = source
+        .url
+        .make_relative(&url)
+        .unwrap_or_default()
+        .trim_matches('/')
+        .replace('/', "-");
+
+    let count = groups.len();
+    let mut counters = HarvestCounters::fetched(count);
+
+    for (station_id, files) in groups {
+        let station = stations.get(&station_id);
+
+        let id = if prefix.is_empty() {
+            format!("{:05}", station_id)
+        } else {
+            format!("{}-{:05}", prefix, station_id)
+        };
+
+        let title = match station {
+            Some(station) => format!("{} ({:05}): {}", station.name, station_id, path),
+            None => format!("Station {:05}: {}", station_id, path),
+        };
+
+        let mut resources = files
+            .into_iter()
+            .map(|file| {
+                Ok(Resource {
+                    r#type: file
+                        .rsplit_once('.')
+                        .map_or(ResourceType::Unknown, |(_, extension)| extension.into()),
+                    url: url.join(file)?.into(),
+                    title: None,
+                    description: None,
+                    license: None,
+                    content_type: None,
+                    format: None,
+                    content_length: None,
+                    checksum: None,
+                    link: None,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        resources.extend(descriptions.iter().map(|description| Resource {
+            r#type: ResourceType::Pdf,
+            url: description.url.clone(),
+            title: None,
+            description: None,
+            license: None,
+            content_type: None,
+            format: None,
+            content_length: None,
+            checksum: None,
+            link: None,
+        }));
+
+        let dataset = Dataset {
+            title,
+            description: station.map(Station::description),
+            summary: None,
+            comment: None,
+            provenance: source.provenance.clone(),
+            publisher: None,
+            license: License::CcBy40,
+            legal_basis: Vec::new(),
+            reporting_obligations: Vec::new(),
+            contacts: Vec::new(),
+            tags: Vec::new(),
+            themes: Vec::new(),
+            region: station.map(|station| station.state.clone()),
+            language: None,
+            bbox: None,
+            time_range: None,
+            issued: None,
+            modified: None,
+            update_frequency: None,
+            last_checked: None,
+            first_seen: None,
+            identifiers: Default::default(),
+            relations: Vec::new(),
+            source_url: url.clone().into(),
+            resources: resources.into(),
+        };
+
+        counters.record(write_dataset(dir, source, &id, dataset).await);
+    }
+
+    Ok((count, counters))
+}
+
+/// Extracts the five-digit station ID from names like `tageswerte_KL_00001_19370101_19860630_hist.zip`.
+fn station_id(file: &str) -> Option<u32> {
+    file.split(['_', '.'])
+        .find(|token| token.len() == 5 && token.bytes().all(|byte| byte.is_ascii_digit()))
+        .and_then(|token| token.parse().ok())
+}
+
+/// Station lists were historically encoded as ISO 8859-1 which maps each byte to the code point of the same value.
+fn decode(buf: &Bytes) -> Cow<str> {
+    match from_utf8(buf) {
+        Ok(text) => Cow::Borrowed(text),
+        Err(_) => Cow::Owned(buf.iter().map(|&byte| byte as char).collect()),
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Station {
+    name: String,
+    state: String,
+    height: String,
+    latitude: String,
+    longitude: String,
+    from: String,
+    to: String,
+}
+
+impl Station {
+    fn description(&self) -> String {
+        format!(
+            "Messstation {} ({}) auf {} m Höhe bei {}° N und {}° O mit Daten vom {} bis zum {}.",
+            self.name,
+            self.state,
+            self.height,
+            self.latitude,
+            self.longitude,
+            format_date(&self.from),
+            format_date(&self.to),
+        )
+    }
+}
+
+fn format_date(val: &str) -> Cow<str> {
+    match Date::parse(val, format_description!("[year][month][day]")) {
+        Ok(date) => Cow::Owned(
+            date.format(format_description!("[day].[month].[year]"))
+                .unwrap(),
+        ),
+        Err(_) => Cow::Borrowed(val),
+    }
+}
+
+/// Parses station lists like `KL_Tageswerte_Beschreibung_Stationen.txt`.
+///
+/// The station name is the only column which can contain whitespace, so the columns
+/// following it in the header are taken from the end of each line.
+fn parse_stations(text: &str) -> HashMap<u32, Station> {
+    let mut lines = text.lines();
+
+    let trailing = match lines.next() {
+        Some(header) => header
+            .split_whitespace()
+            .skip_while(|column| *column != "Stationsname")
+            .count()
+            .saturating_sub(1),
+        None => return HashMap::new(),
+    };
+
+    let mut stations = HashMap::new();
+
+    for line in lines {
+        let columns = line.split_whitespace().collect::<Vec<_>>();
+
+        if columns.len() < 7 + trailing || columns[0].starts_with('-') {
+            continue;
+        }
+
+        let id = match columns[0].parse() {
+            Ok(id) => id,
+            Err(_) => continue,
+        };
+
+        let name = columns[6..columns.len() - trailing].join(" ");
+        let state = columns
+            .get(columns.len() - trailing)
+            .copied()
+            .unwrap_or_default();
+
+        stations.insert(
+            id,
+            Station {
+                name,
+                state: state.to_owned(),
+                height: columns[3].to_owned(),
+                latitude: columns[4].to_owned(),
+                longitude: columns[5].to_owned(),
+                from: columns[1].to_owned(),
+                to: columns[2].to_owned(),
+            },
+        );
+    }
+
+    stations
+}
+
+static LINK_SELECTOR: Lazy<Selector> = Lazy::new(|| Selector::parse("a[href]").unwrap());
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn station_ids_are_extracted_from_file_names() {
+        assert_eq!(
+            station_id("tageswerte_KL_00001_19370101_19860630_hist.zip"),
+            Some(1)
+        );
+        assert_eq!(station_id("Metadaten_Geographie_04928.txt"), Some(4928));
+        assert_eq!(
+            station_id("DESCRIPTION_obsgermany_climate_daily_kl_historical_en.pdf"),
+            None
+        );
+    }
+
+    #[test]
+    fn station_lists_are_parsed() {
+        let text = "\
+Stations_id von_datum bis_datum Stationshoehe geoBreite geoLaenge Stationsname Bundesland Abgabe
+----------- --------- --------- ------------- --------- --------- ----------------------------------------- ---------- ------
+00001 19370101 19860630            478     47.8413    8.8493 Aach                                     Baden-W\u{fc}rttemberg                                                                                  Frei
+00003 18910101 20110331            202     50.7827    6.0941 Aachen                                   Nordrhein-Westfalen                                                                                Frei
+00044 19690101 20240101             44     52.9336    8.2370 Gro\u{df}enkneten                                   Niedersachsen                                                                                      Frei
+00052 19690101 20010101             46     53.6623   10.1990 Ahrensburg-Wulfsdorf                     Schleswig-Holstein                                                                                 Frei
+00073 19530101 20240101            340     48.6159   13.0506 Aldersbach-Kriestorf                     Bayern                                                                                             Frei
+00131 20040101 20231231            306     51.0881   12.9326 Bad Lausick                              Sachsen                                                                                            Frei
+";
+
+        let stations = parse_stations(text);
+
+        assert_eq!(stations.len(), 6);
+        assert_eq!(
+            stations[&131],
+            Station {
+                name: "Bad Lausick".to_owned(),
+                state: "Sachsen".to_owned(),
+                height: "306".to_owned(),
+                latitude: "51.0881".to_owned(),
+                longitude: "12.9326".to_owned(),
+                from: "20040101".to_owned(),
+                to: "20231231".to_owned(),
+            }
+        );
+        assert_eq!(stations[&1].state, "Baden-W\u{fc}rttemberg");
+        assert_eq!(
+            stations[&1].description(),
+            "Messstation Aach (Baden-W\u{fc}rttemberg) auf 478 m Höhe bei 47.8413° N und 8.8493° O mit Daten vom 01.01.1937 bis zum 30.06.1986."
+        );
+    }
+
+    #[test]
+    fn latin1_is_decoded() {
+        let buf = Bytes::from_static(b"Gro\xdfenkneten");
+
+        assert_eq!(decode(&buf), "Gro\u{df}enkneten");
+    }
+}
@@ -0,0 +1,348 @@
+//! Enrichment of harvested datasets applied uniformly when they are written
+//!
+//! Summaries, licenses and languages are derived for all sources while the remaining enrichers
+//! are enabled per source, e.g. `enrich = ["tags", "regions"]`.
+//!
+//! Reporting obligations are assigned for all sources based on the table configured as `reporting_obligations`.
+use serde::Deserialize;
+
+use umwelt_info_core::dataset::{Dataset, Language, ReportingObligation, Tag};
+
+pub trait Enricher {
+    fn enrich(&self, dataset: &mut Dataset);
+}
+
+/// Enrichers which can be enabled per source
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Enrichment {
+    /// Trims tags, maps them onto known terms and removes duplicates.
+    Tags,
+    /// Derives the region from a German state named in the title.
+    Regions,
+}
+
+impl Enrichment {
+    fn enricher(self) -> &'static dyn Enricher {
+        match self {
+            Self::Tags => &TagCleanup,
+            Self::Regions => &RegionMatching,
+        }
+    }
+}
+
+/// Applies the enrichers used for all sources followed by those enabled for the source.
+pub fn enrich(enrichments: &[Enrichment], dataset: &mut Dataset) {
+    let enrichers = [
+        &Summary as &dyn Enricher,
+        &LicenseFromResources,
+        &LanguageDetection,
+    ]
+    .into_iter()
+    .chain(enrichments.iter().map(|enrichment| enrichment.enricher()));
+
+    for enricher in enrichers {
+        enricher.enrich(dataset);
+    }
+}
+
+/// Assigns an entry of the Reporting Obligations Database to all datasets carrying any of the given tags
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReportingObligationMapping {
+    #[serde(flatten)]
+    obligation: ReportingObligation,
+    /// Tags compared case-insensitively, where e.g. `Wasserrahmenrichtlinie` also matches the known tag `WRRL`
+    tags: Vec<String>,
+}
+
+impl Enricher for [ReportingObligationMapping] {
+    fn enrich(&self, dataset: &mut Dataset) {
+        for mapping in self {
+            let mut matches = false;
+
+            for tag in &dataset.tags {
+                tag.with_tokens(|tokens| {
+                    matches |= tokens.iter().any(|token| {
+                        mapping
+                            .tags
+                            .iter()
+                            .any(|val| val.to_lowercase() == token.to_lowercase())
+                    });
+                });
+            }
+
+            if matches && !dataset.reporting_obligations.contains(&mapping.obligation) {
+                dataset
+                    .reporting_obligations
+                    .push(mapping.obligation.clone());
+            }
+        }
+    }
+}
+
+struct Summary;
+
+impl Enricher for Summary {
+    fn enrich(&self, dataset: &mut Dataset) {
+        dataset.summarize();
+    }
+}
+
+struct LicenseFromResources;
+
+impl Enricher for LicenseFromResources {
+    fn enrich(&self, dataset: &mut Dataset) {
+        dataset.summarize_license();
+    }
+}
+
+/// Detects the language of title and description if the source does not specify it.
+///
+/// Counts frequent function words which suffices to tell German and English texts apart
+/// and yields nothing if neither language clearly dominates.
+struct LanguageDetection;
+
+impl Enricher for LanguageDetection {
+    fn enrich(&self, dataset: &mut Dataset) {
+        const GERMAN: &[&str] = &[
+            "der", "die", "das", "und", "ist", "von", "mit", "für", "den", "des", "dem", "im",
+            "auf", "zu", "zur", "zum", "ein", "eine", "einer", "nicht", "werden", "wird", "sind",
+            "sich", "bei", "aus", "nach", "über", "als", "auch", "oder",
+        ];
+
+        const ENGLISH: &[&str] = &[
+            "the", "and", "of", "to", "is", "for", "with", "from", "on", "are", "this", "that",
+            "by", "be", "as", "at", "which", "or", "not", "was", "were", "its",
+        ];
+
+        if dataset.language.is_some() {
+            return;
+        }
+
+        let text = format!(
+            "{} {}",
+            dataset.title,
+            dataset.description.as_deref().unwrap_or_default()
+        )
+        .to_lowercase();
+
+        let mut german = 0;
+        let mut english = 0;
+
+        for word in text.split(|char: char| !char.is_alphabetic()) {
+            if GERMAN.contains(&word) {
+                german += 1;
+            } else if ENGLISH.contains(&word) {
+                english += 1;
+            }
+        }
+
+        dataset.language = if german >= 2 && german > 2 * english {
+            Some(Language::German)
+        } else if english >= 2 && english > 2 * german {
+            Some(Language::English)
+        } else {
+            None
+        };
+    }
+}
+
+struct TagCleanup;
+
+impl Enricher for TagCleanup {
+    fn enrich(&self, dataset: &mut Dataset) {
+        const KNOWN: &[Tag] = &[Tag::Wrrl, Tag::HwrmRl, Tag::MsrRl, Tag::BgRl];
+
+        let mut tags = Vec::with_capacity(dataset.tags.len());
+
+        for tag in dataset.tags.drain(..) {
+            let tag = match tag {
+                Tag::Other(val) => {
+                    let val = val.split_whitespace().collect::<Vec<_>>().join(" ");
+
+                    if val.is_empty() {
+                        continue;
+                    }
+
+                    let mut known = None;
+
+                    for tag in KNOWN {
+                        tag.with_tokens(|tokens| {
+                            if tokens.iter().any(|token| token.eq_ignore_ascii_case(&val)) {
+                                known = Some(tag.clone());
+                            }
+                        });
+                    }
+
+                    known.unwrap_or(Tag::Other(val))
+                }
+                tag => tag,
+            };
+
+            if !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+
+        dataset.tags = tags;
+    }
+}
+
+struct RegionMatching;
+
+impl Enricher for RegionMatching {
+    fn enrich(&self, dataset: &mut Dataset) {
+        const STATES: &[&str] = &[
+            "Baden-Württemberg",
+            "Bayern",
+            "Berlin",
+            "Brandenburg",
+            "Bremen",
+            "Hamburg",
+            "Hessen",
+            "Mecklenburg-Vorpommern",
+            "Niedersachsen",
+            "Nordrhein-Westfalen",
+            "Rheinland-Pfalz",
+            "Saarland",
+            "Sachsen-Anhalt",
+            "Sachsen",
+            "Schleswig-Holstein",
+            "Thüringen",
+        ];
+
+        if dataset.region.is_some() {
+            return;
+        }
+
+        // Compare whole words so that e.g. "Sachsen" does not match "Niedersachsen".
+        let words = dataset
+            .title
+            .split(|char: char| !char.is_alphanumeric() && char != '-')
+            .collect::<Vec<_>>();
+
+        dataset.region = STATES
+            .iter()
+            .find(|state| words.contains(state))
+            .map(|state| (*state).to_owned());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use umwelt_info_core::dataset::License;
+
+    fn dataset(title: &str, tags: Vec<Tag>) -> Dataset {
+        Dataset {
+            title: title.to_owned(),
+            description: None,
+            summary: None,
+            comment: None,
+            provenance: Default::default(),
+            publisher: None,
+            license: License::Unknown,
+            legal_basis: Vec::new(),
+            reporting_obligations: Vec::new(),
+            contacts: Vec::new(),
+            tags,
+            themes: Vec::new(),
+            region: None,
+            language: None,
+            bbox: None,
+            time_range: None,
+            issued: None,
+            modified: None,
+            update_frequency: None,
+            last_checked: None,
+            first_seen: None,
+            identifiers: Default::default(),
+            relations: Vec::new(),
+            source_url: String::new(),
+            resources: Default::default(),
+        }
+    }
+
+    #[test]
+    fn tags_are_cleaned_up() {
+        let mut dataset = dataset(
+            "Messwerte",
+            vec![
+                " Grund  wasser ".into(),
+                "".into(),
+                "wasserrahmenrichtlinie".into(),
+                Tag::Wrrl,
+                "Grund wasser".into(),
+            ],
+        );
+
+        enrich(&[Enrichment::Tags], &mut dataset);
+
+        assert_eq!(dataset.tags, [Tag::from("Grund wasser"), Tag::Wrrl]);
+    }
+
+    #[test]
+    fn regions_are_matched() {
+        let mut dataset1 = dataset("Pegelstände in Niedersachsen", Vec::new());
+        enrich(&[Enrichment::Regions], &mut dataset1);
+        assert_eq!(dataset1.region.as_deref(), Some("Niedersachsen"));
+
+        let mut dataset2 = dataset("Pegelstände Sachsen-Anhalt 2020", Vec::new());
+        enrich(&[Enrichment::Regions], &mut dataset2);
+        assert_eq!(dataset2.region.as_deref(), Some("Sachsen-Anhalt"));
+
+        let mut dataset3 = dataset("Pegelstände", Vec::new());
+        enrich(&[Enrichment::Regions], &mut dataset3);
+        assert_eq!(dataset3.region, None);
+    }
+
+    #[test]
+    fn reporting_obligations_are_assigned() {
+        let mappings = [ReportingObligationMapping {
+            obligation: ReportingObligation {
+                id: 1,
+                title: "Bewirtschaftungspläne".to_owned(),
+            },
+            tags: vec!["Wasserrahmenrichtlinie".to_owned()],
+        }];
+
+        let mut dataset1 = dataset("Messwerte", vec![Tag::Wrrl]);
+        mappings.enrich(&mut dataset1);
+        mappings.enrich(&mut dataset1);
+        assert_eq!(
+            dataset1.reporting_obligations,
+            [mappings[0].obligation.clone()]
+        );
+
+        let mut dataset2 = dataset("Messwerte", vec!["wasserrahmenrichtlinie".into()]);
+        mappings.enrich(&mut dataset2);
+        assert_eq!(
+            dataset2.reporting_obligations,
+            [mappings[0].obligation.clone()]
+        );
+
+        let mut dataset3 = dataset("Messwerte", vec!["Grundwasser".into()]);
+        mappings.enrich(&mut dataset3);
+        assert_eq!(dataset3.reporting_obligations, []);
+    }
+
+    #[test]
+    fn languages_are_detected() {
+        let mut dataset1 = dataset("Messwerte", Vec::new());
+        dataset1.description =
+            Some("Die Messwerte der Pegel an der Elbe und ihren Nebenflüssen.".to_owned());
+        enrich(&[], &mut dataset1);
+        assert_eq!(dataset1.language, Some(Language::German));
+
+        let mut dataset2 = dataset("Water levels", Vec::new());
+        dataset2.description =
+            Some("Measurements of the water levels at the gauges of the river Elbe.".to_owned());
+        enrich(&[], &mut dataset2);
+        assert_eq!(dataset2.language, Some(Language::English));
+
+        let mut dataset3 = dataset("PM2,5", Vec::new());
+        enrich(&[], &mut dataset3);
+        assert_eq!(dataset3.language, None);
+    }
+}
@@ -0,0 +1,171 @@
+//! Declarative filters selecting the datasets of a source, configured via a `[sources.filters]` table
+//!
+//! Unlike the `filter` option which is interpreted by some harvesters when fetching records,
+//! these filters apply to the translated datasets of all types of sources.
+use regex::Regex;
+use serde::{
+    de::{Deserializer, Error},
+    Deserialize,
+};
+
+use umwelt_info_core::dataset::Dataset;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Filters {
+    /// Only datasets whose title or one of whose tags matches this expression are included.
+    #[serde(deserialize_with = "deserialize_regex")]
+    include: Option<Regex>,
+    /// Datasets whose title or one of whose tags matches this expression are excluded.
+    #[serde(deserialize_with = "deserialize_regex")]
+    exclude: Option<Regex>,
+    /// Only datasets available under a license below one of these facets, e.g. `/open/cc`, are included.
+    licenses: Vec<String>,
+    /// Only datasets carrying one of these tags, compared case-insensitively, are included.
+    tags: Vec<String>,
+}
+
+impl Filters {
+    /// Yields whether the dataset passes all configured filters.
+    pub fn matches(&self, dataset: &Dataset) -> bool {
+        let matches_text = |regex: &Regex| {
+            regex.is_match(&dataset.title)
+                || dataset
+                    .tags
+                    .iter()
+                    .any(|tag| regex.is_match(&tag.to_string()))
+        };
+
+        if let Some(include) = &self.include {
+            if !matches_text(include) {
+                return false;
+            }
+        }
+
+        if let Some(exclude) = &self.exclude {
+            if matches_text(exclude) {
+                return false;
+            }
+        }
+
+        if !self.licenses.is_empty() {
+            let licenses = dataset.licenses();
+
+            let allowed = licenses.iter().any(|license| {
+                let facet = license.facet();
+
+                self.licenses.iter().any(|allowed| {
+                    let allowed = allowed.split('/').filter(|component| !component.is_empty());
+
+                    facet.len() >= allowed.clone().count()
+                        && allowed.zip(&facet).all(|(lhs, rhs)| lhs == rhs)
+                })
+            });
+
+            if !allowed {
+                return false;
+            }
+        }
+
+        if !self.tags.is_empty() {
+            let tagged = dataset.tags.iter().any(|tag| {
+                let tag = tag.to_string().to_lowercase();
+
+                self.tags
+                    .iter()
+                    .any(|allowed| allowed.to_lowercase() == tag)
+            });
+
+            if !tagged {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+fn deserialize_regex<'de, D>(deserializer: D) -> Result<Option<Regex>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let val = String::deserialize(deserializer)?;
+
+    Regex::new(&val).map(Some).map_err(D::Error::custom)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use toml::from_str;
+
+    use umwelt_info_core::dataset::{License, Tag};
+
+    fn dataset(title: &str, license: License, tags: Vec<Tag>) -> Dataset {
+        Dataset {
+            title: title.to_owned(),
+            description: None,
+            summary: None,
+            comment: None,
+            provenance: Default::default(),
+            publisher: None,
+            license,
+            legal_basis: Vec::new(),
+            reporting_obligations: Vec::new(),
+            contacts: Vec::new(),
+            tags,
+            themes: Vec::new(),
+            region: None,
+            language: None,
+            bbox: None,
+            time_range: None,
+            issued: None,
+            modified: None,
+            update_frequency: None,
+            last_checked: None,
+            first_seen: None,
+            identifiers: Default::default(),
+            relations: Vec::new(),
+            source_url: String::new(),
+            resources: Default::default(),
+        }
+    }
+
+    #[test]
+    fn filters_are_combined() {
+        let filters = from_str::<Filters>(
+            r#"
+include = "(?i)wasser"
+exclude = "Bebauungsplan"
+licenses = ["/open/cc"]
+"#,
+        )
+        .unwrap();
+
+        assert!(filters.matches(&dataset("Grundwasser", License::CcBy40, Vec::new())));
+        assert!(filters.matches(&dataset(
+            "Pegel",
+            License::CcBy40,
+            vec!["Oberflächenwasser".into()]
+        )));
+
+        assert!(!filters.matches(&dataset("Luftqualität", License::CcBy40, Vec::new())));
+        assert!(!filters.matches(&dataset(
+            "Bebauungsplan Wasserstraße",
+            License::CcBy40,
+            Vec::new()
+        )));
+        assert!(!filters.matches(&dataset("Grundwasser", License::DlDeBy20, Vec::new())));
+    }
+
+    #[test]
+    fn tags_are_compared_case_insensitively() {
+        let filters = from_str::<Filters>(r#"tags = ["wrrl"]"#).unwrap();
+
+        assert!(filters.matches(&dataset("Messwerte", License::Unknown, vec![Tag::Wrrl])));
+        assert!(!filters.matches(&dataset("Messwerte", License::Unknown, Vec::new())));
+
+        assert!(Filters::default().matches(&dataset("Messwerte", License::Unknown, Vec::new())));
+    }
+}
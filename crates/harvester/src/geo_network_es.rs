@@ -0,0 +1,151 @@
+//! This harvester pages through the Elasticsearch-based JSON search API of GeoNetwork 4
+//! and fetches the ISO 19139 document of each record to reuse the translation of the CSW harvester.
+//!
+//! The `url` of the source is expected to point to the API root, e.g. `https://example.com/geonetwork/srv/api/`,
+//! and the optional `filter` restricts the records to the given topic category like the `q` service does.
+use std::time::SystemTime;
+
+use anyhow::Result;
+use bytes::Bytes;
+use cap_std::fs::Dir;
+use futures_util::future::{BoxFuture, FutureExt};
+use reqwest::header::ACCEPT;
+use serde::Deserialize;
+use serde_json::{from_slice, json};
+use serde_roxmltree::{from_doc, roxmltree::Document};
+
+use crate::{
+    client::Client, csw, fetch_remaining_pages, HarvestCounters, Harvester, Outcome, Source,
+};
+
+pub struct GeoNetworkEs;
+
+impl Harvester for GeoNetworkEs {
+    fn harvest<'a>(
+        &self,
+        dir: &'a Dir,
+        client: &'a Client,
+        source: &'a Source,
+        _since: Option<SystemTime>,
+    ) -> BoxFuture<'a, Result<(usize, HarvestCounters)>> {
+        harvest(dir, client, source).boxed()
+    }
+}
+
+pub async fn harvest(
+    dir: &Dir,
+    client: &Client,
+    source: &Source,
+) -> Result<(usize, HarvestCounters)> {
+    let size = source.batch_size;
+
+    let (count, counters) = fetch_datasets(dir, client, source, 0, size).await?;
+
+    let counters = fetch_remaining_pages(source, count, counters, |from, size| {
+        fetch_datasets(dir, client, source, from, size)
+    })
+    .await;
+
+    Ok((count, counters))
+}
+
+#[tracing::instrument(skip(dir, client, source))]
+async fn fetch_datasets(
+    dir: &Dir,
+    client: &Client,
+    source: &Source,
+    from: usize,
+    size: usize,
+) -> Result<(usize, HarvestCounters)> {
+    tracing::debug!("Fetching {} datasets starting at {}", size, from);
+
+    let url = source.url.join("search/records/_search")?;
+
+    let mut filter = vec![json!({ "term": { "isTemplate": "n" } })];
+
+    if let Some(topic) = &source.filter {
+        filter.push(json!({ "term": { "cl_topic.key": topic } }));
+    }
+
+    let query = json!({
+        "from": from,
+        "size": size,
+        "track_total_hits": true,
+        "sort": [{ "uuid": "asc" }],
+        "_source": ["uuid"],
+        "query": { "bool": { "filter": filter } },
+    });
+
+    let body: Bytes = client
+        .make_request(&format!("{}-{}", source.name, from), |client| {
+            client
+                .post(url.clone())
+                .header(ACCEPT, "application/json")
+                .json(&query)
+        })
+        .await?;
+
+    let response = from_slice::<SearchResponse>(&body)?;
+
+    let count = response.hits.total.value;
+    let mut counters = HarvestCounters::fetched(response.hits.hits.len());
+
+    for hit in response.hits.hits {
+        counters.record(fetch_dataset(dir, client, source, &hit.source.uuid).await);
+    }
+
+    Ok((count, counters))
+}
+
+async fn fetch_dataset(dir: &Dir, client: &Client, source: &Source, uuid: &str) -> Result<Outcome> {
+    tracing::debug!("Fetching dataset {}", uuid);
+
+    let url = source
+        .url
+        .join(&format!("records/{}/formatters/xml", uuid))?;
+
+    let key = format!("{}-record-{}", source.name, uuid);
+
+    let body: String = client
+        .make_request(&key, |client| {
+            client.get(url.clone()).header(ACCEPT, "application/xml")
+        })
+        .await?;
+
+    client.quarantine(&key, translate_record(dir, source, &body).await)
+}
+
+async fn translate_record(dir: &Dir, source: &Source, body: &str) -> Result<Outcome> {
+    let document = Document::parse(body)?;
+
+    let record = from_doc::<csw::Record>(&document)?;
+
+    csw::translate_dataset(dir, source, record).await
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchResponse {
+    hits: Hits,
+}
+
+#[derive(Debug, Deserialize)]
+struct Hits {
+    total: Total,
+    hits: Vec<Hit>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Total {
+    value: usize,
+}
+
+#[derive(Debug, Deserialize)]
+struct Hit {
+    #[serde(rename = "_source")]
+    source: HitSource,
+}
+
+#[derive(Debug, Deserialize)]
+struct HitSource {
+    uuid: String,
+}
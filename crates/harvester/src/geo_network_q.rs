@@ -0,0 +1,102 @@
+use std::time::SystemTime;
+
+use anyhow::Result;
+use cap_std::fs::Dir;
+use futures_util::future::{BoxFuture, FutureExt};
+use serde::{Deserialize, Serialize};
+use serde_roxmltree::{from_doc, roxmltree::Document};
+
+use crate::{client::Client, csw, fetch_remaining_pages, HarvestCounters, Harvester, Source};
+
+pub struct GeoNetworkQ;
+
+impl Harvester for GeoNetworkQ {
+    fn harvest<'a>(
+        &self,
+        dir: &'a Dir,
+        client: &'a Client,
+        source: &'a Source,
+        _since: Option<SystemTime>,
+    ) -> BoxFuture<'a, Result<(usize, HarvestCounters)>> {
+        harvest(dir, client, source).boxed()
+    }
+}
+
+pub async fn harvest(
+    dir: &Dir,
+    client: &Client,
+    source: &Source,
+) -> Result<(usize, HarvestCounters)> {
+    let records = source.batch_size;
+
+    let (count, counters) = fetch_datasets(dir, client, source, true, 1, records).await?;
+    // Positions of records are one-based and ranges are inclusive.
+    let counters = fetch_remaining_pages(source, count, counters, |offset, records| {
+        fetch_datasets(dir, client, source, false, 1 + offset, offset + records)
+    })
+    .await;
+
+    Ok((count, counters))
+}
+
+#[tracing::instrument(skip(dir, client, source))]
+async fn fetch_datasets(
+    dir: &Dir,
+    client: &Client,
+    source: &Source,
+    summary: bool,
+    from: usize,
+    to: usize,
+) -> Result<(usize, HarvestCounters)> {
+    tracing::debug!("Fetching datasets from {} to {}", from, to);
+
+    let key = format!("{}-{}", source.name, from);
+
+    let body: String = client
+        .make_request(&key, |client| {
+            client.get(source.url.clone()).query(&SearchParams {
+                fast: false,
+                summary,
+                from,
+                to,
+                topic: source.filter.as_deref(),
+            })
+        })
+        .await?;
+
+    let document = Document::parse(&body)?;
+
+    let response = from_doc::<SearchResults>(&document)?;
+
+    let count = response.summary.map_or(0, |summary| summary.count);
+    let mut counters = HarvestCounters::fetched(response.records.len());
+
+    for record in response.records {
+        counters.record(client.quarantine(&key, csw::translate_dataset(dir, source, record).await));
+    }
+
+    Ok((count, counters))
+}
+
+#[derive(Debug, Serialize)]
+struct SearchParams<'a> {
+    fast: bool,
+    #[serde(rename = "buildSummary")]
+    summary: bool,
+    from: usize,
+    to: usize,
+    #[serde(rename = "topicCat", skip_serializing_if = "Option::is_none")]
+    topic: Option<&'a str>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchResults<'a> {
+    summary: Option<Summary>,
+    #[serde(rename = "MD_Metadata", borrow)]
+    records: Vec<csw::Record<'a>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Summary {
+    count: usize,
+}
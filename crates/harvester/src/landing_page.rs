@@ -0,0 +1,311 @@
+//! This module enriches already harvested datasets by fetching their landing pages, i.e. their `source_url`,
+//! and extracting standard metadata where the structured APIs of the source lack it.
+//!
+//! | Original field                                                       | Mapped field | Comment                       |
+//! | -------------------------------------------------------------------- | ------------ | ----------------------------- |
+//! | JSON-LD description, citation_abstract, og:description, description  | description  | First one present is used     |
+//! | JSON-LD datePublished, citation_publication_date, citation_date      | issued       | First one present is used     |
+//! | JSON-LD distribution, citation_pdf_url                               | resources    | contentUrl preferred over url |
+//!
+//! Fields already set by the harvester are never overwritten. It is enabled by setting `landing_pages = true` for a source.
+use anyhow::{anyhow, Result};
+use cap_std::fs::Dir;
+use futures_util::stream::{iter, StreamExt};
+use once_cell::sync::Lazy;
+use scraper::{Html, Selector};
+use serde_json::{from_str, Value};
+use url::Url;
+
+use umwelt_info_core::dataset::{Dataset, PartialDate, Resource, ResourceType};
+
+use crate::{
+    client::Client,
+    date::parse_date,
+    schema_org::{find_dataset, JSON_LD_SELECTOR},
+    Source,
+};
+
+pub async fn enrich(dir: &Dir, client: &Client, source: &Source) -> Result<()> {
+    if !source.landing_pages {
+        return Ok(());
+    }
+
+    let mut ids = Vec::new();
+
+    for entry in dir.entries()? {
+        let id = entry?
+            .file_name()
+            .into_string()
+            .map_err(|id| anyhow!("Invalid dataset ID {:?}", id))?;
+
+        ids.push(id);
+    }
+
+    tracing::info!("Checking landing pages of {} datasets", ids.len());
+
+    let enriched = iter(ids)
+        .map(|id| async move { enrich_dataset(dir, client, source, &id).await })
+        .buffer_unordered(source.concurrency)
+        .fold(0, |enriched, res| async move {
+            match res {
+                Ok(true) => enriched + 1,
+                Ok(false) => enriched,
+                Err(err) => {
+                    tracing::error!("{:#}", err);
+
+                    enriched
+                }
+            }
+        })
+        .await;
+
+    tracing::info!("Enriched {} datasets using their landing pages", enriched);
+
+    Ok(())
+}
+
+#[tracing::instrument(skip(dir, client, source))]
+async fn enrich_dataset(dir: &Dir, client: &Client, source: &Source, id: &str) -> Result<bool> {
+    let mut dataset = Dataset::read(dir.open(id)?)?;
+
+    let lacks_description = dataset
+        .description
+        .as_deref()
+        .map_or(true, |description| description.trim().is_empty());
+    let lacks_issued = dataset.issued.is_none();
+    let lacks_resources = dataset.resources.is_empty();
+
+    if !lacks_description && !lacks_issued && !lacks_resources {
+        return Ok(false);
+    }
+
+    // A landing page shared by all datasets does not describe any of them.
+    if dataset.source_url == source.source_url() || dataset.source_url == source.url.as_str() {
+        return Ok(false);
+    }
+
+    if !client
+        .allowed_by_robots(&Url::parse(&dataset.source_url)?)
+        .await
+    {
+        return Ok(false);
+    }
+
+    tracing::debug!("Fetching landing page at {}", dataset.source_url);
+
+    let body: String = client
+        .make_request(&format!("{}-landing-page-{}", source.name, id), |client| {
+            client.get(&dataset.source_url)
+        })
+        .await?;
+
+    let metadata = LandingPage::extract(&body);
+
+    let mut enriched = false;
+
+    if lacks_description && metadata.description.is_some() {
+        dataset.description = metadata.description;
+        enriched = true;
+    }
+
+    if lacks_issued && metadata.issued.is_some() {
+        dataset.issued = metadata.issued;
+        enriched = true;
+    }
+
+    if lacks_resources && !metadata.resources.is_empty() {
+        dataset.resources = metadata.resources.into_iter().collect();
+        enriched = true;
+    }
+
+    if enriched {
+        dataset.summarize();
+        dataset.write(dir.create(id)?).await?;
+    }
+
+    Ok(enriched)
+}
+
+#[derive(Debug, Default)]
+struct LandingPage {
+    description: Option<String>,
+    issued: Option<PartialDate>,
+    resources: Vec<Resource>,
+}
+
+impl LandingPage {
+    fn extract(body: &str) -> Self {
+        let document = Html::parse_document(body);
+
+        let meta = |names: &[&str]| {
+            names.iter().find_map(|name| {
+                document
+                    .select(&SELECTORS.meta)
+                    .filter(|element| {
+                        let element = element.value();
+
+                        element.attr("name").or_else(|| element.attr("property")) == Some(name)
+                    })
+                    .filter_map(|element| element.value().attr("content"))
+                    .map(str::trim)
+                    .find(|content| !content.is_empty())
+            })
+        };
+
+        let json_ld = document
+            .select(&JSON_LD_SELECTOR)
+            .filter_map(|element| from_str::<Value>(&element.text().collect::<String>()).ok())
+            .find_map(find_dataset);
+
+        let json_ld_str = |key: &str| {
+            json_ld
+                .as_ref()
+                .and_then(|dataset| dataset.get(key))
+                .and_then(Value::as_str)
+                .map(str::trim)
+                .filter(|val| !val.is_empty())
+        };
+
+        let description = json_ld_str("description")
+            .or_else(|| meta(&["citation_abstract", "og:description", "description"]))
+            .map(ToOwned::to_owned);
+
+        let issued = json_ld_str("datePublished")
+            .or_else(|| meta(&["citation_publication_date", "citation_date"]))
+            .and_then(parse_date);
+
+        let mut resources = Vec::new();
+
+        match json_ld
+            .as_ref()
+            .and_then(|dataset| dataset.get("distribution"))
+        {
+            Some(Value::Array(distributions)) => {
+                resources.extend(distributions.iter().filter_map(parse_distribution))
+            }
+            Some(distribution) => resources.extend(parse_distribution(distribution)),
+            None => (),
+        }
+
+        if let Some(url) = meta(&["citation_pdf_url"]) {
+            resources.push(Resource {
+                r#type: ResourceType::Pdf,
+                url: url.to_owned(),
+                title: None,
+                description: None,
+                license: None,
+                content_type: None,
+                format: None,
+                content_length: None,
+                checksum: None,
+                link: None,
+            });
+        }
+
+        Self {
+            description,
+            issued,
+            resources,
+        }
+    }
+}
+
+fn parse_distribution(distribution: &Value) -> Option<Resource> {
+    let url = distribution
+        .get("contentUrl")
+        .or_else(|| distribution.get("url"))?
+        .as_str()?
+        .to_owned();
+
+    let r#type = distribution
+        .get("encodingFormat")
+        .and_then(Value::as_str)
+        .map_or(ResourceType::Unknown, Into::into);
+
+    let text = |key| {
+        distribution
+            .get(key)
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|val| !val.is_empty())
+            .map(ToOwned::to_owned)
+    };
+
+    Some(Resource {
+        r#type,
+        url,
+        title: text("name"),
+        description: text("description"),
+        license: None,
+        content_type: None,
+        format: text("encodingFormat"),
+        content_length: None,
+        checksum: None,
+        link: None,
+    })
+}
+
+static SELECTORS: Lazy<Selectors> = Lazy::new(Selectors::default);
+
+struct Selectors {
+    meta: Selector,
+}
+
+impl Default for Selectors {
+    fn default() -> Self {
+        Self {
+            meta: Selector::parse("head > meta[content]").unwrap(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use time::{Date, Month};
+
+    #[test]
+    fn extract_citation_and_open_graph() {
+        let metadata = LandingPage::extract(
+            r#"<html><head>
+                <meta property="og:description" content="Open Graph">
+                <meta name="citation_abstract" content=" Abstract ">
+                <meta name="citation_publication_date" content="2021/03/04">
+                <meta name="citation_pdf_url" content="https://example.com/report.pdf">
+            </head><body></body></html>"#,
+        );
+
+        assert_eq!(metadata.description.as_deref(), Some("Abstract"));
+        assert_eq!(
+            metadata.issued,
+            Some(PartialDate::Full(
+                Date::from_calendar_date(2021, Month::March, 4).unwrap()
+            ))
+        );
+        assert_eq!(metadata.resources.len(), 1);
+        assert_eq!(metadata.resources[0].url, "https://example.com/report.pdf");
+    }
+
+    #[test]
+    fn extract_json_ld_first() {
+        let metadata = LandingPage::extract(
+            r#"<html><head>
+                <meta name="description" content="Generic">
+                <script type="application/ld+json">
+                    {"@context":"https://schema.org","@graph":[{"@type":"Dataset","name":"Foo","description":"Specific","datePublished":"2020-01-02T03:04:05Z","distribution":{"contentUrl":"https://example.com/data.csv","encodingFormat":"text/csv"}}]}
+                </script>
+            </head><body></body></html>"#,
+        );
+
+        assert_eq!(metadata.description.as_deref(), Some("Specific"));
+        assert_eq!(
+            metadata.issued,
+            Some(PartialDate::Full(
+                Date::from_calendar_date(2020, Month::January, 2).unwrap()
+            ))
+        );
+        assert_eq!(metadata.resources.len(), 1);
+        assert_eq!(metadata.resources[0].url, "https://example.com/data.csv");
+    }
+}
@@ -0,0 +1,904 @@
+//! Harvesting of datasets from the configured sources into the data directory.
+pub mod bfn;
+pub mod ckan;
+pub mod client;
+pub mod csw;
+pub mod date;
+pub mod doris_bfs;
+pub mod dwd_cdc;
+pub mod enrichment;
+pub mod filters;
+pub mod geo_network_es;
+pub mod geo_network_q;
+pub mod landing_page;
+pub mod local_dir;
+pub mod resource_probe;
+pub mod schedule;
+pub mod schema_org;
+pub mod smart_finder;
+pub mod validation;
+pub mod wasser_de;
+
+use std::env::var;
+use std::fmt;
+use std::future::Future;
+use std::io::{ErrorKind, Read};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+use anyhow::{ensure, Result};
+use cap_std::fs::Dir;
+use futures_util::{
+    future::BoxFuture,
+    stream::{FuturesUnordered, StreamExt},
+};
+use hashbrown::{HashMap, HashSet};
+use serde::{
+    de::{value::Error as ValueError, Deserializer, IntoDeserializer},
+    Deserialize,
+};
+use string_cache::DefaultAtom;
+use time::{macros::format_description, OffsetDateTime};
+use toml::from_str;
+use url::Url;
+
+use umwelt_info_core::{
+    dataset::{Dataset, PartialDate},
+    encode_id,
+    metrics::{HarvestCounters, Outcome},
+    provenance::provenance_path,
+    sources::{default_trust, Source as SourceInfo, Sources, Visibility},
+    validate_name,
+};
+
+use crate::enrichment::Enricher;
+
+async fn write_dataset(
+    dir: &Dir,
+    source: &Source,
+    id: &str,
+    mut dataset: Dataset,
+) -> Result<Outcome> {
+    let name = encode_id(id)?;
+
+    enrichment::enrich(&source.enrich, &mut dataset);
+    source.reporting_obligations.enrich(&mut dataset);
+
+    if !source.filters.matches(&dataset) {
+        return Ok(Outcome::Filtered);
+    }
+
+    let violations = source.validation.violations(&dataset);
+    let valid = violations.is_empty();
+
+    // In report mode, the violations are collected after the harvest instead.
+    if !valid && !source.validation.report() {
+        tracing::warn!(
+            "Dataset {id} violates the validation rules {}",
+            violations.join(", ")
+        );
+
+        if source.validation.skip_invalid() {
+            return Ok(Outcome::Invalid);
+        }
+    }
+
+    // Merge rather than overwrite records which a source yields more than once, e.g. on multiple pages.
+    // The file is read and written synchronously, i.e. without any await point in between,
+    // so that concurrently translated records cannot interleave or observe a partially written file.
+    // As the directory contains only the datasets written during this harvest,
+    // records yielded again with identical content are skipped silently.
+    let outcome = match dir.read(&*name) {
+        Ok(buf) => {
+            if buf == dataset.encode()? {
+                return Ok(Outcome::Identical);
+            }
+
+            let (previous, _schema) = Dataset::decode(&buf)?;
+            dataset.merge(previous);
+
+            tracing::warn!("Merging duplicate dataset {id} with differing content");
+            Outcome::Duplicate { valid }
+        }
+        Err(err) if err.kind() == ErrorKind::NotFound => Outcome::Written { valid },
+        Err(err) => return Err(err.into()),
+    };
+
+    dir.write(&*name, dataset.encode()?)?;
+
+    Ok(outcome)
+}
+
+/// Carries over the date on which each dataset was first seen from the previous run.
+///
+/// Datasets which were not part of the previous run are considered first seen at the start of this one.
+///
+/// Datasets which are unchanged compared to the previous run are replaced by hard links to its files
+/// so that they are not stored twice. Hence this must happen after any enrichment modifying the files.
+/// Yields the number of unchanged datasets.
+pub async fn carry_over(previous_dir: Option<&Dir>, dir: &Dir, start: SystemTime) -> Result<usize> {
+    let today = PartialDate::from(OffsetDateTime::from(start).date());
+
+    let mut unchanged = 0;
+
+    for entry in dir.entries()? {
+        let id = entry?.file_name();
+
+        let previous = match previous_dir {
+            Some(previous_dir) if previous_dir.exists(&id) => Some(previous_dir.read(&id).ok()),
+            _ => None,
+        };
+
+        let first_seen = match &previous {
+            Some(previous) => previous
+                .as_deref()
+                .and_then(|buf| Dataset::decode(buf).ok())
+                .and_then(|(dataset, _schema)| dataset.first_seen),
+            None => Some(today),
+        };
+
+        let mut dataset = Dataset::read(dir.open(&id)?)?;
+
+        if dataset.first_seen == first_seen {
+            continue;
+        }
+
+        dataset.first_seen = first_seen;
+
+        let buf = dataset.encode()?;
+
+        match (previous_dir, previous) {
+            (Some(previous_dir), Some(Some(previous))) if previous == buf => {
+                dir.remove_file(&id)?;
+                previous_dir.hard_link(&id, dir, &id)?;
+
+                unchanged += 1;
+            }
+            _ => dir.write(&id, buf)?,
+        }
+    }
+
+    Ok(unchanged)
+}
+
+/// Sends the requests using the configured concurrency or adaptively if `max_concurrency` is set.
+///
+/// After `max_consecutive_failures` requests failed in a row, the remaining ones are skipped
+/// instead of hammering a source which is obviously broken. Setting it to zero disables this.
+async fn fetch_many<R, T, M, F>(
+    source: &Source,
+    mut counters: HarvestCounters,
+    mut requests: R,
+    make_request: M,
+) -> HarvestCounters
+where
+    R: Iterator<Item = T>,
+    M: Fn(T) -> F,
+    F: Future<Output = Result<(usize, HarvestCounters)>>,
+{
+    let mut concurrency = source
+        .max_concurrency
+        .map(|max_concurrency| AdaptiveConcurrency::new(source.concurrency, max_concurrency));
+
+    let mut consecutive_failures = 0;
+
+    let mut pending = FuturesUnordered::new();
+
+    loop {
+        let limit = concurrency
+            .as_ref()
+            .map_or(source.concurrency, |concurrency| concurrency.limit);
+
+        while pending.len() < limit {
+            let Some(request) = requests.next() else {
+                break;
+            };
+
+            let response = make_request(request);
+
+            pending.push(async move {
+                let start = Instant::now();
+                let res = response.await;
+                (res, start.elapsed())
+            });
+        }
+
+        let Some((res, latency)) = pending.next().await else {
+            break;
+        };
+
+        if let Some(concurrency) = &mut concurrency {
+            concurrency.record(latency, res.is_err());
+        }
+
+        if res.is_err() {
+            consecutive_failures += 1;
+        } else {
+            consecutive_failures = 0;
+        }
+
+        account(source, &mut counters, res);
+
+        if source.max_consecutive_failures != 0
+            && consecutive_failures >= source.max_consecutive_failures
+        {
+            counters.aborted = pending.len() + requests.count();
+
+            tracing::error!(
+                "Aborting harvest after {} consecutive failures, skipping {} requests",
+                consecutive_failures,
+                counters.aborted
+            );
+
+            break;
+        }
+    }
+
+    if let Some(concurrency) = concurrency {
+        tracing::info!(
+            "Finished with an effective concurrency of {}",
+            concurrency.limit
+        );
+
+        counters.concurrency = concurrency.limit;
+    }
+
+    counters
+}
+
+/// Fetches the remaining pages of `batch_size` records after the first page reported `count` records.
+///
+/// `fetch_page` is called with the zero-based offset of the first record of each page and the number of records to fetch.
+/// If a page fails due to a server error or a timeout, it is retried as two pages of half the size
+/// before it is counted as failed as some servers cannot handle large pages.
+async fn fetch_remaining_pages<M, F>(
+    source: &Source,
+    count: usize,
+    counters: HarvestCounters,
+    fetch_page: M,
+) -> HarvestCounters
+where
+    M: Fn(usize, usize) -> F,
+    F: Future<Output = Result<(usize, HarvestCounters)>>,
+{
+    tracing::info!("Harvesting {} datasets", count);
+
+    let batch_size = source.batch_size;
+
+    let pages = (count + batch_size - 1) / batch_size;
+    let offsets = (1..pages).map(|page| page * batch_size);
+
+    fetch_many(source, counters, offsets, |offset| {
+        fetch_page_halving(&fetch_page, offset, batch_size)
+    })
+    .await
+}
+
+async fn fetch_page_halving<M, F>(
+    fetch_page: &M,
+    offset: usize,
+    limit: usize,
+) -> Result<(usize, HarvestCounters)>
+where
+    M: Fn(usize, usize) -> F,
+    F: Future<Output = Result<(usize, HarvestCounters)>>,
+{
+    let err = match fetch_page(offset, limit).await {
+        Ok(res) => return Ok(res),
+        Err(err) if limit > 1 && is_server_error(&err) => err,
+        Err(err) => return Err(err),
+    };
+
+    let half = (limit + 1) / 2;
+
+    tracing::warn!(
+        "Retrying {} datasets starting at {} as two pages of {} datasets: {:#}",
+        limit,
+        offset,
+        half,
+        err
+    );
+
+    let mut count = 0;
+    let mut counters = HarvestCounters::default();
+    let mut failed = 0;
+
+    for (offset, limit) in [(offset, half), (offset + half, limit - half)] {
+        match fetch_page(offset, limit).await {
+            Ok((count1, counters1)) => {
+                count = count1;
+                counters += counters1;
+            }
+            Err(err) => {
+                tracing::error!("{:#}", err);
+
+                counters.failed += limit;
+                failed += 1;
+            }
+        }
+    }
+
+    // Only if both halves failed, the page is accounted as a whole.
+    if failed == 2 {
+        return Err(err);
+    }
+
+    Ok((count, counters))
+}
+
+/// Yields whether a request failed due to the server, i.e. with a `5xx` status or by timing out.
+fn is_server_error(err: &anyhow::Error) -> bool {
+    err.chain()
+        .filter_map(|err| err.downcast_ref::<reqwest::Error>())
+        .any(|err| {
+            err.is_timeout()
+                || err
+                    .status()
+                    .map_or(false, |status| status.is_server_error())
+        })
+}
+
+fn account(source: &Source, counters: &mut HarvestCounters, res: Result<(usize, HarvestCounters)>) {
+    match res {
+        Ok((_count, counters1)) => *counters += counters1,
+        Err(err) => {
+            tracing::error!("{:#}", err);
+
+            counters.failed += source.batch_size;
+        }
+    }
+}
+
+/// Controls the number of concurrent requests by additive increase and multiplicative decrease (AIMD)
+///
+/// The limit grows by one after as many fast successful responses as requests are currently allowed,
+/// stays put while latencies exceed twice the fastest response seen so far and is halved on errors,
+/// including those due to the source responding with `429 Too Many Requests`.
+struct AdaptiveConcurrency {
+    limit: usize,
+    max: usize,
+    successes: usize,
+    min_latency: Option<Duration>,
+}
+
+impl AdaptiveConcurrency {
+    fn new(initial: usize, max: usize) -> Self {
+        Self {
+            limit: initial.clamp(1, max.max(1)),
+            max: max.max(1),
+            successes: 0,
+            min_latency: None,
+        }
+    }
+
+    fn record(&mut self, latency: Duration, failed: bool) {
+        if failed {
+            self.limit = (self.limit / 2).max(1);
+            self.successes = 0;
+            return;
+        }
+
+        let min_latency = self
+            .min_latency
+            .map_or(latency, |min_latency| min_latency.min(latency));
+        self.min_latency = Some(min_latency);
+
+        if latency > 2 * min_latency {
+            self.successes = 0;
+            return;
+        }
+
+        self.successes += 1;
+
+        if self.successes >= self.limit {
+            self.limit = (self.limit + 1).min(self.max);
+            self.successes = 0;
+        }
+    }
+}
+
+/// Formats the start of a delta harvest as expected by Solr and CQL queries.
+///
+/// It goes back one day to tolerate clock skew and delayed indexing at the source.
+fn modified_since(since: SystemTime) -> String {
+    let since = OffsetDateTime::from(since - Duration::from_secs(24 * 60 * 60));
+
+    since
+        .format(format_description!(
+            "[year]-[month]-[day]T[hour]:[minute]:[second]Z"
+        ))
+        .unwrap()
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Config {
+    pub sources: Vec<Source>,
+    #[serde(default)]
+    pub hosts: Vec<client::Host>,
+    /// Applies to all sources and is therefore shared with each of them when reading the configuration
+    #[serde(default)]
+    reporting_obligations: Vec<enrichment::ReportingObligationMapping>,
+}
+
+impl Config {
+    pub fn read(dir: &Dir) -> Result<Self> {
+        let mut file = dir.open("harvester.toml")?;
+
+        let mut buf = String::new();
+        file.read_to_string(&mut buf)?;
+        let mut val = from_str::<Self>(&buf)?;
+
+        {
+            let mut source_names = HashSet::new();
+
+            for source in &val.sources {
+                validate_name(&source.name)?;
+
+                ensure!(
+                    source.trust > 0.0,
+                    "Trust of source {} must be positive",
+                    source.name
+                );
+
+                ensure!(
+                    source_names.insert(&source.name),
+                    "Source names must be unique but {} was used twice",
+                    source.name
+                );
+            }
+        }
+
+        let reporting_obligations = Arc::new(val.reporting_obligations.clone());
+
+        for source in &mut val.sources {
+            source.reporting_obligations = reporting_obligations.clone();
+        }
+
+        Ok(val)
+    }
+
+    pub fn sources(&self) -> Sources {
+        let sources = self
+            .sources
+            .iter()
+            .map(|source| SourceInfo {
+                name: source.name.clone(),
+                title: source.title.as_ref().unwrap_or(&source.name).clone(),
+                group: source.group.clone(),
+                url: source.url.to_string(),
+                provenance: source.provenance.to_string(),
+                trust: source.trust,
+                visibility: source.visibility,
+            })
+            .collect();
+
+        Sources { sources }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct Source {
+    pub name: String,
+    pub r#type: Type,
+    title: Option<String>,
+    group: Option<String>,
+    url: Url,
+    #[serde(deserialize_with = "deserialize_provenance")]
+    provenance: DefaultAtom,
+    filter: Option<String>,
+    source_url: Option<String>,
+    #[serde(default = "default_concurrency")]
+    concurrency: usize,
+    max_concurrency: Option<usize>,
+    #[serde(default = "default_max_consecutive_failures")]
+    max_consecutive_failures: usize,
+    #[serde(default = "default_batch_size")]
+    batch_size: usize,
+    #[serde(default = "default_trust")]
+    trust: f64,
+    #[serde(default)]
+    visibility: Visibility,
+    api_key: Option<String>,
+    #[serde(default)]
+    auth: client::Auth,
+    proxy: Option<Url>,
+    /// Replaces the default `User-Agent`, e.g. if a portal requires registered agents.
+    user_agent: Option<String>,
+    /// Headers sent with every request which are not secret, unlike those of `auth`
+    #[serde(default)]
+    headers: HashMap<String, String>,
+    #[serde(default)]
+    landing_pages: bool,
+    /// Whether to fetch the detail endpoint of each document, currently only supported by Wasser-DE
+    #[serde(default)]
+    details: bool,
+    #[serde(default)]
+    probe_resources: bool,
+    #[serde(default)]
+    pub delta: bool,
+    /// Cron expression determining when the source is harvested in daemon mode
+    pub schedule: Option<schedule::Schedule>,
+    #[serde(default)]
+    csw: csw::Options,
+    #[serde(default)]
+    enrich: Vec<enrichment::Enrichment>,
+    #[serde(default)]
+    filters: filters::Filters,
+    #[serde(default, deserialize_with = "validation::deserialize_rules")]
+    validation: validation::Rules,
+    #[serde(skip)]
+    reporting_obligations: Arc<Vec<enrichment::ReportingObligationMapping>>,
+}
+
+/// Accepts provenances either as a path like `/Bund/UBA/GDI` or as a list of the levels of the hierarchy.
+fn deserialize_provenance<'de, D>(deserializer: D) -> Result<DefaultAtom, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Provenance {
+        Path(String),
+        Levels(Vec<String>),
+    }
+
+    let provenance = match Provenance::deserialize(deserializer)? {
+        Provenance::Path(path) => path,
+        Provenance::Levels(levels) => provenance_path(levels),
+    };
+
+    Ok(provenance.into())
+}
+
+fn default_concurrency() -> usize {
+    1
+}
+
+fn default_max_consecutive_failures() -> usize {
+    5
+}
+
+fn default_batch_size() -> usize {
+    100
+}
+
+impl Source {
+    pub fn source_url(&self) -> &str {
+        self.source_url
+            .as_deref()
+            .unwrap_or_else(|| self.url.as_str())
+    }
+
+    /// Yields the configured API key or otherwise the one from the environment,
+    /// e.g. `$GOVDATA_API_KEY` for a source named `govdata`.
+    ///
+    /// As the key is sent via the `Authorization` header, the `auth` table takes precedence if it sets that header.
+    fn api_key(&self) -> Option<String> {
+        if self.auth.sets_authorization() {
+            return None;
+        }
+
+        self.api_key.clone().or_else(|| {
+            let name = self.name.to_uppercase().replace('-', "_");
+
+            var(format!("{name}_API_KEY")).ok()
+        })
+    }
+}
+
+impl fmt::Debug for Source {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let Self {
+            name,
+            r#type,
+            title,
+            group,
+            url,
+            provenance,
+            filter,
+            source_url,
+            concurrency,
+            max_concurrency,
+            max_consecutive_failures,
+            batch_size,
+            trust,
+            visibility,
+            api_key,
+            auth,
+            proxy,
+            user_agent,
+            headers,
+            landing_pages,
+            details,
+            probe_resources,
+            delta,
+            schedule,
+            csw,
+            enrich,
+            filters,
+            validation,
+            // Shared by all sources and hence not repeated for each of them
+            reporting_obligations: _,
+        } = self;
+
+        fmt.debug_struct("Source")
+            .field("name", name)
+            .field("type", r#type)
+            .field("title", title)
+            .field("group", group)
+            // The default formats of `Url` and `DefaultAtom` are too verbose for the logs.
+            .field("url", &url.as_str())
+            .field("provenance", &provenance.as_ref())
+            .field("filter", filter)
+            .field("source_url", source_url)
+            .field("concurrency", concurrency)
+            .field("max_concurrency", max_concurrency)
+            .field("max_consecutive_failures", max_consecutive_failures)
+            .field("batch_size", batch_size)
+            .field("trust", trust)
+            .field("visibility", visibility)
+            // Do not leak secrets into the logs.
+            .field("api_key", &api_key.as_ref().map(|_| "..."))
+            .field("auth", auth)
+            // Proxy URLs might contain credentials.
+            .field(
+                "proxy",
+                &proxy
+                    .as_ref()
+                    .map(|proxy| proxy.origin().ascii_serialization()),
+            )
+            .field("user_agent", user_agent)
+            .field("headers", headers)
+            .field("landing_pages", landing_pages)
+            .field("details", details)
+            .field("probe_resources", probe_resources)
+            .field("delta", delta)
+            .field("schedule", schedule)
+            .field("csw", csw)
+            .field("enrich", enrich)
+            .field("filters", filters)
+            .field("validation", validation)
+            .finish()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Type {
+    Ckan,
+    Csw,
+    WasserDe,
+    GeoNetworkQ,
+    GeoNetworkEs,
+    DorisBfs,
+    SmartFinder,
+    SchemaOrg,
+    Bfn,
+    DwdCdc,
+    LocalDir,
+}
+
+impl Type {
+    /// Registry of the implementations of all types of sources
+    pub fn harvester(self) -> &'static dyn Harvester {
+        match self {
+            Self::Ckan => &ckan::Ckan,
+            Self::Csw => &csw::Csw,
+            Self::WasserDe => &wasser_de::WasserDe,
+            Self::GeoNetworkQ => &geo_network_q::GeoNetworkQ,
+            Self::GeoNetworkEs => &geo_network_es::GeoNetworkEs,
+            Self::DorisBfs => &doris_bfs::DorisBfs,
+            Self::SmartFinder => &smart_finder::SmartFinder,
+            Self::SchemaOrg => &schema_org::SchemaOrg,
+            Self::Bfn => &bfn::Bfn,
+            Self::DwdCdc => &dwd_cdc::DwdCdc,
+            Self::LocalDir => &local_dir::LocalDir,
+        }
+    }
+}
+
+/// Harvesting of a single type of source, looked up via [`Type::harvester`]
+pub trait Harvester: Sync {
+    /// Writes the datasets of the source into `dir`, yielding the number of records reported by the source.
+    ///
+    /// If `since` is given, only records modified since then are harvested.
+    fn harvest<'a>(
+        &self,
+        dir: &'a Dir,
+        client: &'a client::Client,
+        source: &'a Source,
+        since: Option<SystemTime>,
+    ) -> BoxFuture<'a, Result<(usize, HarvestCounters)>>;
+
+    /// Whether `since` is supported, i.e. unmodified datasets can be kept from the previous harvest
+    fn supports_delta(&self) -> bool {
+        false
+    }
+}
+
+impl FromStr for Type {
+    type Err = ValueError;
+
+    /// Parses the same names as used in `harvester.toml`, e.g. `geo_network_q`.
+    fn from_str(val: &str) -> Result<Self, Self::Err> {
+        Self::deserialize(val.into_deserializer())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::env::temp_dir;
+    use std::fs::{create_dir_all, remove_dir_all};
+    use std::process;
+
+    use anyhow::anyhow;
+    use cap_std::ambient_authority;
+
+    use umwelt_info_core::dataset::License;
+
+    #[tokio::test]
+    async fn fetch_many_aborts_after_consecutive_failures() {
+        let source = from_str::<Source>(
+            r#"
+name = "test"
+type = "ckan"
+url = "http://localhost"
+provenance = "/test"
+batch_size = 10
+max_consecutive_failures = 2
+"#,
+        )
+        .unwrap();
+
+        let counters = fetch_many(
+            &source,
+            HarvestCounters::default(),
+            0..10,
+            |request| async move {
+                if request == 0 {
+                    Ok((1, HarvestCounters::fetched(1)))
+                } else {
+                    Err(anyhow!("failure"))
+                }
+            },
+        )
+        .await;
+
+        assert_eq!(counters.fetched, 1);
+        assert_eq!(counters.failed, 2 * 10);
+        assert_eq!(counters.aborted, 7);
+    }
+
+    #[test]
+    fn auth_takes_precedence_over_api_key() {
+        let source = |auth: &str| {
+            from_str::<Source>(&format!(
+                r#"
+name = "test"
+type = "ckan"
+url = "http://localhost"
+provenance = "/test"
+api_key = "secret"
+{auth}
+"#
+            ))
+            .unwrap()
+        };
+
+        assert_eq!(source("").api_key().as_deref(), Some("secret"));
+        assert_eq!(
+            source(r#"auth = { headers = { "X-Api-Key" = "TEST_API_KEY" } }"#)
+                .api_key()
+                .as_deref(),
+            Some("secret")
+        );
+        assert_eq!(
+            source(r#"auth = { bearer = "TEST_TOKEN" }"#).api_key(),
+            None
+        );
+        assert_eq!(
+            source(r#"auth = { headers = { "authorization" = "TEST_TOKEN" } }"#).api_key(),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn identical_duplicates_are_skipped() {
+        let path = temp_dir().join(format!("umwelt-info-write-dataset-{}", process::id()));
+        create_dir_all(&path).unwrap();
+
+        let dir = Dir::open_ambient_dir(&path, ambient_authority()).unwrap();
+
+        let source = from_str::<Source>(
+            r#"
+name = "test"
+type = "ckan"
+url = "http://localhost"
+provenance = "/test"
+"#,
+        )
+        .unwrap();
+
+        let dataset = |description: &str| Dataset {
+            title: "Pegel".to_owned(),
+            description: Some(description.to_owned()),
+            summary: None,
+            comment: None,
+            provenance: Default::default(),
+            publisher: None,
+            license: License::Unknown,
+            legal_basis: Vec::new(),
+            reporting_obligations: Vec::new(),
+            contacts: Vec::new(),
+            tags: Vec::new(),
+            themes: Vec::new(),
+            region: None,
+            language: None,
+            bbox: None,
+            time_range: None,
+            issued: None,
+            modified: None,
+            update_frequency: None,
+            last_checked: None,
+            first_seen: None,
+            identifiers: Default::default(),
+            relations: Vec::new(),
+            source_url: String::new(),
+            resources: Default::default(),
+        };
+
+        let outcome = write_dataset(&dir, &source, "a", dataset("Pegel der Elbe"))
+            .await
+            .unwrap();
+        assert!(matches!(outcome, Outcome::Written { .. }));
+
+        let outcome = write_dataset(&dir, &source, "a", dataset("Pegel der Elbe"))
+            .await
+            .unwrap();
+        assert!(matches!(outcome, Outcome::Identical));
+
+        let outcome = write_dataset(&dir, &source, "a", dataset("Pegel der Elbe bei Dresden"))
+            .await
+            .unwrap();
+        assert!(matches!(outcome, Outcome::Duplicate { .. }));
+
+        let dataset = Dataset::read(dir.open("a").unwrap()).unwrap();
+        assert_eq!(
+            dataset.description.as_deref(),
+            Some("Pegel der Elbe bei Dresden")
+        );
+
+        remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn adaptive_concurrency_increases_additively_and_decreases_multiplicatively() {
+        let mut concurrency = AdaptiveConcurrency::new(1, 4);
+
+        let fast = Duration::from_millis(100);
+        let slow = Duration::from_millis(500);
+
+        concurrency.record(fast, false);
+        assert_eq!(concurrency.limit, 2);
+
+        concurrency.record(fast, false);
+        concurrency.record(slow, false);
+        concurrency.record(fast, false);
+        assert_eq!(concurrency.limit, 2);
+
+        for _ in 0..10 {
+            concurrency.record(fast, false);
+        }
+        assert_eq!(concurrency.limit, 4);
+
+        concurrency.record(fast, true);
+        assert_eq!(concurrency.limit, 2);
+
+        concurrency.record(fast, true);
+        concurrency.record(fast, true);
+        assert_eq!(concurrency.limit, 1);
+    }
+}
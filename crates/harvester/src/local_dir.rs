@@ -0,0 +1,148 @@
+//! This harvester reads records delivered as files, e.g. on removable media or via SFTP, from a local directory.
+//!
+//! The directory is given as a `file://` URL and each file is translated using the mapping of an existing harvester:
+//!
+//! | Extension | Contents                                                 | Mapping         |
+//! | --------- | -------------------------------------------------------- | --------------- |
+//! | .json     | A single CKAN package or an array of them                | [`crate::ckan`] |
+//! | .toml     | A single CKAN package                                    | [`crate::ckan`] |
+//! | .xml      | A single ISO 19139 record, i.e. an `MD_Metadata` element | [`crate::csw`]  |
+//!
+//! Files with other extensions are ignored.
+use std::time::SystemTime;
+
+use anyhow::{anyhow, Result};
+use cap_std::{ambient_authority, fs::Dir};
+use futures_util::future::{BoxFuture, FutureExt};
+use serde_json::from_str as from_json_str;
+use serde_roxmltree::{from_doc as from_xml_doc, roxmltree::Document};
+use toml::from_str as from_toml_str;
+
+use umwelt_info_core::metrics::Outcome;
+
+use crate::{ckan, client::Client, csw, HarvestCounters, Harvester, Source};
+
+pub struct LocalDir;
+
+impl Harvester for LocalDir {
+    fn harvest<'a>(
+        &self,
+        dir: &'a Dir,
+        _client: &'a Client,
+        source: &'a Source,
+        _since: Option<SystemTime>,
+    ) -> BoxFuture<'a, Result<(usize, HarvestCounters)>> {
+        harvest(dir, source).boxed()
+    }
+}
+
+pub async fn harvest(dir: &Dir, source: &Source) -> Result<(usize, HarvestCounters)> {
+    let path = source
+        .url
+        .to_file_path()
+        .map_err(|()| anyhow!("Source URL {} is not a local path", source.url))?;
+
+    let records_dir = Dir::open_ambient_dir(path, ambient_authority())?;
+
+    let mut names = records_dir
+        .entries()?
+        .map(|entry| Ok(entry?.file_name().to_string_lossy().into_owned()))
+        .collect::<Result<Vec<_>>>()?;
+
+    names.sort_unstable();
+
+    let mut count = 0;
+    let mut counters = HarvestCounters::default();
+
+    for name in names {
+        let Some((_, extension)) = name.rsplit_once('.') else {
+            continue;
+        };
+
+        let text = match extension {
+            "json" | "toml" | "xml" => records_dir.read_to_string(&name)?,
+            _ => {
+                tracing::debug!("Ignoring file {name}");
+                continue;
+            }
+        };
+
+        let outcomes = match extension {
+            "json" => translate_json(dir, source, &text).await,
+            "toml" => translate_toml(dir, source, &text).await,
+            _ => translate_xml(dir, source, &text).await,
+        };
+
+        match outcomes {
+            Ok(outcomes) => {
+                count += outcomes.len();
+                counters.fetched += outcomes.len();
+
+                for outcome in outcomes {
+                    counters.record(outcome);
+                }
+            }
+            Err(err) => {
+                count += 1;
+                counters.fetched += 1;
+
+                counters.record(Err(err.context(format!("Failed to parse file {name}"))));
+            }
+        }
+    }
+
+    Ok((count, counters))
+}
+
+async fn translate_json(dir: &Dir, source: &Source, text: &str) -> Result<Vec<Result<Outcome>>> {
+    let packages = if text.trim_start().starts_with('[') {
+        from_json_str::<Vec<ckan::Package>>(text)?
+    } else {
+        vec![from_json_str::<ckan::Package>(text)?]
+    };
+
+    let mut outcomes = Vec::with_capacity(packages.len());
+
+    for package in packages {
+        outcomes.push(ckan::translate_dataset(dir, source, package).await);
+    }
+
+    Ok(outcomes)
+}
+
+async fn translate_toml(dir: &Dir, source: &Source, text: &str) -> Result<Vec<Result<Outcome>>> {
+    let package = from_toml_str::<ckan::Package>(text)?;
+
+    Ok(vec![ckan::translate_dataset(dir, source, package).await])
+}
+
+async fn translate_xml(dir: &Dir, source: &Source, text: &str) -> Result<Vec<Result<Outcome>>> {
+    let document = Document::parse(text)?;
+
+    let record = from_xml_doc::<csw::Record>(&document)?;
+
+    Ok(vec![csw::translate_dataset(dir, source, record).await])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_toml_package() {
+        from_toml_str::<ckan::Package>(
+            r#"
+id = "0a1b2c"
+name = "pegel-elbe"
+title = "Pegelstände der Elbe"
+license_id = "cc-by-4.0"
+
+[[resources]]
+url = "https://example.org/pegel.csv"
+format = "CSV"
+name = "Pegelstände"
+"#,
+        )
+        .unwrap();
+    }
+}
@@ -0,0 +1,168 @@
+//! This module probes the resources of already harvested datasets by sending `HEAD` requests to their URLs
+//! and recording the `Content-Type` and `Content-Length` reported by the servers hosting them.
+//!
+//! If the type of a resource is unknown, it is derived from the reported media type. The requests are subject
+//! to robots.txt and go through the same storage as all other responses, so that they are replayed and cached.
+//! It is enabled by setting `probe_resources = true` for a source.
+//!
+//! When checking links, which is enabled for all sources by passing `--check-links` to the harvester,
+//! the status of each response is recorded as well, so that broken links can be surfaced to users.
+//! Servers rejecting `HEAD` requests are asked for the first byte of the resource using a `GET` request instead.
+use anyhow::{anyhow, Result};
+use cap_std::fs::Dir;
+use futures_util::stream::{iter, StreamExt};
+use reqwest::{header::RANGE, StatusCode};
+use url::Url;
+
+use umwelt_info_core::dataset::{Dataset, LinkCheck, ResourceType};
+
+use crate::{
+    client::{Client, ContentHeaders},
+    Source,
+};
+
+pub async fn enrich(dir: &Dir, client: &Client, source: &Source, check_links: bool) -> Result<()> {
+    if !source.probe_resources && !check_links {
+        return Ok(());
+    }
+
+    let mut ids = Vec::new();
+
+    for entry in dir.entries()? {
+        let id = entry?
+            .file_name()
+            .into_string()
+            .map_err(|id| anyhow!("Invalid dataset ID {:?}", id))?;
+
+        ids.push(id);
+    }
+
+    tracing::info!("Probing resources of {} datasets", ids.len());
+
+    let (probed, broken) = iter(ids)
+        .map(|id| async move { probe_dataset(dir, client, source, check_links, &id).await })
+        .buffer_unordered(source.concurrency)
+        .fold((0, 0), |(probed, broken), res| async move {
+            match res {
+                Ok((probed1, broken1)) => (probed + probed1, broken + broken1),
+                Err(err) => {
+                    tracing::error!("{:#}", err);
+
+                    (probed, broken)
+                }
+            }
+        })
+        .await;
+
+    tracing::info!("Probed {} resources", probed);
+
+    if check_links {
+        tracing::info!("Found {} broken links", broken);
+    }
+
+    Ok(())
+}
+
+/// Yields the number of probed resources and of those whose links are broken.
+#[tracing::instrument(skip(dir, client, source))]
+async fn probe_dataset(
+    dir: &Dir,
+    client: &Client,
+    source: &Source,
+    check_links: bool,
+    id: &str,
+) -> Result<(usize, usize)> {
+    let mut dataset = Dataset::read(dir.open(id)?)?;
+
+    let mut probed = 0;
+    let mut broken = 0;
+
+    for (index, resource) in dataset.resources.iter_mut().enumerate() {
+        // Sizes given by the source do not imply that the resource was probed.
+        if !check_links && resource.content_type.is_some() {
+            continue;
+        }
+
+        let url = match Url::parse(&resource.url) {
+            Ok(url) if matches!(url.scheme(), "http" | "https") => url,
+            _ => continue,
+        };
+
+        if !client.allowed_by_robots(&url).await {
+            continue;
+        }
+
+        let key = format!("{}-head-{}-{}", source.name, id, index);
+
+        let mut res = client
+            .make_request::<_, ContentHeaders>(&key, |client| client.head(url.clone()))
+            .await;
+
+        if let Ok(headers) = &res {
+            let status = StatusCode::from_u16(headers.status)?;
+
+            if status == StatusCode::METHOD_NOT_ALLOWED || status == StatusCode::NOT_IMPLEMENTED {
+                res = client
+                    .make_request::<_, ContentHeaders>(&format!("{key}-get"), |client| {
+                        client.get(url.clone()).header(RANGE, "bytes=0-0")
+                    })
+                    .await;
+            }
+        }
+
+        probed += 1;
+
+        let headers = match res {
+            Ok(headers) => headers,
+            Err(err) => {
+                tracing::debug!("Failed to probe resource at {}: {:#}", url, err);
+
+                if check_links {
+                    resource.link = Some(LinkCheck { status: None });
+                    broken += 1;
+                }
+
+                continue;
+            }
+        };
+
+        if check_links {
+            let link = LinkCheck {
+                status: Some(headers.status),
+            };
+
+            if !link.available() {
+                broken += 1;
+            }
+
+            resource.link = Some(link);
+        }
+
+        // The headers of error pages do not describe the resource.
+        if !StatusCode::from_u16(headers.status)?.is_success() {
+            continue;
+        }
+
+        if let (ResourceType::Unknown, Some(content_type)) =
+            (&resource.r#type, &headers.content_type)
+        {
+            // Strip parameters like `charset` before matching the media type.
+            let media_type = content_type.split(';').next().unwrap_or_default();
+
+            resource.r#type = media_type.into();
+        }
+
+        resource.content_type = headers.content_type;
+
+        // Partial responses only report the length of the requested range.
+        if headers.status != StatusCode::PARTIAL_CONTENT.as_u16() {
+            resource.content_length = headers.content_length.or(resource.content_length);
+        }
+    }
+
+    if probed != 0 {
+        dataset.write(dir.create(id)?).await?;
+    }
+
+    Ok((probed, broken))
+}
@@ -0,0 +1,217 @@
+//! Cron expressions used to schedule the harvesting of individual sources in daemon mode.
+//!
+//! The five fields are minute, hour, day of month, month and day of week, each accepting `*`,
+//! single values, ranges like `1-5`, steps like `*/15` or `8-18/2` and comma-separated lists thereof.
+//! Days of week count from Sunday as `0`, with `7` also denoting Sunday. Times are interpreted as UTC.
+use std::str::FromStr;
+
+use anyhow::{anyhow, ensure, Error, Result};
+use serde::{Deserialize, Deserializer};
+use time::{Date, Duration, OffsetDateTime, Time, UtcOffset};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    minutes: u64,
+    hours: u32,
+    days: u32,
+    months: u16,
+    weekdays: u8,
+    /// Whether day of month and day of week are both restricted so that matching either suffices
+    either_day: bool,
+}
+
+impl Schedule {
+    /// Yields the first point in time strictly after `now` matching this schedule.
+    ///
+    /// Schedules which can never match, e.g. the 31st of February, yield `None`.
+    pub fn next_after(&self, now: OffsetDateTime) -> Option<OffsetDateTime> {
+        let now = now.to_offset(UtcOffset::UTC);
+
+        let start = now.replace_time(Time::from_hms(now.hour(), now.minute(), 0).unwrap())
+            + Duration::minutes(1);
+
+        let mut date = start.date();
+
+        // Leap days can take up to eight years to come around again.
+        for _ in 0..8 * 366 {
+            if self.matches_date(date) {
+                for hour in 0..24 {
+                    if self.hours & (1 << hour) == 0 {
+                        continue;
+                    }
+
+                    for minute in 0..60 {
+                        if self.minutes & (1 << minute) == 0 {
+                            continue;
+                        }
+
+                        let time = Time::from_hms(hour, minute, 0).unwrap();
+                        let candidate = date.with_time(time).assume_utc();
+
+                        if candidate >= start {
+                            return Some(candidate);
+                        }
+                    }
+                }
+            }
+
+            date = date.next_day()?;
+        }
+
+        None
+    }
+
+    fn matches_date(&self, date: Date) -> bool {
+        if self.months & (1 << u8::from(date.month())) == 0 {
+            return false;
+        }
+
+        let day = self.days & (1 << date.day()) != 0;
+        let weekday = self.weekdays & (1 << date.weekday().number_days_from_sunday()) != 0;
+
+        if self.either_day {
+            day || weekday
+        } else {
+            day && weekday
+        }
+    }
+}
+
+impl FromStr for Schedule {
+    type Err = Error;
+
+    fn from_str(val: &str) -> Result<Self> {
+        let fields = val.split_whitespace().collect::<Vec<_>>();
+
+        ensure!(
+            fields.len() == 5,
+            "Cron expression `{}` does not have five fields",
+            val
+        );
+
+        let minutes = parse_field(fields[0], 0, 59)?;
+        let hours = parse_field(fields[1], 0, 23)?;
+        let days = parse_field(fields[2], 1, 31)?;
+        let months = parse_field(fields[3], 1, 12)?;
+        let mut weekdays = parse_field(fields[4], 0, 7)?;
+
+        if weekdays & (1 << 7) != 0 {
+            weekdays |= 1;
+            weekdays &= !(1 << 7);
+        }
+
+        Ok(Self {
+            minutes,
+            hours: hours as u32,
+            days: days as u32,
+            months: months as u16,
+            weekdays: weekdays as u8,
+            either_day: !fields[2].starts_with('*') && !fields[4].starts_with('*'),
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for Schedule {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let val = String::deserialize(deserializer)?;
+
+        val.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Yields a bit set of the values matched by a single field.
+fn parse_field(field: &str, min: u32, max: u32) -> Result<u64> {
+    let mut bits = 0;
+
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse::<u32>()?),
+            None => (part, 1),
+        };
+
+        ensure!(step != 0, "Step of `{}` must not be zero", part);
+
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (start.parse()?, end.parse()?)
+        } else {
+            let start = range.parse()?;
+
+            // A single value with a step like `5/10` extends to the maximum.
+            (start, if part.contains('/') { max } else { start })
+        };
+
+        if start < min || end > max || start > end {
+            return Err(anyhow!(
+                "Value `{}` is outside of the range {}-{}",
+                part,
+                min,
+                max
+            ));
+        }
+
+        for val in (start..=end).step_by(step as usize) {
+            bits |= 1 << val;
+        }
+    }
+
+    Ok(bits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use time::macros::datetime;
+
+    #[test]
+    fn next_runs() {
+        let schedule = "30 2 * * *".parse::<Schedule>().unwrap();
+
+        assert_eq!(
+            schedule.next_after(datetime!(2023-03-01 01:00 UTC)),
+            Some(datetime!(2023-03-01 02:30 UTC))
+        );
+        assert_eq!(
+            schedule.next_after(datetime!(2023-03-01 02:30 UTC)),
+            Some(datetime!(2023-03-02 02:30 UTC))
+        );
+
+        let schedule = "*/15 8-18 * * 1-5".parse::<Schedule>().unwrap();
+
+        assert_eq!(
+            schedule.next_after(datetime!(2023-03-03 18:50 UTC)),
+            Some(datetime!(2023-03-06 08:00 UTC))
+        );
+
+        let schedule = "0 0 29 2 *".parse::<Schedule>().unwrap();
+
+        assert_eq!(
+            schedule.next_after(datetime!(2023-03-01 00:00 UTC)),
+            Some(datetime!(2024-02-29 00:00 UTC))
+        );
+
+        let schedule = "0 0 1 * 7".parse::<Schedule>().unwrap();
+
+        assert_eq!(
+            schedule.next_after(datetime!(2023-03-02 00:00 UTC)),
+            Some(datetime!(2023-03-05 00:00 UTC))
+        );
+
+        let schedule = "0 0 31 2 *".parse::<Schedule>().unwrap();
+
+        assert_eq!(schedule.next_after(datetime!(2023-03-01 00:00 UTC)), None);
+    }
+
+    #[test]
+    fn invalid_expressions() {
+        assert!("* * * *".parse::<Schedule>().is_err());
+        assert!("60 * * * *".parse::<Schedule>().is_err());
+        assert!("*/0 * * * *".parse::<Schedule>().is_err());
+        assert!("* * 0 * *".parse::<Schedule>().is_err());
+    }
+}
@@ -0,0 +1,354 @@
+//! This harvester reads the `sitemap.xml` of a portal and extracts the [schema.org Dataset][https://schema.org/Dataset] JSON-LD blocks embedded into the listed pages.
+//!
+//! | Original field  | Mapped field | Comment                                                  |
+//! | --------------- | ------------ | -------------------------------------------------------- |
+//! | identifier      | id           | Last component of the page URL if missing                |
+//! | name            | title        |                                                          |
+//! | description     | description  |                                                          |
+//! | license         | license      | Either a URL or a CreativeWork with URL or name          |
+//! | keywords        | tags         | Either a list or a comma-separated string                |
+//! | datePublished   | issued       |                                                          |
+//! | dateModified    | modified     |                                                          |
+//! | distribution    | resources    | contentUrl preferred over url                            |
+//!
+//! The optional `filter` of the source is a regular expression which page URLs must match to be fetched.
+use std::time::SystemTime;
+
+use anyhow::{anyhow, Result};
+use cap_std::fs::Dir;
+use futures_util::future::{BoxFuture, FutureExt};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use scraper::{Html, Selector};
+use serde::Deserialize;
+use serde_json::{from_str, from_value, Value};
+use serde_roxmltree::{from_doc, roxmltree::Document};
+use url::Url;
+
+use umwelt_info_core::dataset::{Dataset, License, Resource};
+
+use crate::{
+    client::Client, date::parse_date, fetch_many, write_dataset, HarvestCounters, Harvester,
+    Outcome, Source,
+};
+
+pub struct SchemaOrg;
+
+impl Harvester for SchemaOrg {
+    fn harvest<'a>(
+        &self,
+        dir: &'a Dir,
+        client: &'a Client,
+        source: &'a Source,
+        _since: Option<SystemTime>,
+    ) -> BoxFuture<'a, Result<(usize, HarvestCounters)>> {
+        harvest(dir, client, source).boxed()
+    }
+}
+
+pub async fn harvest(
+    dir: &Dir,
+    client: &Client,
+    source: &Source,
+) -> Result<(usize, HarvestCounters)> {
+    let filter = source.filter.as_deref().map(Regex::new).transpose()?;
+
+    let mut pages = Vec::new();
+    let mut filtered = 0;
+    let mut sitemaps = vec![source.url.to_string()];
+    let mut sitemap_index = 0;
+
+    while let Some(sitemap) = sitemaps.pop() {
+        let key = format!("{}-sitemap-{}", source.name, sitemap_index);
+        sitemap_index += 1;
+
+        let (urls, nested_sitemaps) = fetch_sitemap(client, &key, &sitemap).await?;
+
+        for url in urls {
+            if filter.as_ref().map_or(true, |filter| filter.is_match(&url)) {
+                pages.push(url);
+            } else {
+                filtered += 1;
+            }
+        }
+
+        sitemaps.extend(nested_sitemaps);
+    }
+
+    let count = pages.len();
+    tracing::info!("Harvesting {} pages", count);
+
+    let counters = HarvestCounters {
+        filtered,
+        ..Default::default()
+    };
+
+    let counters = fetch_many(
+        source,
+        counters,
+        pages.into_iter().enumerate(),
+        |(index, url)| async move {
+            let mut counters = HarvestCounters::fetched(1);
+
+            counters.record(fetch_page(dir, client, source, index, &url).await);
+
+            Ok((1, counters))
+        },
+    )
+    .await;
+
+    Ok((count, counters))
+}
+
+#[tracing::instrument(skip(client))]
+async fn fetch_sitemap(
+    client: &Client,
+    key: &str,
+    url: &str,
+) -> Result<(Vec<String>, Vec<String>)> {
+    tracing::debug!("Fetching sitemap");
+
+    let body: String = client.make_request(key, |client| client.get(url)).await?;
+
+    let document = Document::parse(&body)?;
+
+    let sitemap = from_doc::<Sitemap>(&document)?;
+
+    let urls = sitemap
+        .urls
+        .into_iter()
+        .map(|url| url.loc.trim().to_owned())
+        .collect();
+
+    let sitemaps = sitemap
+        .sitemaps
+        .into_iter()
+        .map(|sitemap| sitemap.loc.trim().to_owned())
+        .collect();
+
+    Ok((urls, sitemaps))
+}
+
+#[tracing::instrument(skip(dir, client, source))]
+async fn fetch_page(
+    dir: &Dir,
+    client: &Client,
+    source: &Source,
+    index: usize,
+    url: &str,
+) -> Result<Outcome> {
+    tracing::debug!("Fetching page");
+
+    if !client.allowed_by_robots(&Url::parse(url)?).await {
+        return Ok(Outcome::Disallowed);
+    }
+
+    let key = format!("{}-{}", source.name, index);
+
+    let body: String = client.make_request(&key, |client| client.get(url)).await?;
+
+    client.quarantine(&key, translate_page(dir, source, url, &body).await)
+}
+
+async fn translate_page(dir: &Dir, source: &Source, url: &str, body: &str) -> Result<Outcome> {
+    let blocks = {
+        let document = Html::parse_document(body);
+
+        document
+            .select(&JSON_LD_SELECTOR)
+            .map(|element| element.text().collect::<String>())
+            .collect::<Vec<_>>()
+    };
+
+    let dataset = blocks
+        .iter()
+        .filter_map(|block| from_str::<Value>(block).ok())
+        .find_map(find_dataset)
+        .ok_or_else(|| anyhow!("Page {url} does not contain a schema.org Dataset"))?;
+
+    let dataset = from_value::<JsonLdDataset>(dataset)?;
+
+    translate_dataset(dir, source, url, dataset).await
+}
+
+async fn translate_dataset(
+    dir: &Dir,
+    source: &Source,
+    url: &str,
+    dataset: JsonLdDataset,
+) -> Result<Outcome> {
+    let id = match dataset.identifier {
+        Some(Identifier::Text(identifier)) => identifier,
+        Some(Identifier::Number(identifier)) => identifier.to_string(),
+        None => url
+            .trim_end_matches('/')
+            .rsplit('/')
+            .next()
+            .unwrap()
+            .to_owned(),
+    };
+
+    let license = match &dataset.license {
+        Some(LicenseRef::Text(license)) => license.as_str().into(),
+        Some(LicenseRef::CreativeWork { url, name }) => url.as_deref().or(name.as_deref()).into(),
+        None => License::Unknown,
+    };
+
+    let tags = match dataset.keywords {
+        Some(Keywords::List(keywords)) => keywords,
+        Some(Keywords::Text(keywords)) => keywords
+            .split(',')
+            .map(|keyword| keyword.trim().to_owned())
+            .filter(|keyword| !keyword.is_empty())
+            .collect(),
+        None => Vec::new(),
+    }
+    .into_iter()
+    .map(Into::into)
+    .collect();
+
+    let issued = dataset.date_published.as_deref().and_then(parse_date);
+    let modified = dataset.date_modified.as_deref().and_then(parse_date);
+
+    let resources = dataset
+        .distribution
+        .into_vec()
+        .into_iter()
+        .filter_map(|distribution| distribution.content_url.or(distribution.url))
+        .map(Resource::unknown)
+        .collect();
+
+    let dataset = Dataset {
+        title: dataset.name,
+        description: dataset.description,
+        summary: None,
+        comment: None,
+        provenance: source.provenance.clone(),
+        publisher: None,
+        license,
+        legal_basis: Vec::new(),
+        reporting_obligations: Vec::new(),
+        contacts: Vec::new(),
+        tags,
+        themes: Vec::new(),
+        region: None,
+        language: None,
+        bbox: None,
+        time_range: None,
+        issued,
+        modified,
+        update_frequency: None,
+        last_checked: None,
+        first_seen: None,
+        identifiers: Default::default(),
+        relations: Vec::new(),
+        source_url: url.to_owned(),
+        resources,
+    };
+
+    write_dataset(dir, source, &id, dataset).await
+}
+
+/// Finds the first object of type `Dataset` within a JSON-LD block, including those nested into `@graph`.
+pub(super) fn find_dataset(val: Value) -> Option<Value> {
+    match val {
+        Value::Array(vals) => vals.into_iter().find_map(find_dataset),
+        Value::Object(mut obj) => {
+            let is_dataset = match obj.get("@type") {
+                Some(Value::String(r#type)) => r#type == "Dataset",
+                Some(Value::Array(types)) => types.iter().any(|r#type| r#type == "Dataset"),
+                _ => false,
+            };
+
+            if is_dataset {
+                Some(Value::Object(obj))
+            } else {
+                obj.remove("@graph").and_then(find_dataset)
+            }
+        }
+        _ => None,
+    }
+}
+
+pub(super) static JSON_LD_SELECTOR: Lazy<Selector> =
+    Lazy::new(|| Selector::parse(r#"script[type="application/ld+json"]"#).unwrap());
+
+#[derive(Debug, Deserialize)]
+struct Sitemap<'a> {
+    #[serde(rename = "url", default, borrow)]
+    urls: Vec<Loc<'a>>,
+    #[serde(rename = "sitemap", default, borrow)]
+    sitemaps: Vec<Loc<'a>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Loc<'a> {
+    loc: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonLdDataset {
+    name: String,
+    description: Option<String>,
+    identifier: Option<Identifier>,
+    license: Option<LicenseRef>,
+    keywords: Option<Keywords>,
+    date_published: Option<String>,
+    date_modified: Option<String>,
+    #[serde(default)]
+    distribution: OneOrMany<Distribution>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Identifier {
+    Text(String),
+    Number(u64),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum LicenseRef {
+    Text(String),
+    CreativeWork {
+        url: Option<String>,
+        name: Option<String>,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Keywords {
+    List(Vec<String>),
+    Text(String),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Distribution {
+    content_url: Option<String>,
+    url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum OneOrMany<T> {
+    One(T),
+    Many(Vec<T>),
+}
+
+impl<T> Default for OneOrMany<T> {
+    fn default() -> Self {
+        Self::Many(Vec::new())
+    }
+}
+
+impl<T> OneOrMany<T> {
+    fn into_vec(self) -> Vec<T> {
+        match self {
+            Self::One(val) => vec![val],
+            Self::Many(vals) => vals,
+        }
+    }
+}
@@ -0,0 +1,205 @@
+//! This harvester maps the Solr index behind smart.finder SDI portals into our catalogue.
+//!
+//! | Original field | Mapped field | Comment                                                     |
+//! | -------------- | ------------ | ----------------------------------------------------------- |
+//! | id             | id           |                                                             |
+//! | title          | title        |                                                             |
+//! | description    | description  |                                                             |
+//! | license        | license      | Either a license ID or JSON-encoded license as per GDI-DE   |
+//! | keywords       | tags         |                                                             |
+//! | links          | resources    | Type inferred from file extension                           |
+use std::borrow::Cow;
+use std::time::SystemTime;
+
+use anyhow::Result;
+use cap_std::fs::Dir;
+use futures_util::future::{BoxFuture, FutureExt};
+use serde::{Deserialize, Serialize};
+use serde_json::from_str;
+
+use umwelt_info_core::dataset::{Dataset, Resource, ResourceType};
+
+use crate::{
+    client::Client, fetch_remaining_pages, write_dataset, HarvestCounters, Harvester, Outcome,
+    Source,
+};
+
+pub struct SmartFinder;
+
+impl Harvester for SmartFinder {
+    fn harvest<'a>(
+        &self,
+        dir: &'a Dir,
+        client: &'a Client,
+        source: &'a Source,
+        _since: Option<SystemTime>,
+    ) -> BoxFuture<'a, Result<(usize, HarvestCounters)>> {
+        harvest(dir, client, source).boxed()
+    }
+}
+
+pub async fn harvest(
+    dir: &Dir,
+    client: &Client,
+    source: &Source,
+) -> Result<(usize, HarvestCounters)> {
+    let rows = source.batch_size;
+
+    let (count, counters) = fetch_datasets(dir, client, source, rows, 0).await?;
+    let counters = fetch_remaining_pages(source, count, counters, |start, rows| {
+        fetch_datasets(dir, client, source, rows, start)
+    })
+    .await;
+
+    Ok((count, counters))
+}
+
+#[tracing::instrument(skip(dir, client, source))]
+async fn fetch_datasets(
+    dir: &Dir,
+    client: &Client,
+    source: &Source,
+    rows: usize,
+    start: usize,
+) -> Result<(usize, HarvestCounters)> {
+    tracing::debug!("Fetching {} datasets starting at {}", rows, start);
+
+    let key = format!("{}-{}", source.name, start);
+
+    let body: String = client
+        .make_request(&key, |client| {
+            client.get(source.url.clone()).query(&SelectParams {
+                q: "*",
+                rows,
+                start,
+            })
+        })
+        .await?;
+
+    let response = from_str::<SelectResponse>(&body)?;
+
+    let count = response.results.num_found;
+    let mut counters = HarvestCounters::fetched(response.results.docs.len());
+
+    for doc in response.results.docs {
+        counters.record(client.quarantine(&key, translate_dataset(dir, source, doc).await));
+    }
+
+    Ok((count, counters))
+}
+
+async fn translate_dataset(dir: &Dir, source: &Source, doc: Document<'_>) -> Result<Outcome> {
+    let license = doc.license().into();
+
+    let tags = doc
+        .keywords
+        .iter()
+        .map(|keyword| keyword.trim())
+        .filter(|keyword| !keyword.is_empty())
+        .map(Into::into)
+        .collect();
+
+    let resources = doc
+        .links
+        .into_iter()
+        .map(|url| Resource {
+            r#type: url
+                .rsplit_once('.')
+                .map_or(ResourceType::Unknown, |(_, extension)| extension.into()),
+            url,
+            title: None,
+            description: None,
+            license: None,
+            content_type: None,
+            format: None,
+            content_length: None,
+            checksum: None,
+            link: None,
+        })
+        .collect();
+
+    let dataset = Dataset {
+        title: doc.title,
+        description: Some(doc.description),
+        summary: None,
+        comment: None,
+        provenance: source.provenance.clone(),
+        publisher: None,
+        license,
+        legal_basis: Vec::new(),
+        reporting_obligations: Vec::new(),
+        contacts: Vec::new(),
+        tags,
+        themes: Vec::new(),
+        region: None,
+        language: None,
+        bbox: None,
+        time_range: None,
+        issued: None,
+        modified: None,
+        update_frequency: None,
+        last_checked: None,
+        first_seen: None,
+        identifiers: Default::default(),
+        relations: Vec::new(),
+        source_url: source.source_url().replace("{{id}}", &doc.id),
+        resources,
+    };
+
+    write_dataset(dir, source, &doc.id, dataset).await
+}
+
+#[derive(Debug, Serialize)]
+struct SelectParams<'a> {
+    q: &'a str,
+    rows: usize,
+    start: usize,
+}
+
+#[derive(Debug, Deserialize)]
+struct SelectResponse<'a> {
+    #[serde(rename = "response", borrow)]
+    results: Results<'a>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Results<'a> {
+    #[serde(rename = "numFound")]
+    num_found: usize,
+    #[serde(borrow)]
+    docs: Vec<Document<'a>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Document<'a> {
+    #[serde(borrow)]
+    id: Cow<'a, str>,
+    title: String,
+    description: String,
+    #[serde(borrow)]
+    license: Option<Cow<'a, str>>,
+    #[serde(default)]
+    keywords: Vec<String>,
+    #[serde(default)]
+    links: Vec<String>,
+}
+
+impl Document<'_> {
+    /// Extracts the license ID which is either given directly or as a JSON-encoded object as in ISO metadata following the GDI-DE conventions.
+    fn license(&self) -> Option<&str> {
+        let license = self.license.as_deref()?.trim();
+
+        if license.starts_with('{') {
+            return from_str::<LicenseObject>(license)
+                .ok()
+                .map(|license| license.id);
+        }
+
+        Some(license).filter(|license| !license.is_empty())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct LicenseObject<'a> {
+    id: &'a str,
+}
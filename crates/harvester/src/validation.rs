@@ -0,0 +1,242 @@
+//! Rules which the datasets of a source must satisfy, configured via a `[sources.validation]` table
+//!
+//! Alternatively, `validation = "report"` checks strict rules without skipping any dataset
+//! and records the violations with the harvest metrics, e.g. while onboarding a new source.
+use anyhow::Result;
+use cap_std::fs::Dir;
+use serde::{Deserialize, Deserializer};
+
+use umwelt_info_core::{dataset::Dataset, decode_id, metrics::Finding};
+
+use crate::Source;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Rules {
+    /// Titles must contain more than whitespace.
+    non_empty_title: bool,
+    /// Minimum number of characters of the description, missing descriptions count as empty
+    min_description_length: Option<usize>,
+    /// Minimum number of resources
+    min_resources: Option<usize>,
+    /// Skip datasets violating any of the above rules instead of only counting them
+    skip_invalid: bool,
+    /// Write all datasets and record the violations of each one with the harvest metrics
+    report: bool,
+}
+
+impl Rules {
+    /// Rules which are checked if the source is validated using `validation = "report"`
+    fn strict_report() -> Self {
+        Self {
+            non_empty_title: true,
+            min_description_length: Some(50),
+            min_resources: Some(1),
+            skip_invalid: false,
+            report: true,
+        }
+    }
+
+    /// Yields the rules violated by the given dataset.
+    pub fn violations(&self, dataset: &Dataset) -> Vec<&'static str> {
+        let mut violations = Vec::new();
+
+        if self.non_empty_title && dataset.title.trim().is_empty() {
+            violations.push("non_empty_title");
+        }
+
+        if let Some(min_description_length) = self.min_description_length {
+            let description_length = dataset
+                .description
+                .as_deref()
+                .map_or(0, |description| description.trim().chars().count());
+
+            if description_length < min_description_length {
+                violations.push("min_description_length");
+            }
+        }
+
+        if let Some(min_resources) = self.min_resources {
+            if dataset.resources.len() < min_resources {
+                violations.push("min_resources");
+            }
+        }
+
+        violations
+    }
+
+    pub fn skip_invalid(&self) -> bool {
+        self.skip_invalid && !self.report
+    }
+
+    pub fn report(&self) -> bool {
+        self.report
+    }
+}
+
+/// Accepts either a table of rules or the `report` mode.
+pub(crate) fn deserialize_rules<'de, D>(deserializer: D) -> Result<Rules, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(rename_all = "snake_case")]
+    enum Mode {
+        Report,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Config {
+        Mode(Mode),
+        Rules(Rules),
+    }
+
+    let rules = match Config::deserialize(deserializer)? {
+        Config::Mode(Mode::Report) => Rules::strict_report(),
+        Config::Rules(rules) => rules,
+    };
+
+    Ok(rules)
+}
+
+/// Collects the violations of all datasets of a source which is validated in report mode.
+///
+/// This must happen after any datasets were kept from the previous run for the report to be complete.
+pub fn report(source: &Source, dir: &Dir) -> Result<Vec<Finding>> {
+    let rules = &source.validation;
+
+    if !rules.report {
+        return Ok(Vec::new());
+    }
+
+    let mut findings = Vec::new();
+
+    for entry in dir.entries()? {
+        let id = entry?.file_name();
+
+        let dataset = Dataset::read(dir.open(&id)?)?;
+
+        let violations = rules.violations(&dataset);
+
+        if !violations.is_empty() {
+            findings.push(Finding {
+                id: decode_id(&id.to_string_lossy()).into_owned(),
+                violations: violations.into_iter().map(ToOwned::to_owned).collect(),
+            });
+        }
+    }
+
+    findings.sort_unstable_by(|lhs, rhs| lhs.id.cmp(&rhs.id));
+
+    Ok(findings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use toml::from_str;
+
+    use umwelt_info_core::dataset::{License, Resource, ResourceType};
+
+    fn dataset(title: &str, description: Option<&str>, resources: usize) -> Dataset {
+        Dataset {
+            title: title.to_owned(),
+            description: description.map(ToOwned::to_owned),
+            summary: None,
+            comment: None,
+            provenance: Default::default(),
+            publisher: None,
+            license: License::Unknown,
+            legal_basis: Vec::new(),
+            reporting_obligations: Vec::new(),
+            contacts: Vec::new(),
+            tags: Vec::new(),
+            themes: Vec::new(),
+            region: None,
+            language: None,
+            bbox: None,
+            time_range: None,
+            issued: None,
+            modified: None,
+            update_frequency: None,
+            last_checked: None,
+            first_seen: None,
+            identifiers: Default::default(),
+            relations: Vec::new(),
+            source_url: String::new(),
+            resources: (0..resources)
+                .map(|_| Resource {
+                    r#type: ResourceType::Unknown,
+                    url: "https://example.org".to_owned(),
+                    title: None,
+                    description: None,
+                    license: None,
+                    content_type: None,
+                    format: None,
+                    content_length: None,
+                    checksum: None,
+                    link: None,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn rules_are_checked() {
+        let rules = from_str::<Rules>(
+            r#"
+non_empty_title = true
+min_description_length = 10
+min_resources = 1
+"#,
+        )
+        .unwrap();
+
+        assert!(rules
+            .violations(&dataset("Messwerte", Some("Messwerte der Elbe"), 1))
+            .is_empty());
+
+        assert_eq!(
+            rules.violations(&dataset(" ", Some("Messwerte"), 0)),
+            ["non_empty_title", "min_description_length", "min_resources"]
+        );
+
+        assert_eq!(
+            rules.violations(&dataset("Messwerte", None, 2)),
+            ["min_description_length"]
+        );
+
+        assert!(Rules::default()
+            .violations(&dataset("", None, 0))
+            .is_empty());
+    }
+
+    #[test]
+    fn report_mode_checks_strict_rules() {
+        #[derive(Deserialize)]
+        struct Source {
+            #[serde(default, deserialize_with = "deserialize_rules")]
+            validation: Rules,
+        }
+
+        let rules = from_str::<Source>(r#"validation = "report""#)
+            .unwrap()
+            .validation;
+
+        assert!(rules.report());
+        assert!(!rules.skip_invalid());
+        assert_eq!(
+            rules.violations(&dataset("Messwerte", Some("Messwerte der Elbe"), 1)),
+            ["min_description_length"]
+        );
+
+        let rules = from_str::<Source>("[validation]\nmin_resources = 1\nskip_invalid = true")
+            .unwrap()
+            .validation;
+
+        assert!(!rules.report());
+        assert!(rules.skip_invalid());
+    }
+}
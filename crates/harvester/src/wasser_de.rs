@@ -27,34 +27,63 @@
 //! | ANSPRECHPARTNER_EMAIL_RL3 | contact_emails     |                                                              |
 //! | ANSPRECHPARTNER_NAME_RL4  | contact_names      |                                                              |
 //! | ANSPRECHPARTNER_EMAIL_RL4 | contact_emails     |                                                              |
+//!
+//! The listing contains only teaser information. Setting `details = true` for a source additionally fetches the detail endpoint
+//! of each document, bounded by `concurrency`, which is mapped as follows.
+//!
+//! | Original field            | Mapped field       | Comment                                                      |
+//! | ------------------------- | ------------------ | ------------------------------------------------------------ |
+//! | BESCHREIBUNG              | description        | Preferred over the teaser texts                              |
+//! | RAUMBEZUG                 | region             | Spatial references joined and preferred over REGION_NAME     |
+//! | DOKUMENTE                 | resources          | Appended to URL, duplicates are skipped                      |
 //!  
+use std::time::SystemTime;
+
 use anyhow::{anyhow, Result};
+use bytes::Bytes;
 use cap_std::fs::Dir;
+use futures_util::{
+    future::{BoxFuture, FutureExt},
+    stream::{iter, StreamExt},
+};
 use serde::{Deserialize, Serialize};
 use serde_json::from_slice;
-use smallvec::smallvec;
-use time::{macros::format_description, Date};
+use smallvec::{smallvec, SmallVec};
+
+use umwelt_info_core::dataset::{Contact, Dataset, PartialDate, Resource, Tag};
 
 use crate::{
-    dataset::{Contact, Dataset, Resource, Tag},
-    harvester::{client::Client, write_dataset, Source},
+    client::Client, date::parse_date, write_dataset, HarvestCounters, Harvester, Outcome, Source,
 };
 
-pub async fn harvest(dir: &Dir, client: &Client, source: &Source) -> Result<(usize, usize, usize)> {
+pub struct WasserDe;
+
+impl Harvester for WasserDe {
+    fn harvest<'a>(
+        &self,
+        dir: &'a Dir,
+        client: &'a Client,
+        source: &'a Source,
+        _since: Option<SystemTime>,
+    ) -> BoxFuture<'a, Result<(usize, HarvestCounters)>> {
+        harvest(dir, client, source).boxed()
+    }
+}
+
+pub async fn harvest(
+    dir: &Dir,
+    client: &Client,
+    source: &Source,
+) -> Result<(usize, HarvestCounters)> {
     let url = source
         .url
         .join("rest/BaseController/FilterElements/V_REP_BASE_VALID")?;
 
-    let body = client
-        .make_request(&source.name, |client| async {
+    let body: Bytes = client
+        .make_request(&source.name, |client| {
             client
                 .post(url.clone())
                 .json(&Request { filter: Filter {} })
-                .send()
-                .await?
-                .error_for_status()?
-                .bytes()
-                .await
         })
         .await?;
 
@@ -63,36 +92,82 @@ pub async fn harvest(dir: &Dir, client: &Client, source: &Source) -> Result<(usi
     let count = response.results.len();
     tracing::info!("Retrieved {count} documents");
 
-    let mut errors = 0;
+    let counters = iter(response.results)
+        .map(|document| translate_dataset(dir, client, source, document))
+        .buffer_unordered(source.concurrency)
+        .fold(
+            HarvestCounters::fetched(count),
+            |mut counters, res| async move {
+                counters.record(client.quarantine(&source.name, res));
 
-    for document in response.results {
-        if let Err(err) = translate_dataset(dir, source, document).await {
-            tracing::error!("{:#}", err);
+                counters
+            },
+        )
+        .await;
 
-            errors += 1;
-        }
-    }
-
-    Ok((count, count, errors))
+    Ok((count, counters))
 }
 
-async fn translate_dataset(dir: &Dir, source: &Source, document: Document) -> Result<()> {
+async fn translate_dataset(
+    dir: &Dir,
+    client: &Client,
+    source: &Source,
+    mut document: Document,
+) -> Result<Outcome> {
+    let details = if source.details {
+        match fetch_details(client, source, document.id).await {
+            Ok(details) => details,
+            Err(err) => {
+                tracing::warn!(
+                    "Failed to fetch details of document {}: {:#}",
+                    document.id,
+                    err
+                );
+
+                None
+            }
+        }
+    } else {
+        None
+    };
+
     let tags = document.tags();
 
+    let mut resources: SmallVec<[Resource; 4]> = smallvec![Resource::unknown(document.url)];
+
+    if let Some(details) = details {
+        if details.description.is_some() {
+            document.teaser_text = details.description;
+        }
+
+        if !details.spatial_references.is_empty() {
+            document.region_name = Some(details.spatial_references.join(", "));
+        }
+
+        for attachment in details.attachments {
+            if resources
+                .iter()
+                .all(|resource| resource.url != attachment.url)
+            {
+                resources.push(Resource::unknown(attachment.url));
+            }
+        }
+    }
+
     let title = document
         .name
         .ok_or_else(|| anyhow!("Document {} has no title", document.id))?;
 
     let description = document.teaser_text.or(document.auto_teaser_text);
 
-    let issued = document
-        .year_issued
-        .map(|year_issued| Date::from_ordinal_date(year_issued, 1))
-        .transpose()?;
+    let issued = document.year_issued.map(PartialDate::Year);
 
     let last_checked = document
         .last_checked
-        .map(|last_checked| Date::parse(&last_checked, format_description!("[year]-[month]-[day]")))
+        .map(|last_checked| {
+            parse_date(&last_checked)
+                .ok_or_else(|| anyhow!("Invalid date of last check: {}", last_checked))
+        })
         .transpose()?;
 
     let mut contacts = Vec::new();
@@ -113,19 +188,50 @@ async fn translate_dataset(dir: &Dir, source: &Source, document: Document) -> Re
     let dataset = Dataset {
         title,
         description,
+        summary: None,
         comment: document.comment,
         provenance: source.provenance.clone(),
+        publisher: None,
         license: document.license.as_str().into(),
+        legal_basis: Vec::new(),
+        reporting_obligations: Vec::new(),
         contacts,
         tags,
+        themes: Vec::new(),
         region: document.region_name,
+        language: None,
+        bbox: None,
+        time_range: None,
         issued,
+        modified: None,
+        update_frequency: None,
         last_checked,
+        first_seen: None,
+        identifiers: Default::default(),
+        relations: Vec::new(),
         source_url: source.url.clone().into(),
-        resources: smallvec![Resource::unknown(document.url)],
+        resources,
     };
 
-    write_dataset(dir, &document.id.to_string(), dataset).await
+    write_dataset(dir, source, &document.id.to_string(), dataset).await
+}
+
+async fn fetch_details(client: &Client, source: &Source, id: usize) -> Result<Option<Details>> {
+    let url = source
+        .url
+        .join("rest/BaseController/FilterElements/V_REP_BASE_DETAIL")?;
+
+    let body: Bytes = client
+        .make_request(&format!("{}-{}", source.name, id), |client| {
+            client.post(url.clone()).json(&DetailsRequest {
+                filter: DetailsFilter { id },
+            })
+        })
+        .await?;
+
+    let response = from_slice::<DetailsResponse>(&body)?;
+
+    Ok(response.results.into_iter().next())
 }
 
 #[derive(Serialize)]
@@ -189,6 +295,39 @@ struct Document {
     contact_email_rl4: Option<String>,
 }
 
+#[derive(Serialize)]
+struct DetailsRequest {
+    filter: DetailsFilter,
+}
+
+#[derive(Serialize)]
+struct DetailsFilter {
+    #[serde(rename = "ID")]
+    id: usize,
+}
+
+#[derive(Deserialize)]
+struct DetailsResponse {
+    #[serde(rename = "V_REP_BASE_DETAIL")]
+    results: Vec<Details>,
+}
+
+#[derive(Deserialize)]
+struct Details {
+    #[serde(rename = "BESCHREIBUNG")]
+    description: Option<String>,
+    #[serde(rename = "RAUMBEZUG", default)]
+    spatial_references: Vec<String>,
+    #[serde(rename = "DOKUMENTE", default)]
+    attachments: Vec<Attachment>,
+}
+
+#[derive(Deserialize)]
+struct Attachment {
+    #[serde(rename = "URL")]
+    url: String,
+}
+
 impl Document {
     fn tags(&self) -> Vec<Tag> {
         let mut tags = Vec::new();
@@ -0,0 +1,87 @@
+use axum::{
+    extract::Extension,
+    http::header::CONTENT_TYPE,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+use tokio::task::spawn_blocking;
+
+use crate::{indexes::Indexes, version::VERSION, ServerError};
+
+/// Entry point of the API linking to all other resources so that clients need not hard-code their URLs.
+///
+/// Templated links follow RFC 6570 and resources supporting content negotiation yield JSON if requested via `Accept`.
+pub async fn api_root(
+    Extension(indexes): Extension<&'static Indexes>,
+) -> Result<Json<ApiRoot>, ServerError> {
+    let index_generation = spawn_blocking(|| indexes.current().generation()).await??;
+
+    Ok(Json(ApiRoot {
+        version: VERSION,
+        index_generation,
+        links: Links {
+            this: Link::fixed("/api/v1"),
+            openapi: Link::fixed("/api/v1/openapi.json"),
+            search: Link::templated(
+                "/api/v1/search{?query,provenances_root,licenses_root,availability_root,publishers_root,themes_root,update_frequencies_root,reporting_obligations_root,page,results_per_page,sort,fields,snippet_length,index}",
+            ),
+            dataset: Link::templated("/dataset/{source}/{id}"),
+            sources: Link::fixed("/sources"),
+            metrics: Link::fixed("/metrics"),
+            version: Link::fixed("/version"),
+        },
+    }))
+}
+
+/// OpenAPI description of the API, in particular of the parameters and the JSON body accepted by search.
+pub async fn openapi() -> Response {
+    (
+        [(CONTENT_TYPE, "application/json")],
+        include_str!("../openapi.json"),
+    )
+        .into_response()
+}
+
+#[derive(Serialize)]
+pub struct ApiRoot {
+    version: &'static str,
+    /// Operation stamp of the last commit to the search index
+    index_generation: u64,
+    #[serde(rename = "_links")]
+    links: Links,
+}
+
+#[derive(Serialize)]
+struct Links {
+    #[serde(rename = "self")]
+    this: Link,
+    openapi: Link,
+    search: Link,
+    dataset: Link,
+    sources: Link,
+    metrics: Link,
+    version: Link,
+}
+
+#[derive(Serialize)]
+struct Link {
+    href: &'static str,
+    templated: bool,
+}
+
+impl Link {
+    fn fixed(href: &'static str) -> Self {
+        Self {
+            href,
+            templated: false,
+        }
+    }
+
+    fn templated(href: &'static str) -> Self {
+        Self {
+            href,
+            templated: true,
+        }
+    }
+}
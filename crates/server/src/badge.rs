@@ -0,0 +1,190 @@
+//! Counters of the datasets of a source which partner portals can embed into their own sites
+//!
+//! `/badge/:source` yields JSON or JSONP if a `callback` is given while `/badge/:source/svg` renders an image.
+//! Both are computed once per index generation as partner sites might embed them on every page.
+use askama::{filters::urlencode, Template};
+use axum::{
+    extract::{Extension, Host, Path, Query},
+    http::header::{ACCESS_CONTROL_ALLOW_ORIGIN, CACHE_CONTROL, CONTENT_TYPE},
+    response::{IntoResponse, Json, Response},
+};
+use cap_std::fs::Dir;
+use hashbrown::HashMap;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::to_string;
+use time::OffsetDateTime;
+use tokio::task::spawn_blocking;
+
+use umwelt_info_core::{
+    index::Searcher,
+    metrics::Metrics,
+    sources::{Sources, Visibility},
+    validate_name,
+};
+
+use crate::{indexes::Indexes, ServerError};
+
+/// Badges computed for the current index generation
+#[derive(Default)]
+pub struct Badges {
+    cache: Mutex<Cache>,
+}
+
+#[derive(Default)]
+struct Cache {
+    generation: u64,
+    badges: HashMap<String, Badge>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct Badge {
+    title: String,
+    /// Number of indexed datasets of the source
+    count: usize,
+    /// Date on which the source was last harvested
+    last_harvest: Option<String>,
+    /// Search for the datasets of the source
+    link: String,
+}
+
+impl Badges {
+    fn get(&self, searcher: &Searcher, dir: &Dir, source: &str) -> Result<Badge, ServerError> {
+        if validate_name(source).is_err() {
+            return Err(ServerError::BadRequest("Invalid source name"));
+        }
+
+        let generation = searcher.generation()?;
+
+        {
+            let mut cache = self.cache.lock();
+
+            if cache.generation != generation {
+                cache.generation = generation;
+                cache.badges.clear();
+            } else if let Some(badge) = cache.badges.get(source) {
+                return Ok(badge.clone());
+            }
+        }
+
+        let sources = Sources::read(dir)?;
+
+        let source = sources
+            .sources
+            .into_iter()
+            .find(|source1| source1.name == source && source1.visibility != Visibility::Hidden)
+            .ok_or(ServerError::NotFound("Unknown source"))?;
+
+        let count = searcher.count(&source.name)?;
+
+        let last_harvest = Metrics::read(dir)?
+            .harvests
+            .get(&source.name)
+            .map(|harvest| OffsetDateTime::from(harvest.start).date().to_string());
+
+        let link = format!(
+            "/search?provenances_root={}",
+            urlencode(&source.provenance).unwrap()
+        );
+
+        let badge = Badge {
+            title: source.title,
+            count,
+            last_harvest,
+            link,
+        };
+
+        self.cache.lock().badges.insert(source.name, badge.clone());
+
+        Ok(badge)
+    }
+}
+
+#[derive(Deserialize)]
+pub struct BadgeParams {
+    /// Name of the function wrapping the response as JSONP
+    callback: Option<String>,
+}
+
+pub async fn badge(
+    Path(source): Path<String>,
+    Query(params): Query<BadgeParams>,
+    Host(host): Host,
+    Extension(indexes): Extension<&'static Indexes>,
+    Extension(dir): Extension<&'static Dir>,
+    Extension(badges): Extension<&'static Badges>,
+) -> Result<Response, ServerError> {
+    if let Some(callback) = &params.callback {
+        let valid = !callback.is_empty()
+            && callback
+                .chars()
+                .all(|char| char.is_ascii_alphanumeric() || matches!(char, '_' | '$' | '.'));
+
+        if !valid {
+            return Err(ServerError::BadRequest("Invalid callback name"));
+        }
+    }
+
+    let mut badge = spawn_blocking(move || badges.get(&indexes.current(), dir, &source)).await??;
+
+    // Partner sites need an absolute link to our search.
+    badge.link = format!("https://{}{}", host, badge.link);
+
+    let response = match params.callback {
+        Some(callback) => (
+            [(CONTENT_TYPE, "application/javascript; charset=utf-8")],
+            format!("{}({});", callback, to_string(&badge)?),
+        )
+            .into_response(),
+        None => Json(badge).into_response(),
+    };
+
+    Ok((
+        [
+            (ACCESS_CONTROL_ALLOW_ORIGIN, "*"),
+            (CACHE_CONTROL, "public, max-age=3600"),
+        ],
+        response,
+    )
+        .into_response())
+}
+
+pub async fn badge_svg(
+    Path(source): Path<String>,
+    Extension(indexes): Extension<&'static Indexes>,
+    Extension(dir): Extension<&'static Dir>,
+    Extension(badges): Extension<&'static Badges>,
+) -> Result<Response, ServerError> {
+    let badge = spawn_blocking(move || badges.get(&indexes.current(), dir, &source)).await??;
+
+    let label = "umwelt.info";
+    let value = format!("{} Datensätze", badge.count);
+
+    // Approximates the width of the text as the font is chosen by the client.
+    let width = |text: &str| 10 + 7 * text.chars().count();
+
+    let page = BadgeSvg {
+        label,
+        label_width: width(label),
+        value_width: width(&value),
+        value,
+    };
+
+    Ok((
+        [
+            (CONTENT_TYPE, "image/svg+xml"),
+            (CACHE_CONTROL, "public, max-age=3600"),
+        ],
+        page.render().unwrap(),
+    )
+        .into_response())
+}
+
+#[derive(Template)]
+#[template(path = "badge.svg", escape = "html")]
+struct BadgeSvg {
+    label: &'static str,
+    label_width: usize,
+    value: String,
+    value_width: usize,
+}
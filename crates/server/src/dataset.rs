@@ -0,0 +1,182 @@
+use std::borrow::Cow;
+use std::io::ErrorKind;
+
+use askama::Template;
+use axum::{
+    extract::{Extension, Path, Query},
+    http::header::CONTENT_TYPE,
+    response::{IntoResponse, Response},
+};
+use cap_std::fs::Dir;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+use umwelt_info_core::{
+    dataset::{Dataset, RelationTarget, RelationType},
+    encode_id,
+    index::Searcher,
+    sources::{Sources, Visibility},
+    stats::Stats,
+    validate_name,
+};
+
+use crate::{filters, indexes::Indexes, stats::AccessGuard, Accept, ServerError};
+
+#[derive(Deserialize)]
+pub struct DatasetParams {
+    /// Yields the original record as stored by the harvester instead of the dataset
+    #[serde(default)]
+    original: bool,
+}
+
+pub async fn dataset(
+    Path((source, id)): Path<(String, String)>,
+    Query(params): Query<DatasetParams>,
+    accept: Accept,
+    Extension(indexes): Extension<&'static Indexes>,
+    Extension(dir): Extension<&'static Dir>,
+    Extension(stats): Extension<&'static Mutex<Stats>>,
+    Extension(access_guard): Extension<AccessGuard>,
+) -> Result<Response, ServerError> {
+    fn inner(
+        source: String,
+        id: String,
+        searcher: &Searcher,
+        dir: &Dir,
+        stats: &Mutex<Stats>,
+        access_guard: AccessGuard,
+    ) -> Result<DatasetPage, ServerError> {
+        let name = file_name(&source, &id)?;
+
+        let dataset = Dataset::read(dir.open_dir("datasets")?.open_dir(&source)?.open(&*name)?)?;
+
+        let duplicates = searcher.duplicates(&source, &id)?;
+
+        let relations = relations(searcher, &source, &id, &dataset)?;
+
+        let has_record = dir.exists(record_path(&source, &name));
+
+        let accesses = access_guard.publish(stats.lock().record_access(&source, &id));
+
+        let sources = Sources::read(dir)?;
+
+        let source_title = sources.get(&source).map(|source| source.title.clone());
+
+        let experimental = sources.visibility(&source) == Visibility::Experimental;
+
+        let page = DatasetPage {
+            source,
+            source_title,
+            experimental,
+            id,
+            dataset,
+            duplicates,
+            relations,
+            has_record,
+            accesses,
+        };
+
+        Ok(page)
+    }
+
+    if params.original {
+        return original(&source, &id, dir);
+    }
+
+    let page = inner(source, id, &indexes.current(), dir, stats, access_guard)?;
+
+    Ok(accept.into_repsonse(page))
+}
+
+#[derive(Template, Serialize)]
+#[template(path = "dataset.html")]
+struct DatasetPage {
+    source: String,
+    source_title: Option<String>,
+    /// Whether the source is still being evaluated
+    experimental: bool,
+    id: String,
+    dataset: Dataset,
+    /// Records of other sources describing the same dataset, starting with the canonical one
+    duplicates: Vec<(String, String)>,
+    /// Related datasets grouped by the type of relation, including those declared only by the other dataset
+    relations: Vec<(RelationType, Vec<RelationTarget>)>,
+    /// Whether the original record is available via `?original=true`
+    has_record: bool,
+    /// Rounded number of accesses unless too small to be published
+    accesses: Option<u64>,
+}
+
+fn relations(
+    searcher: &Searcher,
+    source: &str,
+    id: &str,
+    dataset: &Dataset,
+) -> Result<Vec<(RelationType, Vec<RelationTarget>)>, ServerError> {
+    let mut relations = Vec::new();
+
+    for r#type in [
+        RelationType::IsPartOf,
+        RelationType::HasPart,
+        RelationType::IsVersionOf,
+        RelationType::HasVersion,
+    ] {
+        let mut targets = dataset
+            .relations
+            .iter()
+            .filter(|relation| relation.r#type == r#type)
+            .map(|relation| relation.target.clone())
+            .collect::<Vec<_>>();
+
+        for (source, id) in searcher.relatives(r#type, source, id)? {
+            let target = RelationTarget::Dataset { source, id };
+
+            if !targets.contains(&target) {
+                targets.push(target);
+            }
+        }
+
+        if !targets.is_empty() {
+            relations.push((r#type, targets));
+        }
+    }
+
+    Ok(relations)
+}
+
+/// Checks the source name and encodes the dataset ID into the name of the file storing the dataset.
+fn file_name<'a>(source: &str, id: &'a str) -> Result<Cow<'a, str>, ServerError> {
+    if validate_name(source).is_err() {
+        return Err(ServerError::BadRequest("Invalid source name or dataset ID"));
+    }
+
+    encode_id(id).map_err(|_err| ServerError::BadRequest("Invalid source name or dataset ID"))
+}
+
+fn record_path(source: &str, name: &str) -> String {
+    format!("records/{source}/{name}.zst")
+}
+
+/// Yields the original record from which the dataset was translated, e.g. to debug its mapping.
+fn original(source: &str, id: &str, dir: &Dir) -> Result<Response, ServerError> {
+    let name = file_name(source, id)?;
+
+    let file = match dir.open(record_path(source, &name)) {
+        Ok(file) => file,
+        Err(err) if err.kind() == ErrorKind::NotFound => {
+            return Err(ServerError::NotFound("No original record available"))
+        }
+        Err(err) => return Err(err.into()),
+    };
+
+    let buf = zstd::decode_all(file.into_std())?;
+
+    // Harvesters preserve CKAN packages as JSON and ISO or OAI-PMH records as XML.
+    let content_type = match buf.iter().find(|byte| !byte.is_ascii_whitespace()) {
+        Some(b'{' | b'[') => "application/json",
+        Some(b'<') => "application/xml",
+        _ => "text/plain; charset=utf-8",
+    };
+
+    Ok(([(CONTENT_TYPE, content_type)], buf).into_response())
+}
@@ -0,0 +1,92 @@
+use askama::Template;
+use axum::{
+    extract::{Extension, Form},
+    response::{Redirect, Response},
+};
+use cap_std::fs::Dir;
+use parking_lot::{const_mutex, Mutex};
+use serde::{Deserialize, Serialize};
+use tokio::task::spawn_blocking;
+
+use umwelt_info_core::duplicates::{Candidate, Candidates, Reviews};
+
+use crate::{filters, Accept, Admin, ServerError};
+
+/// Serializes reviews as the queue and the decisions are read and written as a whole.
+static REVIEWS: Mutex<()> = const_mutex(());
+
+/// Lists the likely duplicates queued by the indexer for review.
+pub async fn duplicates(
+    _admin: Admin,
+    accept: Accept,
+    Extension(dir): Extension<&'static Dir>,
+) -> Result<Response, ServerError> {
+    spawn_blocking(move || {
+        let mut candidates = Candidates::read(dir)?;
+
+        let count = candidates.pairs.len();
+        candidates.pairs.truncate(100);
+
+        let page = DuplicatesPage {
+            count,
+            pairs: candidates.pairs,
+        };
+
+        Ok(accept.into_repsonse(page))
+    })
+    .await?
+}
+
+#[derive(Template, Serialize)]
+#[template(path = "duplicates.html")]
+struct DuplicatesPage {
+    /// Number of all queued pairs of which only the highest scoring are listed
+    count: usize,
+    pairs: Vec<Candidate>,
+}
+
+#[derive(Deserialize)]
+pub struct ReviewForm {
+    lhs: String,
+    rhs: String,
+    decision: Decision,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Decision {
+    Confirm,
+    Reject,
+}
+
+/// Records the decision on a pair of likely duplicates which takes effect with the next run of the indexer.
+pub async fn review(
+    _admin: Admin,
+    Extension(dir): Extension<&'static Dir>,
+    Form(form): Form<ReviewForm>,
+) -> Result<Redirect, ServerError> {
+    spawn_blocking(move || -> Result<(), ServerError> {
+        let _guard = REVIEWS.lock();
+
+        let mut reviews = Reviews::read(dir)?;
+        reviews.review(
+            form.lhs.clone(),
+            form.rhs.clone(),
+            matches!(form.decision, Decision::Confirm),
+        );
+        reviews.write(dir)?;
+
+        // The indexer will not queue the pair again, but remove it right away to continue reviewing.
+        let mut candidates = Candidates::read(dir)?;
+        candidates.pairs.retain(|pair| {
+            (pair.lhs != form.lhs || pair.rhs != form.rhs)
+                && (pair.lhs != form.rhs || pair.rhs != form.lhs)
+        });
+        candidates.write(dir)?;
+
+        Ok(())
+    })
+    .await??;
+
+    Ok(Redirect::to("/admin/duplicates"))
+}
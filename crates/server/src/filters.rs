@@ -1,6 +1,6 @@
 use std::time::{Duration, SystemTime};
 
-use askama::Result;
+use askama::{filters::urlencode_strict, Result};
 use time::{macros::format_description, OffsetDateTime};
 
 pub fn system_time(val: &SystemTime) -> Result<String> {
@@ -28,3 +28,26 @@ pub fn duration(val: &Duration) -> Result<String> {
 pub fn percentage(val: &f64) -> Result<String> {
     Ok(format!("{:.0} %", 100.0 * val))
 }
+
+pub fn bytes(val: &u64) -> Result<String> {
+    let val = if *val > 1 << 30 {
+        format!("{:.1} GiB", *val as f64 / (1 << 30) as f64)
+    } else if *val > 1 << 20 {
+        format!("{:.1} MiB", *val as f64 / (1 << 20) as f64)
+    } else {
+        format!("{:.1} KiB", *val as f64 / (1 << 10) as f64)
+    };
+
+    Ok(val)
+}
+
+/// Links to the page of a dataset identified as `source/id`.
+pub fn dataset_link(val: &str) -> Result<String> {
+    let (source, id) = val.split_once('/').unwrap_or((val, ""));
+
+    Ok(format!(
+        "/dataset/{}/{}",
+        urlencode_strict(source)?,
+        urlencode_strict(id)?
+    ))
+}
@@ -0,0 +1,204 @@
+//! Serving a candidate index alongside the current one, e.g. to evaluate changes of the schema before promoting it.
+use std::fs::{remove_dir_all, rename};
+use std::path::{Path, PathBuf};
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+use std::time::Duration;
+
+use anyhow::Result;
+use axum::{extract::Extension, Json};
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use tokio::task::spawn_blocking;
+
+use umwelt_info_core::index::Searcher;
+
+use crate::{Admin, ServerError};
+
+/// The index currently served and optionally a candidate which receives a share of the searches
+pub struct Indexes {
+    data_path: PathBuf,
+    current: RwLock<Arc<Searcher>>,
+    candidate: RwLock<Option<Arc<Searcher>>>,
+    /// Percentage of searches routed to the candidate unless a generation is requested explicitly
+    candidate_share: u64,
+    searches: AtomicU64,
+    comparison: Mutex<Comparison>,
+}
+
+impl Indexes {
+    pub fn open(data_path: &Path, candidate_share: u64) -> Result<Self> {
+        let current = Searcher::open(data_path)?;
+        let candidate = Searcher::open_candidate(data_path)?;
+
+        if candidate.is_some() {
+            tracing::info!(
+                "Routing {}% of searches to the candidate index",
+                candidate_share
+            );
+        }
+
+        Ok(Self {
+            data_path: data_path.to_owned(),
+            current: RwLock::new(Arc::new(current)),
+            candidate: RwLock::new(candidate.map(Arc::new)),
+            candidate_share,
+            searches: AtomicU64::new(0),
+            comparison: Default::default(),
+        })
+    }
+
+    /// Searcher of the current index used by everything except searches routed to the candidate
+    pub fn current(&self) -> Arc<Searcher> {
+        self.current.read().clone()
+    }
+
+    /// Chooses the index serving a search, either the requested one or the candidate for its share of all searches.
+    pub fn route(
+        &self,
+        requested: Option<Generation>,
+    ) -> Result<(Generation, Arc<Searcher>), ServerError> {
+        let candidate = self.candidate.read().clone();
+
+        match (requested, candidate) {
+            (Some(Generation::Candidate), None) => {
+                Err(ServerError::NotFound("There is no candidate index"))
+            }
+            (Some(Generation::Candidate), Some(candidate)) => {
+                Ok((Generation::Candidate, candidate))
+            }
+            (None, Some(candidate))
+                if self.searches.fetch_add(1, Ordering::Relaxed) % 100 < self.candidate_share =>
+            {
+                Ok((Generation::Candidate, candidate))
+            }
+            _ => Ok((Generation::Current, self.current())),
+        }
+    }
+
+    /// Records the outcome of a search to compare the generations.
+    pub fn record(&self, generation: Generation, count: usize, duration: Duration) {
+        let mut comparison = self.comparison.lock();
+
+        let counters = match generation {
+            Generation::Current => &mut comparison.current,
+            Generation::Candidate => &mut comparison.candidate,
+        };
+
+        counters.searches += 1;
+        counters.results += count as u64;
+        counters.duration_micros += duration.as_micros() as u64;
+
+        if count == 0 {
+            counters.empty_searches += 1;
+        }
+    }
+
+    /// Replaces the current index by the candidate, also on disk so that the indexer and restarts use it.
+    ///
+    /// The previous index is kept as `index.old` until the next promotion.
+    fn promote(&self) -> Result<bool> {
+        let mut candidate = self.candidate.write();
+
+        if candidate.is_none() {
+            return Ok(false);
+        }
+
+        let old_path = self.data_path.join("index.old");
+        let _ = remove_dir_all(&old_path);
+
+        rename(self.data_path.join("index"), old_path)?;
+        rename(
+            self.data_path.join("index.candidate"),
+            self.data_path.join("index"),
+        )?;
+
+        // Reopen the index at its new path so that its reader picks up commits of the indexer.
+        *self.current.write() = Arc::new(Searcher::open(&self.data_path)?);
+        *candidate = None;
+
+        *self.comparison.lock() = Default::default();
+
+        Ok(true)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Generation {
+    Current,
+    Candidate,
+}
+
+/// Metrics of the searches served by each generation since the server started or the last promotion
+#[derive(Default, Serialize)]
+pub struct Comparison {
+    current: Counters,
+    candidate: Counters,
+}
+
+#[derive(Default, Clone, Copy, Serialize)]
+struct Counters {
+    searches: u64,
+    /// Searches which did not find any dataset
+    empty_searches: u64,
+    /// Total number of datasets found by all searches
+    results: u64,
+    /// Total duration of all searches
+    duration_micros: u64,
+}
+
+/// Compares the searches served by the current and the candidate index.
+pub async fn compare(
+    _admin: Admin,
+    Extension(indexes): Extension<&'static Indexes>,
+) -> Result<Json<IndexesPage>, ServerError> {
+    spawn_blocking(|| {
+        let current_generation = indexes.current().generation()?;
+
+        let candidate_generation = indexes
+            .candidate
+            .read()
+            .as_ref()
+            .map(|candidate| candidate.generation())
+            .transpose()?;
+
+        let comparison = indexes.comparison.lock();
+
+        Ok(Json(IndexesPage {
+            current_generation,
+            candidate_generation,
+            candidate_share: indexes.candidate_share,
+            current: comparison.current,
+            candidate: comparison.candidate,
+        }))
+    })
+    .await?
+}
+
+#[derive(Serialize)]
+pub struct IndexesPage {
+    /// Operation stamp of the last commit to the current index
+    current_generation: u64,
+    /// Operation stamp of the last commit to the candidate index if there is one
+    candidate_generation: Option<u64>,
+    candidate_share: u64,
+    current: Counters,
+    candidate: Counters,
+}
+
+/// Promotes the candidate index so that it serves all requests.
+pub async fn promote(
+    _admin: Admin,
+    Extension(indexes): Extension<&'static Indexes>,
+) -> Result<&'static str, ServerError> {
+    if !spawn_blocking(|| indexes.promote()).await?? {
+        return Err(ServerError::NotFound("There is no candidate index"));
+    }
+
+    tracing::info!("Promoted the candidate index");
+
+    Ok("Promoted the candidate index")
+}
@@ -0,0 +1,178 @@
+//! HTTP handlers and templates of the server.
+pub mod api;
+pub mod badge;
+pub mod dataset;
+pub mod duplicates;
+pub mod filters;
+pub mod indexes;
+pub mod maintenance;
+pub mod metrics;
+pub mod search;
+pub mod sources;
+pub mod stats;
+pub mod version;
+pub mod well_known;
+
+use std::convert::Infallible;
+use std::env::var;
+
+use anyhow::Error;
+use askama::Template;
+use axum::{
+    async_trait,
+    extract::{FromRequest, RequestParts},
+    http::{
+        header::{ACCEPT, AUTHORIZATION},
+        StatusCode,
+    },
+    response::{Html, IntoResponse, Json, Response},
+};
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy)]
+pub enum Accept {
+    Unspecified,
+    Html,
+    Json,
+}
+
+impl Accept {
+    pub fn into_repsonse<P>(self, page: P) -> Response
+    where
+        P: Template + Serialize,
+    {
+        match self {
+            Accept::Unspecified | Accept::Html => Html(page.render().unwrap()).into_response(),
+            Accept::Json => Json(page).into_response(),
+        }
+    }
+}
+
+#[async_trait]
+impl<B> FromRequest<B> for Accept
+where
+    B: Send,
+{
+    type Rejection = Infallible;
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        if let Some(accept) = req
+            .headers()
+            .get(ACCEPT)
+            .and_then(|header| header.to_str().ok())
+        {
+            if accept.contains("text/html") {
+                return Ok(Self::Html);
+            } else if accept.contains("application/json") {
+                return Ok(Self::Json);
+            }
+        }
+
+        Ok(Self::Unspecified)
+    }
+}
+
+/// Pagination metadata shared by all JSON responses which list their items page by page
+#[derive(Debug, Serialize)]
+pub struct Pagination {
+    pub total_count: usize,
+    /// One-based number of the current page
+    pub page: usize,
+    pub per_page: usize,
+    pub total_pages: usize,
+    /// URL of the next page if there is one
+    pub next: Option<String>,
+    /// URL of the previous page if there is one
+    pub prev: Option<String>,
+}
+
+impl Pagination {
+    /// `url` yields the URL of the given page of the same listing.
+    pub fn new<U>(total_count: usize, page: usize, per_page: usize, url: U) -> Self
+    where
+        U: Fn(usize) -> String,
+    {
+        let total_pages = (total_count + per_page - 1) / per_page;
+
+        let next = (page < total_pages).then(|| url(page + 1));
+        let prev = (page > 1).then(|| url((page - 1).min(total_pages.max(1))));
+
+        Self {
+            total_count,
+            page,
+            per_page,
+            total_pages,
+            next,
+            prev,
+        }
+    }
+}
+
+/// Marks requests authenticated by the header `Authorization: Bearer $ADMIN_TOKEN`
+///
+/// The token is not accepted as a query parameter as those end up in access logs.
+pub struct Admin;
+
+#[async_trait]
+impl<B> FromRequest<B> for Admin
+where
+    B: Send,
+{
+    type Rejection = ServerError;
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let token = req
+            .headers()
+            .get(AUTHORIZATION)
+            .and_then(|header| header.to_str().ok())
+            .and_then(|header| header.strip_prefix("Bearer "));
+
+        match (token, var("ADMIN_TOKEN")) {
+            (Some(token), Ok(admin_token))
+                if !admin_token.is_empty() && constant_time_eq(token, &admin_token) =>
+            {
+                Ok(Self)
+            }
+            _ => Err(ServerError::Unauthorized("Invalid admin token")),
+        }
+    }
+}
+
+/// Compares the given strings without short-circuiting so that the time taken does not reveal the common prefix.
+fn constant_time_eq(lhs: &str, rhs: &str) -> bool {
+    lhs.len() == rhs.len()
+        && lhs
+            .bytes()
+            .zip(rhs.bytes())
+            .fold(0, |acc, (lhs, rhs)| acc | (lhs ^ rhs))
+            == 0
+}
+
+pub enum ServerError {
+    BadRequest(&'static str),
+    Unauthorized(&'static str),
+    NotFound(&'static str),
+    Internal(Error),
+}
+
+impl<E> From<E> for ServerError
+where
+    Error: From<E>,
+{
+    fn from(err: E) -> Self {
+        Self::Internal(Error::from(err))
+    }
+}
+
+impl IntoResponse for ServerError {
+    fn into_response(self) -> Response {
+        match self {
+            Self::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg).into_response(),
+            Self::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, msg).into_response(),
+            Self::NotFound(msg) => (StatusCode::NOT_FOUND, msg).into_response(),
+            Self::Internal(err) => {
+                (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response()
+            }
+        }
+    }
+}
@@ -0,0 +1,102 @@
+//! Maintenance mode which disables selected routes, e.g. search during index migrations
+//!
+//! Maintenance is enabled by creating `$DATA_PATH/maintenance` which lists the prefixes of the disabled routes,
+//! one per line like `/search`. An empty file disables all routes. The file is checked periodically
+//! so that requests only need to consult the cached list of prefixes.
+use std::io::ErrorKind;
+
+use askama::Template;
+use axum::{
+    http::{
+        header::{ACCEPT, RETRY_AFTER},
+        Request, StatusCode,
+    },
+    middleware::Next,
+    response::{Html, IntoResponse, Json, Response},
+};
+use cap_std::fs::Dir;
+use parking_lot::RwLock;
+use serde::Serialize;
+
+/// Seconds after which clients should retry requests to disabled routes
+const RETRY_AFTER_SECS: &str = "600";
+
+/// Prefixes of the disabled routes if maintenance is enabled
+#[derive(Default)]
+pub struct Maintenance {
+    prefixes: RwLock<Option<Vec<String>>>,
+}
+
+impl Maintenance {
+    /// Re-reads the list of disabled routes from `$DATA_PATH/maintenance`.
+    pub fn refresh(&self, dir: &Dir) {
+        let prefixes = match dir.read_to_string("maintenance") {
+            Ok(text) => Some(
+                text.lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty())
+                    .map(ToOwned::to_owned)
+                    .collect(),
+            ),
+            Err(err) if err.kind() == ErrorKind::NotFound => None,
+            Err(err) => {
+                tracing::warn!("Failed to read maintenance file: {:#}", err);
+                return;
+            }
+        };
+
+        *self.prefixes.write() = prefixes;
+    }
+
+    fn disables(&self, path: &str) -> bool {
+        match &*self.prefixes.read() {
+            Some(prefixes) => {
+                prefixes.is_empty() || prefixes.iter().any(|prefix| path.starts_with(&**prefix))
+            }
+            None => false,
+        }
+    }
+}
+
+/// Answers requests to disabled routes with `503 Service Unavailable` instead of passing them on.
+pub async fn maintenance<B>(req: Request<B>, next: Next<B>) -> Response {
+    let disabled = req
+        .extensions()
+        .get::<&'static Maintenance>()
+        .map_or(false, |maintenance| maintenance.disables(req.uri().path()));
+
+    if !disabled {
+        return next.run(req).await;
+    }
+
+    let json = req
+        .headers()
+        .get(ACCEPT)
+        .and_then(|header| header.to_str().ok())
+        .map_or(false, |accept| {
+            !accept.contains("text/html") && accept.contains("application/json")
+        });
+
+    let page = MaintenancePage {
+        message: "umwelt.info is currently being maintained. Please try again later.",
+    };
+
+    let body = if json {
+        Json(page).into_response()
+    } else {
+        Html(page.render().unwrap()).into_response()
+    };
+
+    (
+        StatusCode::SERVICE_UNAVAILABLE,
+        [(RETRY_AFTER, RETRY_AFTER_SECS)],
+        body,
+    )
+        .into_response()
+}
+
+#[derive(Template, Serialize)]
+#[template(path = "maintenance.html")]
+struct MaintenancePage {
+    message: &'static str,
+}
@@ -6,43 +6,59 @@ use cap_std::fs::Dir;
 use hashbrown::HashMap;
 use tokio::task::spawn_blocking;
 
-use crate::{
+use umwelt_info_core::{
     dataset::{License, Tag},
-    metrics::{Harvest as HarvestMetrics, Metrics},
-    server::{filters, stats::Stats, ServerError},
+    metrics::{Harvest as HarvestMetrics, HarvestCounters, HostTraffic, Metrics},
+    stats::Stats,
 };
 
-pub async fn metrics(Extension(dir): Extension<&'static Dir>) -> Result<Html<String>, ServerError> {
-    fn inner(dir: &Dir) -> Result<Html<String>, ServerError> {
+use crate::{filters, stats::AccessGuard, ServerError};
+
+pub async fn metrics(
+    Extension(dir): Extension<&'static Dir>,
+    Extension(access_guard): Extension<AccessGuard>,
+) -> Result<Html<String>, ServerError> {
+    fn inner(dir: &Dir, access_guard: AccessGuard) -> Result<Html<String>, ServerError> {
         let stats = Stats::read(dir)?;
 
-        let mut accesses = stats
+        let accesses = stats
             .accesses
             .into_iter()
-            .map(|(source_name, accesses)| (source_name, accesses.values().sum()))
+            .map(|(source_name, accesses)| (source_name, accesses.values().sum::<u64>()))
             .collect::<Vec<_>>();
 
-        accesses.sort_unstable_by_key(|(_, accesses)| Reverse(*accesses));
+        let sum_accesses =
+            access_guard.publish(accesses.iter().map(|(_, accesses)| accesses).sum());
 
-        let sum_accesses = accesses.iter().map(|(_, accesses)| accesses).sum();
+        let mut accesses = accesses
+            .into_iter()
+            .map(|(source_name, accesses)| (source_name, access_guard.publish(accesses)))
+            .collect::<Vec<_>>();
+
+        accesses.sort_unstable_by_key(|(_, accesses)| Reverse(*accesses));
 
         let metrics = Metrics::read(dir)?;
 
         let mut harvests = metrics.harvests.into_iter().collect::<Vec<_>>();
 
-        harvests.sort_unstable_by_key(|(_, harvest)| Reverse(harvest.failed));
+        harvests.sort_unstable_by_key(|(_, harvest)| {
+            Reverse((harvest.error.is_some(), harvest.counters.failed))
+        });
 
-        let (sum_count, sum_transmitted, sum_failed) = harvests.iter().fold(
-            (0, 0, 0),
-            |(sum_count, sum_transmitted, sum_failed), (_, harvest)| {
-                (
-                    sum_count + harvest.count,
-                    sum_transmitted + harvest.transmitted,
-                    sum_failed + harvest.failed,
-                )
+        let (sum_count, sum_counters) = harvests.iter().fold(
+            (0, HarvestCounters::default()),
+            |(mut sum_count, mut sum_counters), (_, harvest)| {
+                sum_count += harvest.count;
+                sum_counters += harvest.counters;
+
+                (sum_count, sum_counters)
             },
         );
 
+        let mut hosts = metrics.hosts.into_iter().collect::<Vec<_>>();
+
+        hosts.sort_unstable_by_key(|(_, traffic)| Reverse(traffic.requests));
+
         let mut licenses_by_source = metrics
             .licenses
             .iter()
@@ -99,7 +115,35 @@ pub async fn metrics(Extension(dir): Extension<&'static Dir>) -> Result<Html<Str
             .map(|(_, count)| *count)
             .sum();
 
-        let mut tags = metrics.tags.into_iter().collect::<Vec<_>>();
+        let mut other_licenses = licenses
+            .iter()
+            .filter_map(|(license, count)| {
+                license
+                    .normalized_other()
+                    .map(|normalized| (normalized, *count))
+            })
+            .fold(HashMap::new(), |mut other_licenses, (normalized, count)| {
+                *other_licenses.entry(normalized).or_default() += count;
+
+                other_licenses
+            })
+            .into_iter()
+            .collect::<Vec<_>>();
+
+        other_licenses.sort_unstable_by_key(|(_, count)| Reverse(*count));
+
+        let mut tags = metrics
+            .tags
+            .into_iter()
+            .fold(HashMap::new(), |mut tags, (_, tags1)| {
+                for (tag, count) in tags1 {
+                    *tags.entry(tag).or_default() += count;
+                }
+
+                tags
+            })
+            .into_iter()
+            .collect::<Vec<_>>();
 
         tags.sort_unstable_by_key(|(_, count)| Reverse(*count));
 
@@ -114,11 +158,12 @@ pub async fn metrics(Extension(dir): Extension<&'static Dir>) -> Result<Html<Str
             sum_accesses,
             harvests,
             sum_count,
-            sum_transmitted,
-            sum_failed,
+            sum_counters,
+            hosts,
             licenses,
             licenses_by_source,
             sum_other_licenses,
+            other_licenses,
             tags,
             sum_other_tags,
         };
@@ -128,21 +173,23 @@ pub async fn metrics(Extension(dir): Extension<&'static Dir>) -> Result<Html<Str
         Ok(page)
     }
 
-    spawn_blocking(|| inner(dir)).await?
+    spawn_blocking(move || inner(dir, access_guard)).await?
 }
 
 #[derive(Template)]
 #[template(path = "metrics.html")]
 struct MetricsPage {
-    accesses: Vec<(String, u64)>,
-    sum_accesses: u64,
+    /// Published access counts which are suppressed if too small
+    accesses: Vec<(String, Option<u64>)>,
+    sum_accesses: Option<u64>,
     harvests: Vec<(String, HarvestMetrics)>,
     sum_count: usize,
-    sum_transmitted: usize,
-    sum_failed: usize,
+    sum_counters: HarvestCounters,
+    hosts: Vec<(String, HostTraffic)>,
     licenses: Vec<(License, usize)>,
     licenses_by_source: Vec<(String, f64, f64)>,
     sum_other_licenses: usize,
+    other_licenses: Vec<(String, usize)>,
     tags: Vec<(Tag, usize)>,
     sum_other_tags: usize,
 }
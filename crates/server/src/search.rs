@@ -0,0 +1,568 @@
+use std::borrow::Cow;
+use std::time::Instant;
+
+use askama::{filters::urlencode, Template};
+use axum::{
+    extract::{Extension, Json, Query},
+    response::Response,
+};
+use cap_std::fs::Dir;
+use serde::{
+    de::{Deserializer, Error},
+    ser::{SerializeMap, Serializer},
+    Deserialize, Serialize,
+};
+use tantivy::schema::Facet;
+use tokio::task::spawn_blocking;
+
+use umwelt_info_core::{
+    dataset::Dataset,
+    encode_id,
+    index::{FacetRoots, Sort},
+    provenance::provenance_facet,
+    sources::{Sources, Visibility},
+};
+
+use crate::{
+    indexes::{Generation, Indexes},
+    Accept, Admin, Pagination, ServerError,
+};
+
+pub async fn search(
+    Query(params): Query<SearchParams>,
+    admin: Option<Admin>,
+    accept: Accept,
+    Extension(indexes): Extension<&'static Indexes>,
+    Extension(dir): Extension<&'static Dir>,
+) -> Result<Response, ServerError> {
+    let admin = admin.is_some();
+
+    spawn_blocking(move || perform_search(params, admin, accept, indexes, dir)).await?
+}
+
+/// Accepts the same parameters as [`search`] as a JSON body which is easier to construct for complex filters.
+pub async fn search_json(
+    Json(params): Json<SearchParams>,
+    admin: Option<Admin>,
+    accept: Accept,
+    Extension(indexes): Extension<&'static Indexes>,
+    Extension(dir): Extension<&'static Dir>,
+) -> Result<Response, ServerError> {
+    let admin = admin.is_some();
+
+    spawn_blocking(move || perform_search(params, admin, accept, indexes, dir)).await?
+}
+
+fn perform_search(
+    params: SearchParams,
+    admin: bool,
+    accept: Accept,
+    indexes: &Indexes,
+    dir: &Dir,
+) -> Result<Response, ServerError> {
+    if params.page == 0 || params.results_per_page == 0 {
+        return Err(ServerError::BadRequest(
+            "Page and results per page must not be zero",
+        ));
+    }
+
+    if params.results_per_page > 100 {
+        return Err(ServerError::BadRequest(
+            "Results per page must not be larger than 100",
+        ));
+    }
+
+    let fields = params.fields.as_deref().map(parse_fields).transpose()?;
+
+    let roots = FacetRoots {
+        provenances: &params.provenances_root,
+        licenses: &params.licenses_root,
+        availability: &params.availability_root,
+        publishers: &params.publishers_root,
+        themes: &params.themes_root,
+        update_frequencies: &params.update_frequencies_root,
+        reporting_obligations: &params.reporting_obligations_root,
+    };
+
+    // Hidden sources are only searched by administrators.
+    if params.include_hidden && !admin {
+        return Err(ServerError::Unauthorized("Invalid admin token"));
+    }
+
+    let (generation, searcher) = indexes.route(params.index)?;
+
+    let start = Instant::now();
+
+    let results = searcher.search(
+        &params.query,
+        &roots,
+        params.sort,
+        params.results_per_page,
+        (params.page - 1) * params.results_per_page,
+        params.include_hidden,
+    )?;
+
+    indexes.record(generation, results.count, start.elapsed());
+
+    tracing::debug!("Found {} documents", results.count);
+
+    let pagination = Pagination::new(
+        results.count,
+        params.page,
+        params.results_per_page,
+        |page| params.url(page),
+    );
+
+    let pages = pagination.total_pages;
+
+    let provenances = results
+        .provenances
+        .get(params.provenances_root.clone())
+        .collect::<Vec<_>>();
+
+    let licenses = results
+        .licenses
+        .get(params.licenses_root.clone())
+        .collect::<Vec<_>>();
+
+    let availability = results
+        .availability
+        .get(params.availability_root.clone())
+        .collect::<Vec<_>>();
+
+    let publishers = results
+        .publishers
+        .get(params.publishers_root.clone())
+        .collect::<Vec<_>>();
+
+    let themes = results
+        .themes
+        .get(params.themes_root.clone())
+        .collect::<Vec<_>>();
+
+    let update_frequencies = results
+        .update_frequencies
+        .get(params.update_frequencies_root.clone())
+        .collect::<Vec<_>>();
+
+    let reporting_obligations = results
+        .reporting_obligations
+        .get(params.reporting_obligations_root.clone())
+        .collect::<Vec<_>>();
+
+    let sources = Sources::read(dir)?;
+
+    let mut page = SearchPage {
+        params,
+        count: results.count,
+        pages,
+        pagination,
+        results: Vec::new(),
+        provenances,
+        licenses,
+        availability,
+        publishers,
+        themes,
+        update_frequencies,
+        reporting_obligations,
+        sources,
+    };
+
+    let dir = dir.open_dir("datasets")?;
+
+    for doc in results.iter {
+        let (source, id) = doc?;
+
+        let mut dataset = Dataset::read(dir.open_dir(&source)?.open(&*encode_id(&id)?)?)?;
+
+        if let Some(snippet_length) = page.params.snippet_length {
+            for text in [&mut dataset.description, &mut dataset.summary]
+                .into_iter()
+                .flatten()
+            {
+                truncate(text, snippet_length);
+            }
+        }
+
+        page.results.push(SearchResult {
+            source,
+            id,
+            dataset,
+            fields: fields.clone(),
+        });
+    }
+
+    Ok(accept.into_repsonse(page))
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct SearchParams {
+    #[serde(default = "default_query")]
+    query: String,
+    #[serde(deserialize_with = "deserialize_provenance", default = "default_root")]
+    provenances_root: Facet,
+    #[serde(deserialize_with = "deserialize_facet", default = "default_root")]
+    licenses_root: Facet,
+    #[serde(deserialize_with = "deserialize_facet", default = "default_root")]
+    availability_root: Facet,
+    #[serde(deserialize_with = "deserialize_facet", default = "default_root")]
+    publishers_root: Facet,
+    #[serde(deserialize_with = "deserialize_facet", default = "default_root")]
+    themes_root: Facet,
+    #[serde(deserialize_with = "deserialize_facet", default = "default_root")]
+    update_frequencies_root: Facet,
+    #[serde(deserialize_with = "deserialize_facet", default = "default_root")]
+    reporting_obligations_root: Facet,
+    #[serde(default = "default_page")]
+    page: usize,
+    #[serde(default = "default_results_per_page")]
+    results_per_page: usize,
+    #[serde(default)]
+    sort: Sort,
+    /// Comma-separated list of the dataset fields included in JSON results
+    fields: Option<String>,
+    /// Maximum number of characters of the descriptions included in results
+    snippet_length: Option<usize>,
+    /// Includes datasets of hidden sources which is restricted to administrators
+    #[serde(default)]
+    include_hidden: bool,
+    /// Serves the search from the given index generation instead of routing it
+    index: Option<Generation>,
+}
+
+impl SearchParams {
+    /// Yields the URL of the given page of the results of this search.
+    fn url(&self, page: usize) -> String {
+        let sort = match self.sort {
+            Sort::Relevance => "relevance",
+            Sort::FirstSeen => "first_seen",
+            Sort::Modified => "modified",
+        };
+
+        let index = match self.index {
+            Some(Generation::Current) => "&index=current",
+            Some(Generation::Candidate) => "&index=candidate",
+            None => "",
+        };
+
+        format!(
+            "/search?query={}&provenances_root={}&licenses_root={}&availability_root={}&publishers_root={}&themes_root={}&update_frequencies_root={}&reporting_obligations_root={}&sort={}&page={}&results_per_page={}{}",
+            urlencode(&self.query).unwrap(),
+            urlencode(&self.provenances_root).unwrap(),
+            urlencode(&self.licenses_root).unwrap(),
+            urlencode(&self.availability_root).unwrap(),
+            urlencode(&self.publishers_root).unwrap(),
+            urlencode(&self.themes_root).unwrap(),
+            urlencode(&self.update_frequencies_root).unwrap(),
+            urlencode(&self.reporting_obligations_root).unwrap(),
+            sort,
+            page,
+            self.results_per_page,
+            index,
+        )
+    }
+}
+
+fn deserialize_facet<'de, D>(deserializer: D) -> Result<Facet, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let val = Cow::<str>::deserialize(deserializer)?;
+
+    Facet::from_text(&val).map_err(|err| D::Error::custom(err.to_string()))
+}
+
+fn deserialize_provenance<'de, D>(deserializer: D) -> Result<Facet, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let val = Cow::<str>::deserialize(deserializer)?;
+
+    Ok(provenance_facet(&val))
+}
+
+fn default_query() -> String {
+    "*".to_owned()
+}
+
+fn default_root() -> Facet {
+    Facet::root()
+}
+
+fn default_page() -> usize {
+    1
+}
+
+fn default_results_per_page() -> usize {
+    10
+}
+
+#[derive(Template, Serialize)]
+#[template(path = "search.html")]
+struct SearchPage<'a> {
+    params: SearchParams,
+    count: usize,
+    pages: usize,
+    pagination: Pagination,
+    results: Vec<SearchResult>,
+    provenances: Vec<(&'a Facet, u64)>,
+    licenses: Vec<(&'a Facet, u64)>,
+    availability: Vec<(&'a Facet, u64)>,
+    publishers: Vec<(&'a Facet, u64)>,
+    themes: Vec<(&'a Facet, u64)>,
+    update_frequencies: Vec<(&'a Facet, u64)>,
+    reporting_obligations: Vec<(&'a Facet, u64)>,
+    #[serde(skip)]
+    sources: Sources,
+}
+
+impl SearchPage<'_> {
+    fn experimental(&self, source: &str) -> bool {
+        self.sources.visibility(source) == Visibility::Experimental
+    }
+
+    fn pages(&self) -> Vec<usize> {
+        let mut pages = Vec::new();
+
+        pages.extend(1..=self.pages.min(5));
+
+        let mut extend = |new_pages| {
+            for new_page in new_pages {
+                let last_page = *pages.last().unwrap();
+
+                if last_page < new_page {
+                    if last_page + 1 != new_page {
+                        pages.push(0);
+                    }
+
+                    pages.push(new_page);
+                }
+            }
+        };
+
+        if self.params.page > 2 {
+            extend(self.params.page - 2..=self.pages.min(self.params.page + 2))
+        }
+
+        if self.pages > 2 {
+            extend(self.pages - 2..=self.pages);
+        }
+
+        pages
+    }
+}
+
+struct SearchResult {
+    source: String,
+    id: String,
+    dataset: Dataset,
+    fields: Option<Vec<&'static str>>,
+}
+
+impl Serialize for SearchResult {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(3))?;
+
+        map.serialize_entry("source", &self.source)?;
+        map.serialize_entry("id", &self.id)?;
+
+        match &self.fields {
+            Some(fields) => map.serialize_entry(
+                "dataset",
+                &SelectedFields {
+                    dataset: &self.dataset,
+                    fields,
+                },
+            )?,
+            None => map.serialize_entry("dataset", &self.dataset)?,
+        }
+
+        map.end()
+    }
+}
+
+/// Dataset fields which can be selected via the `fields` parameter
+const FIELDS: &[&str] = &[
+    "title",
+    "description",
+    "summary",
+    "comment",
+    "provenance",
+    "publisher",
+    "license",
+    "legal_basis",
+    "reporting_obligations",
+    "contacts",
+    "tags",
+    "themes",
+    "region",
+    "language",
+    "bbox",
+    "time_range",
+    "issued",
+    "modified",
+    "update_frequency",
+    "last_checked",
+    "first_seen",
+    "identifiers",
+    "relations",
+    "source_url",
+    "resources",
+];
+
+fn parse_fields(val: &str) -> Result<Vec<&'static str>, ServerError> {
+    val.split(',')
+        .map(str::trim)
+        .filter(|field| !field.is_empty())
+        .map(|field| {
+            FIELDS
+                .iter()
+                .find(|allowed| **allowed == field)
+                .copied()
+                .ok_or(ServerError::BadRequest("Unknown dataset field requested"))
+        })
+        .collect()
+}
+
+struct SelectedFields<'a> {
+    dataset: &'a Dataset,
+    fields: &'a [&'static str],
+}
+
+impl Serialize for SelectedFields<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let dataset = self.dataset;
+
+        let mut map = serializer.serialize_map(Some(self.fields.len()))?;
+
+        for &field in self.fields {
+            match field {
+                "title" => map.serialize_entry(field, &dataset.title)?,
+                "description" => map.serialize_entry(field, &dataset.description)?,
+                "summary" => map.serialize_entry(field, &dataset.summary)?,
+                "comment" => map.serialize_entry(field, &dataset.comment)?,
+                "provenance" => map.serialize_entry(field, &dataset.provenance)?,
+                "publisher" => map.serialize_entry(field, &dataset.publisher)?,
+                "license" => map.serialize_entry(field, &dataset.license)?,
+                "legal_basis" => map.serialize_entry(field, &dataset.legal_basis)?,
+                "reporting_obligations" => {
+                    map.serialize_entry(field, &dataset.reporting_obligations)?
+                }
+                "contacts" => map.serialize_entry(field, &dataset.contacts)?,
+                "tags" => map.serialize_entry(field, &dataset.tags)?,
+                "themes" => map.serialize_entry(field, &dataset.themes)?,
+                "region" => map.serialize_entry(field, &dataset.region)?,
+                "language" => map.serialize_entry(field, &dataset.language)?,
+                "bbox" => map.serialize_entry(field, &dataset.bbox)?,
+                "time_range" => map.serialize_entry(field, &dataset.time_range)?,
+                "issued" => map.serialize_entry(field, &dataset.issued)?,
+                "modified" => map.serialize_entry(field, &dataset.modified)?,
+                "update_frequency" => map.serialize_entry(field, &dataset.update_frequency)?,
+                "last_checked" => map.serialize_entry(field, &dataset.last_checked)?,
+                "first_seen" => map.serialize_entry(field, &dataset.first_seen)?,
+                "identifiers" => map.serialize_entry(field, &dataset.identifiers)?,
+                "relations" => map.serialize_entry(field, &dataset.relations)?,
+                "source_url" => map.serialize_entry(field, &dataset.source_url)?,
+                "resources" => map.serialize_entry(field, &dataset.resources)?,
+                _ => unreachable!(),
+            }
+        }
+
+        map.end()
+    }
+}
+
+/// Cuts the text after at most `len` characters, preferably at a word boundary, and marks it using an ellipsis.
+fn truncate(text: &mut String, len: usize) {
+    let end = match text.char_indices().nth(len) {
+        Some((end, _)) => end,
+        None => return,
+    };
+
+    let end = text[..end]
+        .rfind(char::is_whitespace)
+        .filter(|pos| *pos != 0)
+        .unwrap_or(end);
+
+    text.truncate(end);
+    text.push('…');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::env::temp_dir;
+    use std::fs::{create_dir_all, remove_dir_all, write};
+    use std::process;
+
+    use axum::{http::StatusCode, response::IntoResponse};
+    use cap_std::ambient_authority;
+    use serde_json::from_str;
+
+    use umwelt_info_core::{dataset::License, index::Indexer};
+
+    #[test]
+    fn datasets_with_slashes_in_their_ids_are_found() {
+        let data_path = temp_dir().join(format!("umwelt-info-search-{}", process::id()));
+
+        let dataset = Dataset {
+            title: "Pegel".to_owned(),
+            description: None,
+            summary: None,
+            comment: None,
+            provenance: Default::default(),
+            publisher: None,
+            license: License::Unknown,
+            legal_basis: Vec::new(),
+            reporting_obligations: Vec::new(),
+            contacts: Vec::new(),
+            tags: Vec::new(),
+            themes: Vec::new(),
+            region: None,
+            language: None,
+            bbox: None,
+            time_range: None,
+            issued: None,
+            modified: None,
+            update_frequency: None,
+            last_checked: None,
+            first_seen: None,
+            identifiers: Default::default(),
+            relations: Vec::new(),
+            source_url: String::new(),
+            resources: Default::default(),
+        };
+
+        let source_path = data_path.join("datasets").join("a");
+        create_dir_all(&source_path).unwrap();
+        write(source_path.join("b%2Fc"), dataset.encode().unwrap()).unwrap();
+
+        let indexer = Indexer::start(&data_path, &[]).unwrap();
+        indexer
+            .add_document("a".to_owned(), "b/c".to_owned(), dataset, 0, None, None)
+            .unwrap();
+        indexer.commit().unwrap();
+
+        let indexes = Indexes::open(&data_path, 0).unwrap();
+        let dir = Dir::open_ambient_dir(&data_path, ambient_authority()).unwrap();
+
+        let params = from_str::<SearchParams>(r#"{"query": "pegel"}"#).unwrap();
+
+        let response = perform_search(params, false, Accept::Json, &indexes, &dir).into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let params = from_str::<SearchParams>(r#"{"include_hidden": true}"#).unwrap();
+
+        let response = perform_search(params, false, Accept::Json, &indexes, &dir).into_response();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        remove_dir_all(data_path).unwrap();
+    }
+}
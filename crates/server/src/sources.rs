@@ -0,0 +1,35 @@
+use askama::Template;
+use axum::{extract::Extension, response::Response};
+use cap_std::fs::Dir;
+use serde::Serialize;
+use tokio::task::spawn_blocking;
+
+use umwelt_info_core::sources::{Sources, Visibility};
+
+use crate::{Accept, ServerError};
+
+pub async fn sources(
+    accept: Accept,
+    Extension(dir): Extension<&'static Dir>,
+) -> Result<Response, ServerError> {
+    fn inner(accept: Accept, dir: &Dir) -> Result<Response, ServerError> {
+        let mut sources = Sources::read(dir)?;
+
+        sources
+            .sources
+            .retain(|source| source.visibility != Visibility::Hidden);
+
+        let page = SourcesPage { sources };
+
+        Ok(accept.into_repsonse(page))
+    }
+
+    spawn_blocking(move || inner(accept, dir)).await?
+}
+
+#[derive(Template, Serialize)]
+#[template(path = "sources.html")]
+struct SourcesPage {
+    #[serde(flatten)]
+    sources: Sources,
+}
@@ -0,0 +1,38 @@
+//! Publication of access statistics without revealing the behaviour of individual users
+//!
+//! Small counts, e.g. of datasets of a small agency, can be traced back to single users.
+//! Hence counts below a threshold are suppressed and all others are rounded before they are published
+//! while the exact values are kept in `$DATA_PATH/stats`.
+
+/// Threshold and granularity applied to access counts before they are published
+#[derive(Debug, Clone, Copy)]
+pub struct AccessGuard {
+    min_count: u64,
+    granularity: u64,
+}
+
+impl AccessGuard {
+    /// Counts below `min_count` are suppressed and all others are rounded to the nearest multiple of `granularity`.
+    ///
+    /// Setting both to zero publishes exact counts.
+    pub fn new(min_count: u64, granularity: u64) -> Self {
+        Self {
+            min_count,
+            granularity,
+        }
+    }
+
+    pub fn publish(&self, accesses: u64) -> Option<u64> {
+        if accesses < self.min_count {
+            return None;
+        }
+
+        if self.granularity <= 1 {
+            return Some(accesses);
+        }
+
+        let rounded = (accesses + self.granularity / 2) / self.granularity * self.granularity;
+
+        Some(rounded)
+    }
+}
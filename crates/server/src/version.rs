@@ -0,0 +1,51 @@
+use std::time::UNIX_EPOCH;
+
+use axum::{extract::Extension, Json};
+use cap_std::fs::Dir;
+use serde::Serialize;
+use tokio::task::spawn_blocking;
+
+use umwelt_info_core::index::Searcher;
+
+use crate::{indexes::Indexes, ServerError};
+
+/// Identifies the code which produced a response, sent as the `X-UmweltInfo-Version` header.
+pub const VERSION: &str = concat!(env!("CARGO_PKG_VERSION"), "+", env!("GIT_COMMIT"));
+
+pub async fn version(
+    Extension(indexes): Extension<&'static Indexes>,
+    Extension(dir): Extension<&'static Dir>,
+) -> Result<Json<Version>, ServerError> {
+    fn inner(searcher: &Searcher, dir: &Dir) -> Result<Json<Version>, ServerError> {
+        let index_generation = searcher.generation()?;
+
+        let datasets_snapshot = dir
+            .metadata("datasets")?
+            .modified()?
+            .into_std()
+            .duration_since(UNIX_EPOCH)?
+            .as_secs();
+
+        Ok(Json(Version {
+            version: env!("CARGO_PKG_VERSION"),
+            git_commit: env!("GIT_COMMIT"),
+            build_timestamp: env!("BUILD_TIMESTAMP").parse().unwrap(),
+            index_generation,
+            datasets_snapshot,
+        }))
+    }
+
+    spawn_blocking(|| inner(&indexes.current(), dir)).await?
+}
+
+#[derive(Serialize)]
+pub struct Version {
+    version: &'static str,
+    git_commit: &'static str,
+    /// Seconds since the Unix epoch
+    build_timestamp: u64,
+    /// Operation stamp of the last commit to the search index
+    index_generation: u64,
+    /// Modification time of the datasets directory in seconds since the Unix epoch
+    datasets_snapshot: u64,
+}
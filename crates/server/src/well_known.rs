@@ -0,0 +1,54 @@
+//! Well-known URIs as defined by RFC 8615 which let automated clients discover our entry points
+use std::io::ErrorKind;
+
+use axum::{
+    extract::Extension,
+    http::header::CONTENT_TYPE,
+    response::{IntoResponse, Json, Response},
+};
+use cap_std::fs::Dir;
+use serde::Serialize;
+use tokio::task::spawn_blocking;
+
+use crate::ServerError;
+
+/// Serves `$DATA_PATH/security.txt` as defined by RFC 9116 if the operator provided it.
+pub async fn security_txt(
+    Extension(dir): Extension<&'static Dir>,
+) -> Result<Response, ServerError> {
+    fn inner(dir: &Dir) -> Result<Response, ServerError> {
+        let text = match dir.read_to_string("security.txt") {
+            Ok(text) => text,
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                return Err(ServerError::NotFound("No security.txt configured"))
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        Ok(([(CONTENT_TYPE, "text/plain; charset=utf-8")], text).into_response())
+    }
+
+    spawn_blocking(|| inner(dir)).await?
+}
+
+/// Points aggregators to the machine-readable representations of our catalog.
+pub async fn data_catalog() -> Json<DataCatalog> {
+    Json(DataCatalog {
+        api: "/api/v1",
+        search: "/search",
+        sources: "/sources",
+        formats: &["application/json"],
+    })
+}
+
+#[derive(Serialize)]
+pub struct DataCatalog {
+    /// Entry point linking to all API routes
+    api: &'static str,
+    /// Search over all datasets, accepting the same parameters via `GET` and `POST`
+    search: &'static str,
+    /// Harvested sources of the catalog
+    sources: &'static str,
+    /// Media types to request via `Accept`
+    formats: &'static [&'static str],
+}
@@ -1,19 +1,21 @@
+use std::collections::HashMap;
+use std::env::{args, current_exe};
+use std::mem::take;
+use std::process::Command;
 use std::sync::Arc;
 use std::time::SystemTime;
 
-use anyhow::{Context, Result};
+use anyhow::{anyhow, ensure, Context, Result};
 use cap_std::{ambient_authority, fs::Dir};
 use parking_lot::Mutex;
-use tokio::spawn;
+use time::OffsetDateTime;
+use tokio::{spawn, task::spawn_blocking, time::sleep};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
-use umwelt_info::{
-    data_path_from_env,
-    harvester::{
-        ckan, client::Client, csw, doris_bfs, geo_network_q, smart_finder, wasser_de, Config,
-        Source, Type,
-    },
-    metrics::Metrics,
+use umwelt_info_core::{data_path_from_env, decode_id, encode_id, metrics::Metrics};
+use umwelt_info_harvester::{
+    carry_over, client::Client, landing_page, resource_probe, schedule::Schedule, validation,
+    Config, Source, Type,
 };
 
 #[tokio::main]
@@ -23,34 +25,105 @@ async fn main() -> Result<()> {
         .with(tracing_subscriber::fmt::layer())
         .init();
 
+    let selection = Selection::parse_args()?;
+
     let data_path = data_path_from_env();
 
     let dir = Dir::open_ambient_dir(&data_path, ambient_authority())?;
 
-    let config = Config::read(&dir)?;
+    if selection.daemon {
+        daemon(&dir, selection).await
+    } else {
+        run(&dir, &selection).await
+    }
+}
+
+/// Harvests the selected sources once, keeping the datasets of all other sources.
+async fn run(dir: &Dir, selection: &Selection) -> Result<()> {
+    let config = Config::read(dir)?;
+
+    let sources = config.sources();
+
+    let client = Client::start(dir, config.hosts)?;
+
+    for name in &selection.names {
+        ensure!(
+            config.sources.iter().any(|source| &source.name == name),
+            "Unknown source {}",
+            name
+        );
+    }
 
-    let count = config.sources.len();
+    let (active_sources, inactive_sources) = config
+        .sources
+        .into_iter()
+        .partition::<Vec<_>, _>(|source| selection.contains(source));
+
+    let count = active_sources.len();
     tracing::info!("Harvesting {} sources", count);
 
-    let metrics = Arc::new(Mutex::new(Metrics::default()));
+    let harvested = active_sources
+        .iter()
+        .map(|source| source.name.clone())
+        .collect::<Vec<_>>();
+
+    let mut previous_metrics = Metrics::read(dir)?;
 
-    let client = Client::start(&dir)?;
+    // Only the start of the last successful harvests is carried over for delta harvesting,
+    // except for inactive sources which keep their previous harvest altogether.
+    let mut metrics = Metrics {
+        last_success: previous_metrics.last_success,
+        ..Default::default()
+    };
+
+    for source in &inactive_sources {
+        if let Some(harvest) = previous_metrics.harvests.remove(&source.name) {
+            metrics.harvests.insert(source.name.clone(), harvest);
+        }
+    }
+
+    let metrics = Arc::new(Mutex::new(metrics));
 
     let _ = dir.remove_dir_all("datasets.new");
     dir.create_dir("datasets.new")?;
 
     {
+        let previous_dir = dir_if_exists(dir, "datasets")?.map(Arc::new);
+
         let dir = Arc::new(dir.open_dir("datasets.new")?);
 
-        let tasks = config
-            .sources
+        if let Some(previous_dir) = &previous_dir {
+            for source in &inactive_sources {
+                if let Some(previous_dir) = dir_if_exists(previous_dir, &source.name)? {
+                    dir.create_dir(&source.name)?;
+
+                    let kept = keep_datasets(&previous_dir, &dir.open_dir(&source.name)?)?;
+
+                    tracing::debug!("Kept {} datasets of inactive source {}", kept, source.name);
+                }
+            }
+        }
+
+        let tasks = active_sources
             .into_iter()
             .map(|source| {
                 let dir = dir.clone();
+                let previous_dir = previous_dir.clone();
                 let client = client.clone();
                 let metrics = metrics.clone();
+                let check_links = selection.check_links;
 
-                spawn(async move { harvest(&dir, &client, &metrics, source).await })
+                spawn(async move {
+                    harvest(
+                        &dir,
+                        previous_dir.as_deref(),
+                        &client,
+                        &metrics,
+                        source,
+                        check_links,
+                    )
+                    .await
+                })
             })
             .collect::<Vec<_>>();
 
@@ -71,53 +144,392 @@ async fn main() -> Result<()> {
 
     if dir.exists("datasets") {
         let _ = dir.remove_dir_all("datasets.old");
-        dir.rename("datasets", &dir, "datasets.old")?;
-        dir.rename("datasets.new", &dir, "datasets")?;
+        dir.rename("datasets", dir, "datasets.old")?;
+        dir.rename("datasets.new", dir, "datasets")?;
     } else {
-        dir.rename("datasets.new", &dir, "datasets")?;
+        dir.rename("datasets.new", dir, "datasets")?;
+    }
+
+    let mut metrics = Arc::try_unwrap(metrics).unwrap().into_inner();
+
+    metrics.hosts = client.traffic();
+
+    if let Err(err) = record_diffs(dir, &mut metrics, &harvested) {
+        tracing::error!(
+            "Failed to compare datasets with previous harvest: {:#}",
+            err
+        );
     }
 
-    Arc::try_unwrap(metrics).unwrap().into_inner().write(&dir)?;
+    metrics.write(dir)?;
+
+    sources.write(dir)?;
 
     Ok(())
 }
 
-#[tracing::instrument(skip(dir, client, metrics))]
+/// Harvests each selected source whenever its `schedule` is due and reindexes them after each cycle.
+async fn daemon(dir: &Dir, selection: Selection) -> Result<()> {
+    let mut next_runs = HashMap::<String, (Schedule, OffsetDateTime)>::new();
+
+    loop {
+        // The configuration is read again for each cycle to pick up changed schedules.
+        let config = Config::read(dir)?;
+
+        let now = OffsetDateTime::now_utc();
+
+        let mut previous_runs = take(&mut next_runs);
+
+        for source in config.sources {
+            if !selection.contains(&source) {
+                continue;
+            }
+
+            let Some(schedule) = source.schedule else {
+                continue;
+            };
+
+            let next_run = match previous_runs.remove(&source.name) {
+                Some((previous_schedule, next_run)) if previous_schedule == schedule => next_run,
+                _ => match schedule.next_after(now) {
+                    Some(next_run) => next_run,
+                    None => {
+                        tracing::warn!("Schedule of source {} is never due", source.name);
+                        continue;
+                    }
+                },
+            };
+
+            next_runs.insert(source.name, (schedule, next_run));
+        }
+
+        let Some(next_run) = next_runs.values().map(|(_, next_run)| *next_run).min() else {
+            return Err(anyhow!("None of the selected sources has a schedule"));
+        };
+
+        // Wake up at least hourly to pick up changes of the configuration.
+        let delay = (next_run - OffsetDateTime::now_utc()).min(time::Duration::HOUR);
+
+        if delay.is_positive() {
+            sleep(delay.try_into()?).await;
+        }
+
+        let now = OffsetDateTime::now_utc();
+
+        let due = next_runs
+            .iter()
+            .filter(|(_, (_, next_run))| *next_run <= now)
+            .map(|(name, _)| name.clone())
+            .collect::<Vec<_>>();
+
+        if due.is_empty() {
+            continue;
+        }
+
+        for name in &due {
+            next_runs.remove(name);
+        }
+
+        tracing::info!("Harvesting due sources {}", due.join(", "));
+
+        let selection = Selection {
+            names: due,
+            check_links: selection.check_links,
+            ..Default::default()
+        };
+
+        if let Err(err) = run(dir, &selection).await {
+            tracing::error!("Failed to harvest due sources: {:#}", err);
+            continue;
+        }
+
+        if let Err(err) = reindex(&selection.names).await {
+            tracing::error!("Failed to reindex harvested sources: {:#}", err);
+        }
+    }
+}
+
+/// Invokes the indexer installed next to this binary, restricted to the harvested sources.
+async fn reindex(names: &[String]) -> Result<()> {
+    let mut command = Command::new(current_exe()?.with_file_name("indexer"));
+
+    for name in names {
+        command.arg("--source").arg(name);
+    }
+
+    let status = spawn_blocking(move || command.status()).await??;
+
+    ensure!(status.success(), "Indexer failed with {}", status);
+
+    Ok(())
+}
+
+#[tracing::instrument(skip(dir, previous_dir, client, metrics))]
 async fn harvest(
     dir: &Dir,
+    previous_dir: Option<&Dir>,
     client: &Client,
     metrics: &Mutex<Metrics>,
     source: Source,
+    check_links: bool,
 ) -> Result<()> {
     tracing::debug!("Harvesting source {}", source.name);
 
+    let client = &client.for_source(&source)?;
+
     dir.create_dir(&source.name)?;
     let dir = dir.open_dir(&source.name)?;
 
+    let previous_dir = match previous_dir {
+        Some(previous_dir) => dir_if_exists(previous_dir, &source.name)?,
+        None => None,
+    };
+
+    let harvester = source.r#type.harvester();
+
+    // Delta harvests are only possible if the datasets of the previous run can be kept.
+    let since = previous_dir
+        .as_ref()
+        .filter(|_| source.delta)
+        .and_then(|_| metrics.lock().last_success.get(&source.name).copied());
+
+    let since = since.filter(|_| {
+        let supported = harvester.supports_delta();
+
+        if !supported {
+            tracing::warn!("Delta harvesting is not supported by this type of source");
+        }
+
+        supported
+    });
+
     let start = SystemTime::now();
 
-    let res = match source.r#type {
-        Type::Ckan => ckan::harvest(&dir, client, &source).await,
-        Type::Csw => csw::harvest(&dir, client, &source).await,
-        Type::WasserDe => wasser_de::harvest(&dir, client, &source).await,
-        Type::GeoNetworkQ => geo_network_q::harvest(&dir, client, &source).await,
-        Type::DorisBfs => doris_bfs::harvest(&dir, client, &source).await,
-        Type::SmartFinder => smart_finder::harvest(&dir, client, &source).await,
+    let res = harvester.harvest(&dir, client, &source, since).await;
+
+    let (count, mut counters) = match res {
+        Ok(res) => res,
+        Err(err) => {
+            let duration = start.elapsed()?;
+            metrics.lock().record_harvest(
+                source.name.clone(),
+                start,
+                duration,
+                0,
+                Default::default(),
+                Some(format!("{:#}", err)),
+            );
+
+            return Err(err).with_context(|| format!("Failed to harvest source {}", source.name));
+        }
     };
 
-    let (count, transmitted, failed) =
-        res.with_context(|| format!("Failed to harvest source {}", source.name))?;
+    counters.quarantined = client.quarantined();
+
+    if let Err(err) = landing_page::enrich(&dir, client, &source).await {
+        tracing::error!("Failed to enrich datasets using landing pages: {:#}", err);
+    }
+
+    if let Err(err) = resource_probe::enrich(&dir, client, &source, check_links).await {
+        tracing::error!("Failed to probe resources: {:#}", err);
+    }
 
-    if failed != 0 {
+    match carry_over(previous_dir.as_ref(), &dir, start).await {
+        Ok(unchanged) => tracing::debug!("Linked {} unchanged datasets", unchanged),
+        Err(err) => tracing::error!("Failed to carry over datasets: {:#}", err),
+    }
+
+    if since.is_some() {
+        counters.kept = keep_datasets(previous_dir.as_ref().unwrap(), &dir)?;
+
+        tracing::info!("Kept {} unmodified datasets", counters.kept);
+    }
+
+    match client.prune_records(&dir) {
+        Ok(pruned) => tracing::debug!("Pruned {} records of removed datasets", pruned),
+        Err(err) => tracing::error!("Failed to prune records: {:#}", err),
+    }
+
+    if counters.failed != 0 {
         tracing::error!(
-            "Failed to harvest {failed} out of {count} datasets ({transmitted} were transmitted)"
+            "Failed to harvest {} out of {} datasets ({} were transmitted)",
+            counters.failed,
+            count,
+            counters.fetched
         );
     }
 
+    let findings = match validation::report(&source, &dir) {
+        Ok(findings) => findings,
+        Err(err) => {
+            tracing::error!("Failed to report validation findings: {:#}", err);
+
+            Vec::new()
+        }
+    };
+
     let duration = start.elapsed()?;
-    metrics
-        .lock()
-        .record_harvest(source.name, start, duration, count, transmitted, failed);
+    let mut metrics = metrics.lock();
+    metrics.record_harvest(source.name.clone(), start, duration, count, counters, None);
+    metrics.record_findings(&source.name, findings);
 
     Ok(())
 }
+
+/// Restricts harvesting to the sources given via `--source NAME` and `--type TYPE`.
+///
+/// Both arguments can be repeated and if none are given, all sources are harvested.
+/// Additionally, `--check-links` enables checking the resources of all selected sources
+/// and `--daemon` harvests them whenever their schedules are due.
+#[derive(Default)]
+struct Selection {
+    names: Vec<String>,
+    types: Vec<Type>,
+    check_links: bool,
+    daemon: bool,
+}
+
+impl Selection {
+    fn parse_args() -> Result<Self> {
+        let mut val = Self::default();
+
+        let mut args = args().skip(1);
+
+        while let Some(arg) = args.next() {
+            let mut param = || {
+                args.next()
+                    .ok_or_else(|| anyhow!("Missing value of {}", arg))
+            };
+
+            match &*arg {
+                "--source" => val.names.push(param()?),
+                "--type" => val.types.push(param()?.parse()?),
+                "--check-links" => val.check_links = true,
+                "--daemon" => val.daemon = true,
+                _ => return Err(anyhow!("Unknown argument {}", arg)),
+            }
+        }
+
+        Ok(val)
+    }
+
+    fn contains(&self, source: &Source) -> bool {
+        (self.names.is_empty() || self.names.contains(&source.name))
+            && (self.types.is_empty() || self.types.contains(&source.r#type))
+    }
+}
+
+/// Compares the datasets of each harvested source against those of the previous run.
+///
+/// Inactive sources keep the counters of their last harvest as their datasets were merely carried over.
+fn record_diffs(dir: &Dir, metrics: &mut Metrics, harvested: &[String]) -> Result<()> {
+    let new_dir = dir.open_dir("datasets")?;
+    let old_dir = dir_if_exists(dir, "datasets.old")?;
+
+    for (source_name, harvest) in &mut metrics.harvests {
+        if !harvested.contains(source_name) {
+            continue;
+        }
+
+        let new_dir = match dir_if_exists(&new_dir, source_name)? {
+            Some(new_dir) => new_dir,
+            None => continue,
+        };
+
+        let old_dir = match &old_dir {
+            Some(old_dir) => dir_if_exists(old_dir, source_name)?,
+            None => None,
+        };
+
+        let counters = &mut harvest.counters;
+
+        counters.added = 0;
+        counters.removed = 0;
+        counters.changed = 0;
+
+        for entry in new_dir.entries()? {
+            let id = entry?.file_name();
+
+            match &old_dir {
+                Some(old_dir) if old_dir.exists(&id) => {
+                    if new_dir.read(&id)? != old_dir.read(&id)? {
+                        counters.changed += 1;
+                    }
+                }
+                _ => counters.added += 1,
+            }
+        }
+
+        let mut previous = 0;
+
+        if let Some(old_dir) = &old_dir {
+            for entry in old_dir.entries()? {
+                let id = entry?.file_name();
+
+                previous += 1;
+
+                if !new_dir.exists(&id) {
+                    counters.removed += 1;
+                }
+            }
+        }
+
+        if counters.removed * 2 > previous {
+            tracing::warn!(
+                "Source {} dropped {} datasets, i.e. more than half of its previous content",
+                source_name,
+                counters.removed
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Links all datasets from the previous run which were not written by the current one.
+///
+/// This must happen after any enrichment as the linked files are shared with the previous run.
+fn keep_datasets(previous_dir: &Dir, dir: &Dir) -> Result<usize> {
+    let mut kept = 0;
+
+    for entry in previous_dir.entries()? {
+        let name = entry?.file_name();
+
+        // Datasets stored before IDs were percent-encoded are kept under their encoded name.
+        // Names which do not round-trip are taken to be verbatim IDs.
+        let id = name.to_string_lossy();
+        let decoded = decode_id(&id);
+
+        let new_name = match encode_id(&decoded) {
+            Ok(new_name) if new_name == id => new_name,
+            _ => match encode_id(&id) {
+                Ok(new_name) => {
+                    tracing::debug!("Renaming dataset {id} to {new_name}");
+
+                    new_name
+                }
+                Err(err) => {
+                    tracing::warn!("Not keeping dataset: {:#}", err);
+
+                    continue;
+                }
+            },
+        };
+
+        if !dir.exists(&*new_name) {
+            previous_dir.hard_link(&name, dir, &*new_name)?;
+
+            kept += 1;
+        }
+    }
+
+    Ok(kept)
+}
+
+fn dir_if_exists(dir: &Dir, name: &str) -> Result<Option<Dir>> {
+    if dir.exists(name) {
+        Ok(Some(dir.open_dir(name)?))
+    } else {
+        Ok(None)
+    }
+}
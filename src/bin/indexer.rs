@@ -1,11 +1,25 @@
-use anyhow::Result;
+use std::collections::HashMap;
+use std::env::{args, var_os};
+use std::fmt::Write as _;
+use std::fs::{rename, write};
+use std::path::Path;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, ensure, Context, Result};
 use cap_std::{ambient_authority, fs::Dir};
 use parking_lot::Mutex;
 use rayon::iter::{ParallelBridge, ParallelIterator};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
-use umwelt_info::{
-    data_path_from_env, dataset::Dataset, index::Indexer, metrics::Metrics, server::stats::Stats,
+use umwelt_info_core::{
+    data_path_from_env,
+    dataset::Dataset,
+    decode_id,
+    duplicates::{Canonical, Duplicates, Reviews},
+    index::{Boilerplate, Indexer},
+    metrics::Metrics,
+    sources::Sources,
+    stats::Stats,
 };
 
 fn main() -> Result<()> {
@@ -14,25 +28,307 @@ fn main() -> Result<()> {
         .with(tracing_subscriber::fmt::layer())
         .init();
 
+    let start = Instant::now();
+
+    let res = run();
+
+    // The textfile is consumed by the textfile collector of the Prometheus node exporter.
+    if let Some(path) = var_os("METRICS_TEXTFILE") {
+        let datasets = res.as_ref().ok();
+
+        if let Err(err) = write_textfile(Path::new(&path), start.elapsed(), datasets) {
+            tracing::error!("Failed to write metrics textfile: {:#}", err);
+        }
+    }
+
+    res.map(|_datasets| ())
+}
+
+/// Yields the number of datasets indexed per source.
+fn run() -> Result<Vec<(String, usize)>> {
+    let Args {
+        source_names,
+        candidate,
+    } = parse_args()?;
+
     let data_path = data_path_from_env();
 
-    let indexer = Indexer::start(&data_path)?;
+    let indexer = if candidate {
+        tracing::info!("Building candidate index");
+
+        Indexer::start_candidate(&data_path)?
+    } else {
+        Indexer::start(&data_path, &source_names)?
+    };
 
     let dir = Dir::open_ambient_dir(data_path, ambient_authority())?;
 
     let stats = Stats::read(&dir)?;
 
+    let sources = Sources::read(&dir)?;
+
     let mut metrics = Mutex::new(Metrics::read(&dir)?);
 
-    metrics.get_mut().clear_datasets();
+    let datasets = dir.open_dir("datasets")?;
+
+    // Duplicates are detected across all sources even if only some of them are reindexed.
+    let scan = scan_datasets(&dir, &datasets, &sources, &source_names)?;
+
+    if source_names.is_empty() {
+        metrics.get_mut().clear_datasets();
+
+        datasets
+            .entries()?
+            .par_bridge()
+            .try_for_each(|source| -> Result<()> {
+                let source = source?;
+                let source_name = source.file_name().into_string().unwrap();
+
+                index_source(
+                    &indexer,
+                    &metrics,
+                    &stats,
+                    &sources,
+                    &scan,
+                    source_name,
+                    source.open_dir()?,
+                )
+            })?;
+    } else {
+        tracing::info!("Indexing only sources {}", source_names.join(", "));
+
+        for source_name in &source_names {
+            metrics.get_mut().clear_source(source_name);
+
+            let source = datasets
+                .open_dir(source_name)
+                .with_context(|| format!("Failed to open datasets of source {}", source_name))?;
+
+            index_source(
+                &indexer,
+                &metrics,
+                &stats,
+                &sources,
+                &scan,
+                source_name.clone(),
+                source,
+            )?;
+        }
+    }
+
+    indexer.commit()?;
+
+    let metrics = metrics.into_inner();
+
+    // The metrics describe the served index which the candidate does not replace until it is promoted.
+    if !candidate {
+        metrics.write(&dir)?;
+    }
+
+    let datasets = metrics
+        .licenses
+        .iter()
+        .filter(|(name, _)| source_names.is_empty() || source_names.contains(name))
+        .map(|(name, licenses)| (name.clone(), licenses.values().sum()))
+        .collect();
+
+    Ok(datasets)
+}
+
+/// Writes the metrics of this run in the Prometheus text format, using the same terms as the harvest metrics.
+fn write_textfile(
+    path: &Path,
+    duration: Duration,
+    datasets: Option<&Vec<(String, usize)>>,
+) -> Result<()> {
+    let mut buf = String::new();
+
+    let mut gauge = |name: &str, help: &str, samples: &[(Option<&str>, f64)]| {
+        writeln!(buf, "# HELP umwelt_info_indexer_{name} {help}").unwrap();
+        writeln!(buf, "# TYPE umwelt_info_indexer_{name} gauge").unwrap();
+
+        for (source, val) in samples {
+            match source {
+                Some(source) => {
+                    let source = source.replace('\\', "\\\\").replace('"', "\\\"");
+
+                    writeln!(
+                        buf,
+                        "umwelt_info_indexer_{name}{{source=\"{source}\"}} {val}"
+                    )
+                    .unwrap()
+                }
+                None => writeln!(buf, "umwelt_info_indexer_{name} {val}").unwrap(),
+            }
+        }
+    };
+
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?;
+
+    gauge(
+        "last_run_timestamp_seconds",
+        "Time at which the last run finished",
+        &[(None, timestamp.as_secs_f64())],
+    );
+    gauge(
+        "duration_seconds",
+        "Duration of the last run",
+        &[(None, duration.as_secs_f64())],
+    );
+    gauge(
+        "failed",
+        "Whether the last run failed",
+        &[(None, if datasets.is_some() { 0.0 } else { 1.0 })],
+    );
+
+    if let Some(datasets) = datasets {
+        let written = datasets.iter().map(|(_, count)| count).sum::<usize>();
+
+        gauge(
+            "written",
+            "Datasets written into the search index by the last run",
+            &[(None, written as f64)],
+        );
+
+        let samples = datasets
+            .iter()
+            .map(|(source, count)| (Some(source.as_str()), *count as f64))
+            .collect::<Vec<_>>();
+
+        gauge(
+            "source_written",
+            "Datasets written into the search index per source by the last run",
+            &samples,
+        );
+    }
+
+    let mut new_path = path.as_os_str().to_owned();
+    new_path.push(".new");
+
+    // Write atomically so that the collector never observes partial files.
+    write(&new_path, buf)?;
+    rename(&new_path, path)?;
+
+    Ok(())
+}
+
+#[derive(Default)]
+struct Args {
+    source_names: Vec<String>,
+    candidate: bool,
+}
+
+/// Parses either the `--source <name>` arguments used to reindex only some sources
+/// or the `--candidate` argument used to build a candidate index served alongside the current one.
+fn parse_args() -> Result<Args> {
+    let mut val = Args::default();
+
+    let mut args = args().skip(1);
+
+    while let Some(arg) = args.next() {
+        match &*arg {
+            "--source" => val
+                .source_names
+                .push(args.next().ok_or_else(|| anyhow!("Missing source name"))?),
+            "--candidate" => val.candidate = true,
+            _ => return Err(anyhow!("Unknown argument {}", arg)),
+        }
+    }
+
+    ensure!(
+        !val.candidate || val.source_names.is_empty(),
+        "Candidate index always contains all sources"
+    );
+
+    Ok(val)
+}
+
+fn index_source(
+    indexer: &Indexer,
+    metrics: &Mutex<Metrics>,
+    stats: &Stats,
+    sources: &Sources,
+    scan: &Scan,
+    source_id: String,
+    source: Dir,
+) -> Result<()> {
+    let accesses = stats.accesses.get(&source_id);
+
+    let source_info = sources.get(&source_id);
 
-    dir.read_dir("datasets")?
+    let boilerplate = scan.boilerplate.get(&source_id);
+
+    source
+        .entries()?
+        .par_bridge()
+        .try_for_each(|dataset| -> Result<()> {
+            let dataset = dataset?;
+            let dataset_id = decode_id(&dataset.file_name().to_string_lossy()).into_owned();
+
+            let mut dataset = Dataset::read(dataset.open()?)?;
+
+            let canonical = scan
+                .canonical
+                .get(&source_id, &dataset_id)
+                .map(ToOwned::to_owned);
+
+            let accesses = accesses.and_then(|accesses| accesses.get(&dataset_id));
+
+            metrics.lock().record_dataset(&source_id, &dataset);
+
+            if let (Some(description), Some(boilerplate)) = (&mut dataset.description, boilerplate)
+            {
+                let stripped = boilerplate.strip(description);
+
+                if stripped.len() != description.len() {
+                    *description = stripped.to_owned();
+                }
+            }
+
+            indexer.add_document(
+                source_id.clone(),
+                dataset_id,
+                dataset,
+                *accesses.unwrap_or(&0),
+                source_info,
+                canonical,
+            )?;
+
+            Ok(())
+        })
+}
+
+/// What is learned by reading all datasets before indexing any of them
+struct Scan {
+    canonical: Canonical,
+    /// Boilerplate in the descriptions of each indexed source
+    boilerplate: HashMap<String, Boilerplate>,
+}
+
+/// Reads all datasets once before indexing them to detect duplicates across all sources
+/// and the boilerplate in the descriptions of each of the given sources, or all of them if none are given.
+///
+/// Also queues likely duplicates for review while applying the decisions of previous reviews.
+fn scan_datasets(
+    dir: &Dir,
+    datasets: &Dir,
+    sources: &Sources,
+    source_names: &[String],
+) -> Result<Scan> {
+    let duplicates = Mutex::new(Duplicates::default());
+    let boilerplate = Mutex::new(HashMap::new());
+
+    datasets
+        .entries()?
         .par_bridge()
         .try_for_each(|source| -> Result<()> {
             let source = source?;
             let source_id = source.file_name().into_string().unwrap();
 
-            let accesses = stats.accesses.get(&source_id);
+            let trust = sources.trust(&source_id);
+
+            let indexed = source_names.is_empty() || source_names.contains(&source_id);
+            let descriptions = Mutex::new(Vec::new());
 
             source
                 .open_dir()?
@@ -40,28 +336,57 @@ fn main() -> Result<()> {
                 .par_bridge()
                 .try_for_each(|dataset| -> Result<()> {
                     let dataset = dataset?;
-                    let dataset_id = dataset.file_name().into_string().unwrap();
+                    let dataset_id = decode_id(&dataset.file_name().to_string_lossy()).into_owned();
 
                     let dataset = Dataset::read(dataset.open()?)?;
 
-                    let accesses = accesses.and_then(|accesses| accesses.get(&dataset_id));
-
-                    metrics.lock().record_dataset(&source_id, &dataset);
+                    duplicates
+                        .lock()
+                        .record(&source_id, &dataset_id, &dataset, trust);
 
-                    indexer.add_document(
-                        source_id.clone(),
-                        dataset_id,
-                        dataset,
-                        *accesses.unwrap_or(&0),
-                    )?;
+                    if indexed {
+                        if let Some(description) = dataset.description {
+                            descriptions.lock().push(description);
+                        }
+                    }
 
                     Ok(())
-                })
+                })?;
+
+            if indexed {
+                let descriptions = descriptions.into_inner();
+
+                let detected = Boilerplate::detect(descriptions.iter().map(String::as_str));
+
+                if !detected.is_empty() {
+                    tracing::debug!("Detected boilerplate in descriptions of {}", source_id);
+                }
+
+                boilerplate.lock().insert(source_id, detected);
+            }
+
+            Ok(())
         })?;
 
-    indexer.commit()?;
+    let duplicates = duplicates.into_inner();
 
-    metrics.get_mut().write(&dir)?;
+    let reviews = Reviews::read(dir)?;
 
-    Ok(())
+    let candidates = duplicates.candidates(&reviews);
+
+    tracing::debug!(
+        "Queued {} likely duplicates for review",
+        candidates.pairs.len()
+    );
+
+    candidates.write(dir)?;
+
+    let canonical = duplicates.canonical(&reviews);
+
+    tracing::debug!("Detected {} duplicated datasets", canonical.len());
+
+    Ok(Scan {
+        canonical,
+        boilerplate: boilerplate.into_inner(),
+    })
 }
@@ -0,0 +1,443 @@
+use std::collections::{BTreeMap, HashSet};
+use std::env::{args, current_exe, var_os};
+use std::fmt::Write as _;
+use std::fs::{rename, write};
+use std::io::{ErrorKind, Write as _};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, ensure, Context, Result};
+use cap_std::{ambient_authority, fs::Dir};
+use serde::{Deserialize, Serialize};
+use serde_json::{from_slice, to_vec_pretty};
+use toml::from_str;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+
+use umwelt_info_core::data_path_from_env;
+
+fn main() -> Result<()> {
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::EnvFilter::from_default_env())
+        .with(tracing_subscriber::fmt::layer())
+        .init();
+
+    let resume = parse_args()?;
+
+    let data_path = data_path_from_env();
+
+    let dir = Dir::open_ambient_dir(data_path, ambient_authority())?;
+
+    let config = Config::read(&dir)?;
+
+    let stages = order(&config.stages)?;
+
+    let mut checkpoint = if resume {
+        Checkpoint::read(&dir)?
+    } else {
+        Checkpoint::new()?
+    };
+
+    let start = Instant::now();
+
+    for stage in &stages {
+        let _span = tracing::info_span!("stage", name = %stage.name).entered();
+
+        if checkpoint.succeeded(&stage.name) {
+            tracing::info!("Skipping stage which already succeeded");
+            continue;
+        }
+
+        let failed_dep = stage.after.iter().find(|dep| !checkpoint.succeeded(dep));
+
+        let outcome = if let Some(dep) = failed_dep {
+            tracing::warn!("Skipping stage as stage {} did not succeed", dep);
+
+            Outcome {
+                success: false,
+                duration_secs: 0.0,
+                message: Some(format!("Stage {dep} did not succeed")),
+            }
+        } else {
+            tracing::info!("Starting stage");
+
+            let stage_start = Instant::now();
+
+            let res = stage.run();
+
+            let duration_secs = stage_start.elapsed().as_secs_f64();
+
+            match res {
+                Ok(()) => {
+                    tracing::info!("Finished stage after {:.0}s", duration_secs);
+
+                    Outcome {
+                        success: true,
+                        duration_secs,
+                        message: None,
+                    }
+                }
+                Err(err) => {
+                    tracing::error!("{:#}", err);
+
+                    Outcome {
+                        success: false,
+                        duration_secs,
+                        message: Some(format!("{:#}", err)),
+                    }
+                }
+            }
+        };
+
+        checkpoint.stages.insert(stage.name.clone(), outcome);
+        checkpoint.write(&dir)?;
+    }
+
+    let summary = checkpoint.summary(&stages, start.elapsed().as_secs_f64());
+
+    for line in summary.lines() {
+        tracing::info!("{}", line);
+    }
+
+    // The textfile is consumed by the textfile collector of the Prometheus node exporter.
+    if let Some(path) = var_os("PIPELINE_METRICS_TEXTFILE") {
+        if let Err(err) = write_textfile(Path::new(&path), &stages, &checkpoint) {
+            tracing::error!("Failed to write metrics textfile: {:#}", err);
+        }
+    }
+
+    if let Err(err) = notify(&config.notify, &summary) {
+        tracing::error!("Failed to send notification: {:#}", err);
+    }
+
+    let failed = stages
+        .iter()
+        .filter(|stage| !checkpoint.succeeded(&stage.name))
+        .count();
+
+    ensure!(
+        failed == 0,
+        "{} out of {} stages did not succeed",
+        failed,
+        stages.len()
+    );
+
+    Ok(())
+}
+
+/// Parses the optional `--resume` argument which continues the last run, skipping the stages which already succeeded.
+fn parse_args() -> Result<bool> {
+    let mut args = args().skip(1);
+
+    let resume = match args.next().as_deref() {
+        None => false,
+        Some("--resume") => true,
+        Some(arg) => return Err(anyhow!("Unknown argument {}", arg)),
+    };
+
+    ensure!(args.next().is_none(), "Too many arguments");
+
+    Ok(resume)
+}
+
+/// Stages of the pipeline read from `$DATA_PATH/pipeline.toml`, defaulting to harvesting followed by indexing
+#[derive(Deserialize)]
+struct Config {
+    #[serde(default = "default_stages")]
+    stages: Vec<Stage>,
+    /// Command receiving the summary of each run via its standard input, e.g. `["mail", "-s", "umwelt.info", "ops@example.org"]`
+    #[serde(default)]
+    notify: Vec<String>,
+}
+
+impl Config {
+    fn read(dir: &Dir) -> Result<Self> {
+        let text = match dir.read_to_string("pipeline.toml") {
+            Ok(text) => text,
+            Err(err) if err.kind() == ErrorKind::NotFound => String::new(),
+            Err(err) => return Err(err.into()),
+        };
+
+        let val = from_str::<Self>(&text).context("Failed to parse pipeline.toml")?;
+
+        Ok(val)
+    }
+}
+
+fn default_stages() -> Vec<Stage> {
+    vec![
+        Stage {
+            name: "harvester".to_owned(),
+            command: Vec::new(),
+            after: Vec::new(),
+        },
+        Stage {
+            name: "indexer".to_owned(),
+            command: Vec::new(),
+            after: vec!["harvester".to_owned()],
+        },
+    ]
+}
+
+#[derive(Deserialize)]
+struct Stage {
+    name: String,
+    /// Program and arguments, defaulting to the binary named like the stage
+    #[serde(default)]
+    command: Vec<String>,
+    /// Stages which have to succeed before this one runs
+    #[serde(default)]
+    after: Vec<String>,
+}
+
+impl Stage {
+    fn run(&self) -> Result<()> {
+        let (program, args) = match self.command.split_first() {
+            Some((program, args)) => (program, args),
+            None => (&self.name, &[][..]),
+        };
+
+        let status = Command::new(locate(program))
+            .args(args)
+            .status()
+            .with_context(|| format!("Failed to start {}", program))?;
+
+        ensure!(status.success(), "{} exited with {}", program, status);
+
+        Ok(())
+    }
+}
+
+/// Prefers the binaries installed next to this one over those found via `$PATH`.
+fn locate(program: &str) -> PathBuf {
+    if !program.contains('/') {
+        if let Some(dir) = current_exe().ok().as_deref().and_then(Path::parent) {
+            let path = dir.join(program);
+
+            if path.exists() {
+                return path;
+            }
+        }
+    }
+
+    program.into()
+}
+
+/// Orders the stages so that each one follows those it depends on, keeping the configured order otherwise.
+fn order(stages: &[Stage]) -> Result<Vec<&Stage>> {
+    let mut names = HashSet::new();
+
+    for stage in stages {
+        ensure!(names.insert(&*stage.name), "Duplicate stage {}", stage.name);
+    }
+
+    for stage in stages {
+        for dep in &stage.after {
+            ensure!(
+                names.contains(&**dep),
+                "Stage {} depends on unknown stage {}",
+                stage.name,
+                dep
+            );
+        }
+    }
+
+    let mut ordered = Vec::<&Stage>::with_capacity(stages.len());
+
+    while ordered.len() != stages.len() {
+        let next = stages.iter().find(|stage| {
+            !ordered.iter().any(|done| done.name == stage.name)
+                && stage
+                    .after
+                    .iter()
+                    .all(|dep| ordered.iter().any(|done| done.name == *dep))
+        });
+
+        match next {
+            Some(stage) => ordered.push(stage),
+            None => return Err(anyhow!("Dependencies between stages form a cycle")),
+        }
+    }
+
+    Ok(ordered)
+}
+
+/// Outcomes of the stages of the current run stored in `$DATA_PATH/pipeline.json` after each stage
+#[derive(Deserialize, Serialize)]
+struct Checkpoint {
+    /// Start of the run in seconds since the Unix epoch
+    start: u64,
+    stages: BTreeMap<String, Outcome>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct Outcome {
+    success: bool,
+    duration_secs: f64,
+    message: Option<String>,
+}
+
+impl Checkpoint {
+    fn new() -> Result<Self> {
+        Ok(Self {
+            start: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+            stages: BTreeMap::new(),
+        })
+    }
+
+    fn read(dir: &Dir) -> Result<Self> {
+        match dir.read("pipeline.json") {
+            Ok(buf) => Ok(from_slice(&buf).context("Failed to parse pipeline.json")?),
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                tracing::warn!("No checkpoint to resume from, starting a new run");
+
+                Self::new()
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn write(&self, dir: &Dir) -> Result<()> {
+        let buf = to_vec_pretty(self)?;
+
+        dir.write("pipeline.json.new", buf)?;
+        dir.rename("pipeline.json.new", dir, "pipeline.json")?;
+
+        Ok(())
+    }
+
+    fn succeeded(&self, name: &str) -> bool {
+        self.stages
+            .get(name)
+            .map_or(false, |outcome| outcome.success)
+    }
+
+    fn summary(&self, stages: &[&Stage], duration_secs: f64) -> String {
+        let succeeded = stages
+            .iter()
+            .filter(|stage| self.succeeded(&stage.name))
+            .count();
+
+        let mut buf = format!(
+            "Pipeline finished after {:.0}s with {} out of {} stages succeeded",
+            duration_secs,
+            succeeded,
+            stages.len()
+        );
+
+        for stage in stages {
+            match self.stages.get(&stage.name) {
+                Some(outcome) if outcome.success => write!(
+                    buf,
+                    "\n{}: succeeded after {:.0}s",
+                    stage.name, outcome.duration_secs
+                ),
+                Some(outcome) => write!(
+                    buf,
+                    "\n{}: failed after {:.0}s: {}",
+                    stage.name,
+                    outcome.duration_secs,
+                    outcome.message.as_deref().unwrap_or_default()
+                ),
+                None => write!(buf, "\n{}: not run", stage.name),
+            }
+            .unwrap();
+        }
+
+        buf
+    }
+}
+
+/// Writes the outcomes of the stages in the Prometheus text format.
+fn write_textfile(path: &Path, stages: &[&Stage], checkpoint: &Checkpoint) -> Result<()> {
+    let mut buf = String::new();
+
+    let mut gauge = |name: &str, help: &str, samples: &[(Option<&str>, f64)]| {
+        writeln!(buf, "# HELP umwelt_info_pipeline_{name} {help}").unwrap();
+        writeln!(buf, "# TYPE umwelt_info_pipeline_{name} gauge").unwrap();
+
+        for (stage, val) in samples {
+            match stage {
+                Some(stage) => writeln!(
+                    buf,
+                    "umwelt_info_pipeline_{name}{{stage=\"{stage}\"}} {val}"
+                )
+                .unwrap(),
+                None => writeln!(buf, "umwelt_info_pipeline_{name} {val}").unwrap(),
+            }
+        }
+    };
+
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?;
+
+    gauge(
+        "last_run_timestamp_seconds",
+        "Time at which the last run finished",
+        &[(None, timestamp.as_secs_f64())],
+    );
+
+    let durations = stages
+        .iter()
+        .filter_map(|stage| {
+            let outcome = checkpoint.stages.get(&stage.name)?;
+
+            Some((Some(stage.name.as_str()), outcome.duration_secs))
+        })
+        .collect::<Vec<_>>();
+
+    gauge(
+        "stage_duration_seconds",
+        "Duration of each stage of the last run",
+        &durations,
+    );
+
+    let failures = stages
+        .iter()
+        .map(|stage| {
+            let failed = if checkpoint.succeeded(&stage.name) {
+                0.0
+            } else {
+                1.0
+            };
+
+            (Some(stage.name.as_str()), failed)
+        })
+        .collect::<Vec<_>>();
+
+    gauge(
+        "stage_failed",
+        "Whether each stage of the last run did not succeed",
+        &failures,
+    );
+
+    let mut new_path = path.as_os_str().to_owned();
+    new_path.push(".new");
+
+    // Write atomically so that the collector never observes partial files.
+    write(&new_path, buf)?;
+    rename(&new_path, path)?;
+
+    Ok(())
+}
+
+fn notify(command: &[String], summary: &str) -> Result<()> {
+    let Some((program, args)) = command.split_first() else {
+        return Ok(());
+    };
+
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to start {}", program))?;
+
+    let mut stdin = child.stdin.take().unwrap();
+    writeln!(stdin, "{}", summary)?;
+    drop(stdin);
+
+    let status = child.wait()?;
+
+    ensure!(status.success(), "{} exited with {}", program, status);
+
+    Ok(())
+}
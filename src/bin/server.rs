@@ -2,7 +2,14 @@ use std::env::var;
 use std::net::SocketAddr;
 
 use anyhow::Error;
-use axum::{extract::Extension, response::Redirect, routing::get, Router, Server};
+use axum::{
+    extract::Extension,
+    http::header::{HeaderName, HeaderValue},
+    middleware::from_fn,
+    response::Redirect,
+    routing::{get, post},
+    Router, Server,
+};
 use cap_std::{ambient_authority, fs::Dir};
 use parking_lot::Mutex;
 use tokio::{
@@ -12,13 +19,26 @@ use tokio::{
 use tower::{
     limit::GlobalConcurrencyLimitLayer, load_shed::LoadShedLayer, make::Shared, ServiceBuilder,
 };
-use tower_http::trace::{DefaultMakeSpan, TraceLayer};
+use tower_http::{
+    set_header::SetResponseHeaderLayer,
+    trace::{DefaultMakeSpan, TraceLayer},
+};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
-use umwelt_info::{
-    data_path_from_env,
-    index::Searcher,
-    server::{dataset::dataset, metrics::metrics, search::search, stats::Stats},
+use umwelt_info_core::{data_path_from_env, stats::Stats};
+use umwelt_info_server::{
+    api::{api_root, openapi},
+    badge::{badge, badge_svg, Badges},
+    dataset::dataset,
+    duplicates::{duplicates, review},
+    indexes::{compare, promote, Indexes},
+    maintenance::{maintenance, Maintenance},
+    metrics::metrics,
+    search::{search, search_json},
+    sources::sources,
+    stats::AccessGuard,
+    version::{version, VERSION},
+    well_known::{data_catalog, security_txt},
 };
 
 #[tokio::main]
@@ -40,7 +60,27 @@ async fn main() -> Result<(), Error> {
         .parse::<usize>()
         .expect("Environment variable REQUEST_LIMIT invalid");
 
-    let searcher = &*Box::leak(Box::new(Searcher::open(&data_path)?));
+    let u64_var = |name, default| {
+        var(name).map_or(default, |val| {
+            val.parse::<u64>()
+                .unwrap_or_else(|_| panic!("Environment variable {name} invalid"))
+        })
+    };
+
+    // Access counts are suppressed below ten and rounded to tens unless configured otherwise.
+    let access_guard = AccessGuard::new(
+        u64_var("ACCESSES_MIN_COUNT", 10),
+        u64_var("ACCESSES_GRANULARITY", 10),
+    );
+
+    // Searches are only routed to a candidate index if one was built by `indexer --candidate`.
+    let candidate_share = u64_var("CANDIDATE_SHARE", 0);
+    assert!(
+        candidate_share <= 100,
+        "Environment variable CANDIDATE_SHARE invalid"
+    );
+
+    let indexes = &*Box::leak(Box::new(Indexes::open(&data_path, candidate_share)?));
 
     let dir = &*Box::leak(Box::new(Dir::open_ambient_dir(
         data_path,
@@ -49,16 +89,41 @@ async fn main() -> Result<(), Error> {
 
     let stats = &*Box::leak(Box::new(Mutex::new(Stats::read(dir)?)));
 
+    let badges = &*Box::leak(Box::<Badges>::default());
+
+    let maintenance_mode = &*Box::leak(Box::<Maintenance>::default());
+    maintenance_mode.refresh(dir);
+
+    spawn(refresh_maintenance(dir, maintenance_mode));
+
     spawn(write_stats(dir, stats));
 
     let router = Router::new()
         .route("/", get(|| async { Redirect::permanent("/search") }))
-        .route("/search", get(search))
+        .route("/api/v1", get(api_root))
+        .route("/api/v1/openapi.json", get(openapi))
+        .route("/api/v1/search", get(search).post(search_json))
+        .route("/search", get(search).post(search_json))
         .route("/dataset/:source/:id", get(dataset))
+        .route("/sources", get(sources))
         .route("/metrics", get(metrics))
-        .layer(Extension(searcher))
+        .route("/version", get(version))
+        .route("/badge/:source", get(badge))
+        .route("/badge/:source/svg", get(badge_svg))
+        .route("/admin/duplicates", get(duplicates).post(review))
+        .route("/admin/indexes", get(compare))
+        .route("/admin/indexes/promote", post(promote))
+        .route("/.well-known/security.txt", get(security_txt))
+        .route("/.well-known/data-catalog", get(data_catalog))
+        .route("/.well-known/dcat.json", get(data_catalog))
+        // Must be added before the extensions to be able to access them.
+        .layer(from_fn(maintenance))
+        .layer(Extension(indexes))
         .layer(Extension(dir))
-        .layer(Extension(stats));
+        .layer(Extension(stats))
+        .layer(Extension(badges))
+        .layer(Extension(maintenance_mode))
+        .layer(Extension(access_guard));
 
     let make_service = Shared::new(
         ServiceBuilder::new()
@@ -68,6 +133,10 @@ async fn main() -> Result<(), Error> {
                 TraceLayer::new_for_http()
                     .make_span_with(DefaultMakeSpan::default().include_headers(true)),
             )
+            .layer(SetResponseHeaderLayer::overriding(
+                HeaderName::from_static("x-umweltinfo-version"),
+                HeaderValue::from_static(VERSION),
+            ))
             .service(router),
     );
 
@@ -96,3 +165,19 @@ async fn write_stats(dir: &'static Dir, stats: &'static Mutex<Stats>) {
         .unwrap();
     }
 }
+
+async fn refresh_maintenance(dir: &'static Dir, maintenance: &'static Maintenance) {
+    let mut interval = interval_at(
+        Instant::now() + Duration::from_secs(10),
+        Duration::from_secs(10),
+    );
+    interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
+    loop {
+        interval.tick().await;
+
+        spawn_blocking(move || maintenance.refresh(dir))
+            .await
+            .unwrap();
+    }
+}
@@ -1,8 +1,18 @@
+use std::collections::{BTreeSet, HashSet};
 use std::env::args;
-use std::fs::remove_dir_all;
+use std::fs::{copy, create_dir_all, read, read_dir, read_to_string, remove_dir_all, write};
+use std::hash::Hasher;
+use std::path::Path;
 use std::process::Command;
+use std::thread::sleep;
+use std::time::{Duration, SystemTime};
 
-use anyhow::{anyhow, ensure, Result};
+use anyhow::{anyhow, ensure, Context, Result};
+use serde_json::to_string_pretty;
+use siphasher::sip128::{Hasher128, SipHasher};
+use toml::{value::Table, Value};
+
+use umwelt_info_core::{dataset::Dataset, decode_id, encode_id, index::Searcher, validate_name};
 
 fn main() -> Result<()> {
     match args().nth(1).as_deref() {
@@ -11,6 +21,10 @@ fn main() -> Result<()> {
         Some("harvester") => harvester(),
         Some("indexer") => indexer(),
         Some("server") => server(),
+        Some("watch") => watch(),
+        Some("record-fixtures") => record_fixtures(),
+        Some("cat-dataset") => cat_dataset(),
+        Some("fsck") => fsck(),
         Some(name) => Err(anyhow!("Unknown task {}", name)),
     }
 }
@@ -18,29 +32,53 @@ fn main() -> Result<()> {
 fn default() -> Result<()> {
     cargo("Rustfmt", ["fmt"], [])?;
 
-    cargo("Clippy", ["clippy", "--all-targets"], [])?;
+    cargo("Clippy", ["clippy", "--workspace", "--all-targets"], [])?;
 
-    cargo("Tests", ["test"], [])?;
+    cargo("Tests", ["test", "--workspace"], [])?;
 
     Ok(())
 }
 
 fn doc() -> Result<()> {
-    cargo("Rustdoc", ["doc", "--document-private-items"], [])?;
+    cargo(
+        "Rustdoc",
+        ["doc", "--workspace", "--document-private-items"],
+        [],
+    )?;
 
-    println!("Documentation built at target/doc/umwelt_info/index.html");
+    println!("Documentation built at target/doc/umwelt_info_core/index.html");
 
     Ok(())
 }
 
+const HARVESTER_ENVS: &[(&str, &str)] = &[
+    ("DATA_PATH", "data"),
+    (
+        "RUST_LOG",
+        "info,umwelt_info_core=debug,umwelt_info_harvester=debug,harvester=debug",
+    ),
+];
+
+const INDEXER_ENVS: &[(&str, &str)] = &[
+    ("DATA_PATH", "data"),
+    ("RUST_LOG", "info,umwelt_info_core=debug,indexer=debug"),
+];
+
+const SERVER_ENVS: &[(&str, &str)] = &[
+    ("DATA_PATH", "data"),
+    ("BIND_ADDR", "127.0.0.1:8081"),
+    ("REQUEST_LIMIT", "32"),
+    (
+        "RUST_LOG",
+        "info,umwelt_info_core=debug,umwelt_info_server=debug,server=debug",
+    ),
+];
+
 fn harvester() -> Result<()> {
     cargo(
         "Harvester",
         ["run", "--bin", "harvester"],
-        [
-            ("DATA_PATH", "data"),
-            ("RUST_LOG", "info,umwelt_info=debug,harvester=debug"),
-        ],
+        HARVESTER_ENVS.iter().copied(),
     )?;
 
     indexer()?;
@@ -54,10 +92,7 @@ fn indexer() -> Result<()> {
     cargo(
         "Indexer",
         ["run", "--bin", "indexer"],
-        [
-            ("DATA_PATH", "data"),
-            ("RUST_LOG", "info,umwelt_info=debug,indexer=debug"),
-        ],
+        INDEXER_ENVS.iter().copied(),
     )?;
 
     Ok(())
@@ -67,13 +102,299 @@ fn server() -> Result<()> {
     cargo(
         "Server",
         ["run", "--bin", "server"],
+        SERVER_ENVS.iter().copied(),
+    )?;
+
+    Ok(())
+}
+
+/// Rebuilds and restarts the given program whenever the sources or templates change.
+fn watch() -> Result<()> {
+    let (name, envs) = match args().nth(2).as_deref() {
+        Some("harvester") => ("harvester", HARVESTER_ENVS),
+        Some("indexer") => ("indexer", INDEXER_ENVS),
+        Some("server") => ("server", SERVER_ENVS),
+        _ => {
+            return Err(anyhow!(
+                "Usage: cargo xtask watch <harvester|indexer|server>"
+            ))
+        }
+    };
+
+    let mut last_modified = sources_modified()?;
+
+    loop {
+        let child = match cargo("Build", ["build", "--bin", name], []) {
+            Ok(()) => Some(
+                Command::new(Path::new("target/debug").join(name))
+                    .envs(envs.iter().copied())
+                    .spawn()?,
+            ),
+            Err(err) => {
+                eprintln!("{:#}", err);
+
+                None
+            }
+        };
+
+        loop {
+            sleep(Duration::from_secs(1));
+
+            let modified = sources_modified()?;
+
+            if modified > last_modified {
+                last_modified = modified;
+                break;
+            }
+        }
+
+        if let Some(mut child) = child {
+            // The program might have finished already, e.g. the harvester after a run.
+            let _ = child.kill();
+            child.wait()?;
+        }
+
+        eprintln!("Restarting {name} after changes");
+    }
+}
+
+/// Yields the latest modification time of all sources and templates.
+fn sources_modified() -> Result<SystemTime> {
+    fn visit(path: &Path, last_modified: &mut SystemTime) -> Result<()> {
+        for entry in read_dir(path)? {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+
+            if metadata.is_dir() {
+                visit(&entry.path(), last_modified)?;
+            } else {
+                *last_modified = (*last_modified).max(metadata.modified()?);
+            }
+        }
+
+        Ok(())
+    }
+
+    let mut last_modified = SystemTime::UNIX_EPOCH;
+
+    for path in ["src", "templates", "crates"] {
+        visit(Path::new(path), &mut last_modified)?;
+    }
+
+    Ok(last_modified)
+}
+
+/// Harvests a single source with a limited number of requests and keeps the recorded responses as test fixtures.
+///
+/// The responses can be replayed using `$REPLAY_RESPONSES` after copying them into `responses` of a data directory.
+fn record_fixtures() -> Result<()> {
+    let (name, max_requests) = match (args().nth(2), args().nth(3)) {
+        (Some(name), max_requests) => (
+            name,
+            max_requests.as_deref().unwrap_or("100").parse::<i64>()?,
+        ),
+        _ => {
+            return Err(anyhow!(
+                "Usage: cargo xtask record-fixtures <source> [max requests]"
+            ))
+        }
+    };
+
+    validate_name(&name)?;
+
+    let config = read_to_string("data/harvester.toml")
+        .context("Failed to read data/harvester.toml")?
+        .parse::<Value>()?;
+
+    let source = config
+        .get("sources")
+        .and_then(Value::as_array)
+        .and_then(|sources| {
+            sources
+                .iter()
+                .find(|source| source.get("name").and_then(Value::as_str) == Some(&name))
+        })
+        .ok_or_else(|| anyhow!("Unknown source {}", name))?;
+
+    let domain = source
+        .get("url")
+        .and_then(Value::as_str)
+        .and_then(|url| url.split_once("://"))
+        .and_then(|(_scheme, rest)| rest.split(['/', ':', '?']).next())
+        .ok_or_else(|| anyhow!("Source {} has no URL", name))?;
+
+    // The request budget of the host bounds the harvest.
+    let mut host = Table::new();
+    host.insert("domain".to_owned(), Value::String(domain.to_owned()));
+    host.insert("max_requests".to_owned(), Value::Integer(max_requests));
+
+    let mut config = Table::new();
+    config.insert("sources".to_owned(), Value::Array(vec![source.clone()]));
+    config.insert("hosts".to_owned(), Value::Array(vec![Value::Table(host)]));
+
+    let data_path = Path::new("target/record-fixtures");
+
+    let _ = remove_dir_all(data_path);
+    create_dir_all(data_path)?;
+
+    write(
+        data_path.join("harvester.toml"),
+        toml::to_string(&Value::Table(config))?,
+    )?;
+
+    let res = cargo(
+        "Harvester",
+        ["run", "--bin", "harvester"],
         [
-            ("DATA_PATH", "data"),
-            ("BIND_ADDR", "127.0.0.1:8081"),
-            ("REQUEST_LIMIT", "32"),
-            ("RUST_LOG", "info,umwelt_info=debug,server=debug"),
+            ("DATA_PATH", data_path.to_str().unwrap()),
+            (
+                "RUST_LOG",
+                "info,umwelt_info_harvester=debug,harvester=debug",
+            ),
         ],
-    )?;
+    );
+
+    // Failures are expected once the budget is exhausted.
+    if let Err(err) = res {
+        eprintln!("{:#}", err);
+    }
+
+    let fixtures = Path::new("crates/harvester/tests/fixtures").join(&name);
+
+    let _ = remove_dir_all(&fixtures);
+    create_dir_all(&fixtures)?;
+
+    let mut count = 0;
+
+    for entry in read_dir(data_path.join("responses"))? {
+        let entry = entry?;
+
+        copy(entry.path(), fixtures.join(entry.file_name()))?;
+
+        count += 1;
+    }
+
+    println!("Recorded {count} responses at {}", fixtures.display());
+
+    Ok(())
+}
+
+/// Prints a stored dataset as JSON together with diagnostics about the file containing it.
+fn cat_dataset() -> Result<()> {
+    let (source, id) = match (args().nth(2), args().nth(3)) {
+        (Some(source), Some(id)) => (source, id),
+        _ => return Err(anyhow!("Usage: cargo xtask cat-dataset <source> <id>")),
+    };
+
+    validate_name(&source)?;
+
+    let path = Path::new("data/datasets")
+        .join(&source)
+        .join(&*encode_id(&id)?);
+
+    let buf = read(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+
+    let mut hasher = SipHasher::new();
+    hasher.write(&buf);
+
+    eprintln!("Path: {}", path.display());
+    eprintln!("Size: {} bytes", buf.len());
+    eprintln!("Hash: {:032x}", hasher.finish128().as_u128());
+
+    match Dataset::decode(&buf) {
+        Ok((dataset, schema)) => {
+            eprintln!("Schema: {:?}", schema);
+
+            println!("{}", to_string_pretty(&dataset)?);
+
+            Ok(())
+        }
+        Err(err) => {
+            match Dataset::decode_partially(&buf) {
+                Some((title, description)) => {
+                    eprintln!("Title: {}", title);
+
+                    if let Some(description) = description {
+                        eprintln!("Description: {}", description);
+                    }
+                }
+                None => eprintln!("Not even title and description could be decoded"),
+            }
+
+            Err(err)
+        }
+    }
+}
+
+/// Cross-checks the documents of the search index against the stored datasets.
+///
+/// With `--repair`, all sources with inconsistencies are reindexed which drops orphaned documents
+/// and adds the missing datasets as documents cannot be deleted individually.
+fn fsck() -> Result<()> {
+    let repair = match args().nth(2).as_deref() {
+        None => false,
+        Some("--repair") => true,
+        Some(_) => return Err(anyhow!("Usage: cargo xtask fsck [--repair]")),
+    };
+
+    let indexed = Searcher::open(Path::new("data"))?
+        .documents()?
+        .into_iter()
+        .collect::<HashSet<_>>();
+
+    let mut stored = HashSet::new();
+
+    for source in read_dir("data/datasets")? {
+        let source = source?;
+        let source_name = source.file_name().to_string_lossy().into_owned();
+
+        for dataset in read_dir(source.path())? {
+            let id = decode_id(&dataset?.file_name().to_string_lossy()).into_owned();
+
+            stored.insert((source_name.clone(), id));
+        }
+    }
+
+    let mut sources = BTreeSet::new();
+
+    let mut orphaned = indexed.difference(&stored).collect::<Vec<_>>();
+    orphaned.sort_unstable();
+
+    for (source, id) in &orphaned {
+        println!("Indexed but not stored: {source}/{id}");
+
+        sources.insert(source.clone());
+    }
+
+    let mut missing = stored.difference(&indexed).collect::<Vec<_>>();
+    missing.sort_unstable();
+
+    for (source, id) in &missing {
+        println!("Stored but not indexed: {source}/{id}");
+
+        sources.insert(source.clone());
+    }
+
+    eprintln!(
+        "{} orphaned documents and {} missing datasets in {} sources",
+        orphaned.len(),
+        missing.len(),
+        sources.len()
+    );
+
+    if !repair {
+        ensure!(sources.is_empty(), "Index and datasets are inconsistent");
+
+        return Ok(());
+    }
+
+    for source in sources {
+        cargo(
+            "Indexer",
+            ["run", "--bin", "indexer", "--", "--source", &source],
+            INDEXER_ENVS.iter().copied(),
+        )?;
+    }
 
     Ok(())
 }